@@ -0,0 +1,160 @@
+//! Module for deciding when a scan should alert the user, and for snoozing alerts.
+//!
+//! A scan alerts (e.g. by flashing the window) when it flags a player who isn't friendly. Alerts can
+//! be snoozed for a while during a long session; snoozing only silences the alert, and the results
+//! keep updating as usual. Users who scan repeatedly can choose to be alerted only when a player
+//! is newly flagged since the previous scan. While the app is minimized, an alert also shows a
+//! desktop notification of who was flagged.
+
+use std::time::{Duration, Instant};
+use crate::detector::{self, ScanInfo};
+use crate::history;
+
+/// The snooze lengths offered, in minutes.
+pub(crate) const SNOOZE_OPTIONS_MINS: [u64; 3] = [15, 30, 60];
+
+/// Whether alerts are snoozed at the given time.
+///
+/// # Arguments
+/// * `snoozed_until` - When the snooze expires, or [`None`] if alerts aren't snoozed.
+/// * `now` - The time to check.
+pub(crate) fn is_snoozed(snoozed_until: Option<Instant>, now: Instant) -> bool {
+    snoozed_until.is_some_and(|snoozed_until| now < snoozed_until)
+}
+
+/// Gets how long remains of the snooze, or [`None`] if alerts aren't snoozed.
+///
+/// # Arguments
+/// * `snoozed_until` - When the snooze expires, or [`None`] if alerts aren't snoozed.
+/// * `now` - The time to check.
+pub(crate) fn snooze_remaining(snoozed_until: Option<Instant>, now: Instant) -> Option<Duration> {
+    snoozed_until
+        .filter(|_| is_snoozed(snoozed_until, now))
+        .map(|snoozed_until| snoozed_until.duration_since(now))
+}
+
+/// Whether the results of a scan warrant an alert: any result at or above the threshold that isn't
+/// a friendly player.
+///
+/// # Arguments
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+pub(crate) fn should_alert(scans: &[ScanInfo], similarity_threshold: u8) -> bool {
+    detector::similar_scans(scans, similarity_threshold)
+        .iter()
+        .any(|scan| scan.friendly.is_none())
+}
+
+/// Describes who a scan flagged for a desktop notification, e.g. "SomeMoron (87%), OtherMoron (72%)".
+/// Friendly players are left out.
+///
+/// # Arguments
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+pub(crate) fn notification_body(scans: &[ScanInfo], similarity_threshold: u8) -> String {
+    detector::similar_scans(scans, similarity_threshold)
+        .iter()
+        .filter(|scan| scan.friendly.is_none())
+        .map(|scan| format!("{} ({}%)", scan.username, scan.similarity))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Shows a desktop notification, blocking until it's closed where the desktop reports that,
+/// returning whether it was clicked. Desktops that don't report clicks return straight away.
+///
+/// # Arguments
+/// * `body` - What the notification says, e.g. from [`notification_body`].
+pub(crate) fn notify(body: &str) -> anyhow::Result<bool> {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .appname("Blitz")
+        .summary("Morons detected")
+        .body(body);
+
+    // Only freedesktop notification servers report clicks.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let mut clicked = false;
+        notification
+            .action("default", "Show")
+            .show()?
+            .wait_for_action(|action| clicked = action == "default");
+        Ok(clicked)
+    }
+
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+        notification.show()?;
+        Ok(false)
+    }
+}
+
+/// Whether the results of a scan flag anyone who wasn't flagged in the previous scan. The first scan
+/// alerts like [`should_alert`], as everyone flagged in it is new.
+///
+/// # Arguments
+/// * `previous` - The results of the previous scan, or [`None`] if this is the first.
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+pub(crate) fn should_alert_new(previous: Option<&[ScanInfo]>, scans: &[ScanInfo], similarity_threshold: u8) -> bool {
+    match previous {
+        Some(previous) => !history::diff_flagged(previous, scans, similarity_threshold).joined.is_empty(),
+        None => should_alert(scans, similarity_threshold),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::tests::scan_info;
+
+    fn friendly_scan_info(username: &str, similarity: u8) -> ScanInfo {
+        ScanInfo { friendly: Some(String::from("Moran")), ..scan_info(username, similarity) }
+    }
+
+    #[test]
+    fn friendly_result_above_threshold_does_not_alert() {
+        let scans = vec![friendly_scan_info("Moron", 100)];
+        assert!(!should_alert(&scans, 70));
+        assert_eq!(notification_body(&scans, 70), "");
+    }
+
+    #[test]
+    fn unfriendly_result_above_threshold_alerts() {
+        let scans = vec![friendly_scan_info("Moron", 100), scan_info("Moron", 80)];
+        assert!(should_alert(&scans, 70));
+        assert_eq!(notification_body(&scans, 70), "Moron (80%)");
+    }
+
+    #[test]
+    fn snooze_lasts_until_it_expires() {
+        let now = Instant::now();
+        let snoozed_until = now + Duration::from_secs(60);
+
+        assert!(is_snoozed(Some(snoozed_until), now));
+        assert_eq!(snooze_remaining(Some(snoozed_until), now), Some(Duration::from_secs(60)));
+        assert!(!is_snoozed(Some(snoozed_until), snoozed_until));
+        assert_eq!(snooze_remaining(Some(snoozed_until), snoozed_until), None);
+    }
+
+    #[test]
+    fn nothing_is_snoozed_without_a_snooze() {
+        let now = Instant::now();
+        assert!(!is_snoozed(None, now));
+        assert_eq!(snooze_remaining(None, now), None);
+    }
+
+    #[test]
+    fn first_scan_alerts_on_anyone_flagged() {
+        assert!(should_alert_new(None, &[scan_info("Moron", 90)], 70));
+        assert!(!should_alert_new(None, &[scan_info("Moron", 50)], 70));
+    }
+
+    #[test]
+    fn later_scans_alert_only_on_newly_flagged_players() {
+        let previous = vec![scan_info("Moron", 90)];
+        assert!(!should_alert_new(Some(&previous), &[scan_info("Moron", 85)], 70));
+        assert!(should_alert_new(Some(&previous), &[scan_info("Moron", 85), scan_info("Newcomer", 80)], 70));
+    }
+}
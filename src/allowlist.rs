@@ -0,0 +1,100 @@
+//! This module provides structures and methods for managing an allowlist of confirmed-good players.
+//!
+//! The [`Allowlist`] struct represents a roster of known-good teammates. Detections that match an
+//! allowlisted username are marked as verified friendly in the results and never raise an alert,
+//! even if they fuzzy-match a moron on the blacklist.
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! use crate::allowlist::Allowlist;
+//! use crate::blacklist::default_prefixes;
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let allowlist_path = std::path::PathBuf::from("allowlist.json");
+//!     let allowlist = Allowlist::load(&allowlist_path)?;
+//!
+//!     if let Some(friend) = allowlist.find("General Trusted Teammate", &default_prefixes()) {
+//!         println!("{friend} is verified friendly.");
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use crate::detector;
+
+/// Allowlist containing the usernames of confirmed-good players.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Allowlist {
+    /// The list of allowlisted usernames.
+    pub usernames: Vec<String>,
+}
+
+impl Allowlist {
+    /// Loads and deserializes an existing [`Allowlist`] JSON file into a new [`Allowlist`], dropping
+    /// any usernames that normalize to one already on the list.
+    ///
+    /// # Arguments
+    /// * `allowlist_path` - A reference to the [`PathBuf`] representing the path to the allowlist file.
+    pub fn load(allowlist_path: &PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(allowlist_path)?;
+        let mut allowlist: Allowlist = serde_json::from_str(&content)?;
+        allowlist.dedup();
+        Ok(allowlist)
+    }
+
+    /// Finds the allowlisted username matching the detected text, comparing normalized forms.
+    ///
+    /// # Arguments
+    /// * `detection_text` - The detected text to look up.
+    /// * `prefixes` - The prefixes (e.g. ranks) stripped during normalization.
+    pub fn find(&self, detection_text: &str, prefixes: &[String]) -> Option<&String> {
+        let detection_text_normalised = detector::normalize(detection_text, prefixes);
+        self.usernames
+            .iter()
+            .find(|username| detector::normalize(username, prefixes) == detection_text_normalised)
+    }
+
+    /// Removes usernames whose normalized form duplicates an earlier entry.
+    fn dedup(&mut self) {
+        let mut seen = std::collections::HashSet::new();
+        self.usernames.retain(|username| seen.insert(detector::normalize(username, &[])));
+    }
+}
+
+impl Default for Allowlist {
+    /// Creates a new [`Allowlist`] that contains an example entry.
+    fn default() -> Self {
+        Allowlist {
+            usernames: vec![String::from("Example Friend #1")],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blacklist::default_prefixes;
+
+    #[test]
+    fn find_matches_the_normalized_name() {
+        let allowlist = Allowlist { usernames: vec![String::from("Trusted Teammate")] };
+        assert_eq!(
+            allowlist.find("General trustedteammate", &default_prefixes()),
+            Some(&String::from("Trusted Teammate"))
+        );
+        assert_eq!(allowlist.find("Someone Else", &default_prefixes()), None);
+    }
+
+    #[test]
+    fn dedup_drops_names_that_normalize_to_an_earlier_one() {
+        let mut allowlist = Allowlist {
+            usernames: vec![String::from("Trusted Teammate"), String::from("trusted teammate")],
+        };
+        allowlist.dedup();
+        assert_eq!(allowlist.usernames, vec![String::from("Trusted Teammate")]);
+    }
+}
@@ -1,44 +1,270 @@
-use crate::detector::{ScanInfo};
-use crate::{detector, paths};
+use crate::blacklist::{self, Blacklist, MatchMode, MergeStrategy, Moron, Severity};
+use crate::config::{AppTheme, Banner, Config, SimilarityDisplay, SimilarityLabels};
+use crate::crop::{self, CropRect, Region};
+use crate::detector::{DetectedName, ScanInfo};
+use crate::history::LeaderboardRow;
+use crate::hotkey::{self, ScanHotkey};
+use crate::paths::Asset;
+use crate::report::ScanReport;
+use crate::selector::RegionSelector;
+use crate::session::SessionStats;
+use crate::watch::{WatchAction, Watcher};
+use crate::windows::{self, WindowIdentity};
+use crate::{alerts, danger, detector, history, logging, paths, report, scan_log, telemetry, watch};
+use dialog::DialogBox;
 use iced::font::Style;
 use iced::font::Weight::{Bold};
 use iced::widget::image::Handle;
+use iced::window::{self, UserAttention};
 use iced::widget::{
-    self, container, text, Column, Row
+    self, container, scrollable, text, Column, Row
 };
 use iced::{
-    color, Alignment, Element, Length, Padding, Sandbox, Theme
+    color, executor, Alignment, Application, Command, Element, Length, Padding, Subscription, Theme
 };
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Whether the light theme is showing, so [`silver`] text stays readable on it.
+static LIGHT_THEME: AtomicBool = AtomicBool::new(false);
+
+/// The reason given to players blacklisted straight from the results, to be edited later.
+const UNFLAGGED_REASON: &str = "Added from a scan - edit this reason";
+
+/// The amount, in pixels, a single press of an arrow button nudges the crop region by.
+const CROP_NUDGE_STEP: i32 = 5;
 
 pub(crate) struct BlitzApp {
+    config: Config,
+    view: View,
     error: Option<String>,
     scans: Vec<ScanInfo>,
+    detected_names: Vec<DetectedName>,
+    previous_scans: Option<Vec<ScanInfo>>,
+    cleared_scans: Option<Vec<ScanInfo>>,
+    last_report: Option<ScanReport>,
     done_initial_scan: bool,
+    show_logs: bool,
+    logs_paused: bool,
+    paused_logs: Vec<String>,
+    preview: Option<PreviewCapture>,
+    session_region: Option<Region>,
+    leaderboard: Vec<LeaderboardRow>,
+    morons: Vec<Moron>,
+    watcher: Watcher,
+    last_scan_finished: Option<Instant>,
+    snoozed_until: Option<Instant>,
+    scanning: bool,
+    asset_statuses: Vec<(Asset, String)>,
+    runtime: tokio::runtime::Handle,
+    banner: Option<Handle>,
+    session_stats: SessionStats,
+    last_match: Option<Instant>,
+    scan_hotkey: Option<ScanHotkey>,
+    moron_draft: MoronDraft,
+    theme: Theme,
+}
+
+/// The moron being typed into the blacklist panel, before it's added.
+#[derive(Debug, Clone, Default)]
+struct MoronDraft {
+    username: String,
+    reason: String,
+    error: Option<String>,
+}
+
+/// A capture of the RISK window, ready to display in the preview.
+#[derive(Debug, Clone)]
+struct PreviewCapture {
+    handle: Handle,
+    width: u32,
+    height: u32,
+    content: CropRect,
+}
+
+/// How the results of a scan are listed, as configured.
+#[derive(Debug, Clone, Copy)]
+struct ResultsDisplay<'a> {
+    similarity_threshold: u8,
+    max_results: usize,
+    similarity_display: SimilarityDisplay,
+    similarity_labels: &'a SimilarityLabels,
+    min_read_quality: Option<f32>,
+}
+
+impl<'a> ResultsDisplay<'a> {
+    /// Gets how the results are listed from the config.
+    ///
+    /// # Arguments
+    /// * `config` - A reference to the [`Config`] holding the display settings.
+    fn from_config(config: &'a Config) -> Self {
+        Self {
+            similarity_threshold: config.similarity_threshold,
+            max_results: config.max_results,
+            similarity_display: config.similarity_display,
+            similarity_labels: &config.similarity_labels,
+            min_read_quality: config.min_read_quality,
+        }
+    }
+}
+
+/// The view shown beneath the button row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum View {
+    Scan,
+    Preview,
+    Leaderboard,
+    Blacklist,
+    Session,
+    Maintenance,
+}
+
+impl View {
+    /// Every view, in the order they are listed in the view picker.
+    const ALL: [View; 6] = [
+        View::Scan,
+        View::Preview,
+        View::Leaderboard,
+        View::Blacklist,
+        View::Session,
+        View::Maintenance,
+    ];
+}
+
+impl std::fmt::Display for View {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            View::Scan => "Results",
+            View::Preview => "Preview",
+            View::Leaderboard => "Leaderboard",
+            View::Blacklist => "Blacklist",
+            View::Session => "Session",
+            View::Maintenance => "Maintenance",
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
 pub(crate) enum BlitzMessage {
     OpenBlacklistFIle,
     OpenSupportUrl,
-    ScanRisk
+    OpenEvidence(String),
+    RemoveMoron(String),
+    DraftUsernameChanged(String),
+    DraftReasonChanged(String),
+    AddMoron,
+    BlacklistDetected(String),
+    ImportBlacklistCsv,
+    ExportBlacklistCsv,
+    SyncBlacklist,
+    SyncFinished(Result<usize, String>),
+    ScanRisk,
+    ScanCompleted(Result<detector::ScanResult, String>),
+    DemoScan,
+    DemoScanCompleted(Result<detector::ScanResult, String>),
+    ToggleLogs,
+    ToggleLogsPaused,
+    LogsTick,
+    ViewSelected(View),
+    CapturePreview,
+    NudgeCrop(i32, i32),
+    ResetCropOffsets,
+    RegionSelected(Region),
+    SaveRegion,
+    ClearRegion,
+    ExportReport,
+    ExportLeaderboard,
+    CopyMarkdown,
+    CopyResults,
+    ResetSessionStats,
+    ThresholdChanged(u8),
+    ThresholdReleased,
+    ToggleWatch,
+    ThemeSelected(AppTheme),
+    WatchTick(Instant),
+    ToggleAutoScan,
+    HotkeyTick,
+    WindowMinimized(Option<bool>),
+    NotificationClosed(bool),
+    AutoScanTick(Instant),
+    Snooze(u64),
+    Unsnooze,
+    SnoozeTick(Instant),
+    AutoClearTick(Instant),
+    Redownload(Asset),
+    RedownloadFinished(Asset, Result<(), String>),
 }
 
-impl Sandbox for BlitzApp {
+impl Application for BlitzApp {
+    type Executor = executor::Default;
     type Message = BlitzMessage;
+    type Theme = Theme;
+    type Flags = ();
 
-    fn new() -> Self {
-        Self {
-            error: None,
+    fn new(_flags: ()) -> (Self, Command<BlitzMessage>) {
+        let (config, mut error) = match load_config() {
+            Ok(config) => (config, None),
+            Err(err) => (Config::default(), Some(err)),
+        };
+
+        if let Some(auto_export) = &config.auto_export {
+            if let Err(err) = scan_log::validate_writable(&auto_export.path) {
+                error = Some(format!("Config Error: auto-export: {err}"));
+            }
+        }
+
+        let watcher = create_watcher(&config);
+        let banner = load_banner(&config.banner);
+        let config_theme = config.theme;
+        let scan_hotkey = match config.scan_hotkey.as_deref().map(ScanHotkey::register) {
+            Some(Ok(scan_hotkey)) => Some(scan_hotkey),
+            Some(Err(err)) => {
+                log::warn!("{err}");
+                error = error.or(Some(format!("Hotkey Error: {err}")));
+                None
+            }
+            None => None,
+        };
+        let app = Self {
+            config,
+            view: View::Scan,
+            error,
             scans: Vec::new(),
+            detected_names: Vec::new(),
+            previous_scans: None,
+            cleared_scans: None,
+            last_report: None,
             done_initial_scan: false,
-        }
+            show_logs: false,
+            logs_paused: false,
+            paused_logs: Vec::new(),
+            preview: None,
+            session_region: None,
+            leaderboard: Vec::new(),
+            morons: Vec::new(),
+            watcher,
+            last_scan_finished: None,
+            snoozed_until: None,
+            scanning: false,
+            asset_statuses: Vec::new(),
+            // Downloads use reqwest, which needs the Tokio runtime the app was started in.
+            runtime: tokio::runtime::Handle::current(),
+            banner,
+            session_stats: SessionStats::default(),
+            last_match: None,
+            scan_hotkey,
+            moron_draft: MoronDraft::default(),
+            theme: resolve_theme(config_theme),
+        };
+
+        (app, Command::none())
     }
 
     fn title(&self) -> String {
-        String::from("Blitz - The RISK Moron Detector")
+        String::from(windows::APP_WINDOW_TITLE)
     }
 
-    fn update(&mut self, message: BlitzMessage) {
+    fn update(&mut self, message: BlitzMessage) -> Command<BlitzMessage> {
         match message {
             // Open the blacklist file in the default text editor.
             BlitzMessage::OpenBlacklistFIle => {
@@ -57,27 +283,505 @@ impl Sandbox for BlitzApp {
                     self.error = Some(err.to_string());
                 })
             },
+            // Open a moron's evidence link in the default browser.
+            BlitzMessage::OpenEvidence(evidence_url) => {
+                open::that(&evidence_url).unwrap_or_else(|err| {
+                    self.error = Some(err.to_string());
+                })
+            },
+            // Remove a moron from the blacklist file, along with their results.
+            BlitzMessage::RemoveMoron(username) => {
+                match self.remove_moron(&username) {
+                    Ok(true) => {
+                        log::info!("Removed '{username}' from the blacklist.");
+                        self.scans.retain(|scan| {
+                            scan.clan.is_some() || scan.pattern.is_some() || !scan.username.eq_ignore_ascii_case(&username)
+                        });
+                        self.refresh_morons();
+                    }
+                    Ok(false) => self.error = Some(format!("'{username}' isn't on the blacklist.")),
+                    Err(err) => self.error = Some(err.to_string()),
+                }
+            },
+            // Pick a `username,reason` CSV file and append its morons to the blacklist file.
+            BlitzMessage::ImportBlacklistCsv => {
+                let csv_path = dialog::FileSelection::new("Select a username,reason CSV file to import")
+                    .title("Import Blacklist CSV")
+                    .mode(dialog::FileSelectionMode::Open)
+                    .show();
+                match csv_path {
+                    Ok(Some(csv_path)) => match self.import_blacklist_csv(&std::path::PathBuf::from(&csv_path)) {
+                        Ok(added) => {
+                            log::info!("Imported {added} morons from {csv_path}.");
+                            self.error = None;
+                            self.refresh_morons();
+                        }
+                        Err(err) => self.error = Some(err.to_string()),
+                    },
+                    // The file selection was cancelled.
+                    Ok(None) => {}
+                    Err(err) => self.error = Some(format!("Unable to show the file selection: {err}")),
+                }
+            },
+            // Pick where to save the blacklist as a `username,reason` CSV file, for spreadsheets.
+            BlitzMessage::ExportBlacklistCsv => {
+                let csv_path = dialog::FileSelection::new("Select where to save the blacklist CSV file")
+                    .title("Export Blacklist CSV")
+                    .mode(dialog::FileSelectionMode::Save)
+                    .show();
+                match csv_path {
+                    Ok(Some(csv_path)) => match self.export_blacklist_csv(&std::path::PathBuf::from(&csv_path)) {
+                        Ok(()) => {
+                            log::info!("Exported the blacklist to {csv_path}.");
+                            self.error = None;
+                        }
+                        Err(err) => self.error = Some(err.to_string()),
+                    },
+                    // The file selection was cancelled.
+                    Ok(None) => {}
+                    Err(err) => self.error = Some(format!("Unable to show the file selection: {err}")),
+                }
+            },
+            // Fetch the shared blacklist in the background and merge it into the blacklist file.
+            BlitzMessage::SyncBlacklist => {
+                let Some(sync_url) = self.config.blacklist_sync_url.clone() else {
+                    self.error = Some(String::from("Set blacklist_sync_url in the config to sync the blacklist."));
+                    return Command::none();
+                };
+
+                self.error = Some(String::from("Syncing - Please wait."));
+                let sync = self.runtime.spawn(sync_blacklist(sync_url, self.config.blacklist_sync_strategy));
+                return Command::perform(
+                    async move {
+                        match sync.await {
+                            Ok(result) => result.map_err(|err| err.to_string()),
+                            Err(err) => Err(err.to_string()),
+                        }
+                    },
+                    BlitzMessage::SyncFinished,
+                );
+            },
+            // Report how the sync went, and show the merged blacklist.
+            BlitzMessage::SyncFinished(result) => {
+                match result {
+                    Ok(added) => {
+                        log::info!("Synced the blacklist, adding {added} entries.");
+                        self.error = None;
+                        self.refresh_morons();
+                    }
+                    Err(err) => {
+                        log::error!("Unable to sync the blacklist: {err}");
+                        self.error = Some(err);
+                    }
+                }
+            },
+            // Edit the moron being typed into the blacklist panel.
+            BlitzMessage::DraftUsernameChanged(username) => {
+                self.moron_draft.username = username;
+                self.moron_draft.error = None;
+            },
+            BlitzMessage::DraftReasonChanged(reason) => {
+                self.moron_draft.reason = reason;
+            },
+            // Add the typed moron to the blacklist file, or say why it can't be added.
+            BlitzMessage::AddMoron => {
+                match self.add_moron(&self.moron_draft.username, &self.moron_draft.reason) {
+                    Ok(()) => {
+                        log::info!("Added '{}' to the blacklist.", self.moron_draft.username.trim());
+                        self.moron_draft = MoronDraft::default();
+                        self.refresh_morons();
+                    }
+                    Err(err) => self.moron_draft.error = Some(err.to_string()),
+                }
+            },
+            // Blacklist a player who was read but not flagged, with a placeholder reason.
+            BlitzMessage::BlacklistDetected(name) => {
+                match self.add_moron(&name, UNFLAGGED_REASON) {
+                    Ok(()) => {
+                        log::info!("Added '{name}' to the blacklist from the results.");
+                        self.detected_names.retain(|detected_name| detected_name.name != name);
+                        self.refresh_morons();
+                    }
+                    Err(err) => self.error = Some(err.to_string()),
+                }
+            },
             // Scan the RISK application for morons.
             BlitzMessage::ScanRisk => {
-                self.error = Some(String::from("Scanning - Please wait."));
-                match detector::scan() {
-                    Ok(scans) => {
-                        self.scans = scans;
+                let cooldown = Duration::from_secs(self.config.scan_cooldown_secs);
+                if !cooldown_elapsed(self.last_scan_finished, Instant::now(), cooldown) {
+                    log::debug!("Dropping a scan triggered within the {cooldown:?} cooldown.");
+                    self.error = Some(String::from("Cooling down - Please wait before scanning again."));
+                    return Command::none();
+                }
+
+                if self.scanning {
+                    log::debug!("Dropping a scan triggered while another is in flight.");
+                    return Command::none();
+                }
+
+                self.start_scan();
+                return self.spawn_scan();
+            },
+            // Show the results of the scan, or why it failed.
+            BlitzMessage::ScanCompleted(scan_result) => {
+                return self.finish_scan(scan_result);
+            },
+            // Run the pipeline against the bundled sample lobby.
+            BlitzMessage::DemoScan => {
+                if self.scanning {
+                    log::debug!("Dropping a demo scan triggered while another scan is in flight.");
+                    return Command::none();
+                }
+
+                self.start_scan();
+                return self.spawn_demo_scan();
+            },
+            // Show what the demo scan read. Nothing is recorded, as it isn't a real lobby.
+            BlitzMessage::DemoScanCompleted(scan_result) => {
+                self.scanning = false;
+                match scan_result {
+                    Ok(scan_result) => {
+                        let detection_count: usize = scan_result.report.cards
+                            .iter()
+                            .map(|card| card.raw_detections.len())
+                            .sum();
+                        self.scans = scan_result.scans;
+                        self.detected_names = scan_result.detected_names;
+                        self.previous_scans = None;
+                        self.cleared_scans = None;
                         self.done_initial_scan = true;
+                        self.last_report = Some(scan_result.report);
+                        self.error = if detection_count == 0 {
+                            Some(String::from("Demo scan read no text. Check the OCR models."))
+                        } else {
+                            log::info!("Demo scan read {detection_count} lines of text.");
+                            None
+                        };
+                    }
+                    Err(err) => {
+                        log::error!("Demo scan failed: {err}");
+                        self.error = Some(err);
+                    }
+                }
+            },
+            // Show or hide the log panel.
+            BlitzMessage::ToggleLogs => {
+                self.show_logs = !self.show_logs;
+            },
+            // Freeze the log panel on the current records, or resume tailing new ones.
+            BlitzMessage::ToggleLogsPaused => {
+                self.logs_paused = !self.logs_paused;
+                self.paused_logs = if self.logs_paused {
+                    logging::recent_records()
+                } else {
+                    Vec::new()
+                };
+            },
+            // Redraw the log panel, so it tails the records logged since the last tick.
+            BlitzMessage::LogsTick => {},
+            // Switch to the selected view, loading anything it shows.
+            BlitzMessage::ViewSelected(view) => {
+                self.view = view;
+                match view {
+                    View::Leaderboard => self.refresh_leaderboard(),
+                    View::Blacklist => self.refresh_morons(),
+                    _ => {}
+                }
+            },
+            // Capture the RISK window to preview the crop region against.
+            BlitzMessage::CapturePreview => {
+                let capture = detector::risk_window(self.config.remembered_window.as_ref())
+                    .ok_or(anyhow::anyhow!("Unable to find RISK window."))
+                    .and_then(|risk_window| detector::capture_window(&risk_window));
+                match capture {
+                    Ok(capture) => {
+                        self.preview = Some(PreviewCapture {
+                            width: capture.width(),
+                            height: capture.height(),
+                            content: crop::content_rect(&capture),
+                            handle: Handle::from_pixels(capture.width(), capture.height(), capture.into_raw()),
+                        });
+                        self.error = None;
+                    }
+                    Err(err) => {
+                        self.error = Some(err.to_string());
+                    }
+                }
+            },
+            // Nudge the crop region and persist the new offsets.
+            BlitzMessage::NudgeCrop(dx, dy) => {
+                self.config.crop_offset_x += dx;
+                self.config.crop_offset_y += dy;
+                self.save_config();
+            },
+            // Return the crop region to the center of the window.
+            BlitzMessage::ResetCropOffsets => {
+                self.config.crop_offset_x = 0;
+                self.config.crop_offset_y = 0;
+                self.save_config();
+            },
+            // Use the region dragged out on the preview for the rest of the session.
+            BlitzMessage::RegionSelected(region) => {
+                self.session_region = Some(region);
+            },
+            // Keep the selected region across restarts.
+            BlitzMessage::SaveRegion => {
+                if let Some(region) = self.session_region {
+                    self.config.player_list_region = Some(region);
+                    self.save_config();
+                }
+            },
+            // Return to the centered default region.
+            BlitzMessage::ClearRegion => {
+                self.session_region = None;
+                self.config.player_list_region = None;
+                self.save_config();
+            },
+            // Re-filter the cached results live as the threshold slider is dragged.
+            BlitzMessage::ThresholdChanged(similarity_threshold) => {
+                self.config.similarity_threshold = similarity_threshold;
+            },
+            // Persist the threshold once the slider is let go.
+            BlitzMessage::ThresholdReleased => {
+                self.save_config();
+            },
+            // Write the leaderboard to a timestamped file for sharing.
+            BlitzMessage::ExportLeaderboard => {
+                match self.export_leaderboard() {
+                    Ok(leaderboard_path) => {
+                        log::info!("Exported the leaderboard to {}.", leaderboard_path.display());
+                        self.error = None;
+                    }
+                    Err(err) => {
+                        self.error = Some(err.to_string());
+                    }
+                }
+            },
+            // Clear stale results once nothing new has matched for the configured time.
+            BlitzMessage::AutoClearTick(now) => {
+                let timeout = self.config.auto_clear_secs.map(Duration::from_secs);
+                if should_auto_clear(self.last_match, now, timeout) {
+                    log::info!("Clearing the results after {timeout:?} without a new match.");
+                    self.scans = Vec::new();
+                    self.detected_names = Vec::new();
+                    self.previous_scans = None;
+                    self.cleared_scans = None;
+                    self.done_initial_scan = false;
+                    self.last_match = None;
+                }
+            },
+            // Start the session statistics afresh.
+            BlitzMessage::ResetSessionStats => {
+                self.session_stats = SessionStats::default();
+            },
+            // Copy the flagged results as a Markdown table, for posting in forums and Discord.
+            BlitzMessage::CopyMarkdown => {
+                let markdown = scan_log::to_markdown(&self.scans, self.config.similarity_threshold);
+                return iced::clipboard::write(markdown);
+            },
+            // Switch to the chosen theme and persist it.
+            BlitzMessage::ThemeSelected(theme) => {
+                self.config.theme = theme;
+                self.theme = resolve_theme(theme);
+                self.save_config();
+            },
+            // Copy the flagged results as plain lines, for warning teammates in chat.
+            BlitzMessage::CopyResults => {
+                let chat_text = scan_log::to_chat_text(&self.scans, self.config.similarity_threshold);
+                return iced::clipboard::write(chat_text);
+            },
+            // Turn watch mode on or off, starting afresh so an already open window isn't scanned.
+            BlitzMessage::ToggleWatch => {
+                self.config.watch_mode = !self.config.watch_mode;
+                self.watcher = create_watcher(&self.config);
+                self.save_config();
+            },
+            // Scan if the RISK window appeared or was restored since the last tick.
+            BlitzMessage::WatchTick(now) => {
+                if self.watcher.on_tick(watch::observe(self.config.remembered_window.as_ref()), now) == WatchAction::Scan {
+                    log::info!("Watch mode is scanning the RISK lobby.");
+                    return self.update(BlitzMessage::ScanRisk);
+                }
+            },
+            // Turn auto-scan on or off. Turning it off drops the subscription, which stops the timer.
+            BlitzMessage::ToggleAutoScan => {
+                self.config.auto_scan = !self.config.auto_scan;
+                self.save_config();
+            },
+            // Scan again, as if the Scan button was pressed. A tick while a scan is in flight or
+            // cooling down is skipped rather than reported.
+            BlitzMessage::AutoScanTick(now) => {
+                let cooldown = Duration::from_secs(self.config.scan_cooldown_secs);
+                if self.scanning || !cooldown_elapsed(self.last_scan_finished, now, cooldown) {
+                    return Command::none();
+                }
+
+                self.start_scan();
+                return self.spawn_scan();
+            },
+            // Scan if the global hotkey was pressed since the last tick.
+            BlitzMessage::HotkeyTick => {
+                if self.scan_hotkey.as_ref().is_some_and(ScanHotkey::was_pressed) {
+                    log::info!("The hotkey is scanning the RISK lobby.");
+                    return self.update(BlitzMessage::ScanRisk);
+                }
+            },
+            // Notify the user of who was flagged while the app is out of sight.
+            BlitzMessage::WindowMinimized(minimized) => {
+                if minimized != Some(true) {
+                    return Command::none();
+                }
+
+                let body = alerts::notification_body(&self.scans, self.config.similarity_threshold);
+                // Waiting for a click blocks, so the notification is shown off the UI thread.
+                let notification = self.runtime.spawn_blocking(move || alerts::notify(&body));
+                return Command::perform(
+                    async move {
+                        match notification.await.map_err(anyhow::Error::from).and_then(|clicked| clicked) {
+                            Ok(clicked) => clicked,
+                            Err(err) => {
+                                log::warn!("Unable to show the desktop notification: {err}");
+                                false
+                            }
+                        }
+                    },
+                    BlitzMessage::NotificationClosed,
+                );
+            },
+            // Restore the app if the notification was clicked.
+            BlitzMessage::NotificationClosed(clicked) => {
+                if clicked {
+                    return Command::batch([
+                        window::minimize(window::Id::MAIN, false),
+                        window::gain_focus(window::Id::MAIN),
+                    ]);
+                }
+            },
+            // Silence alerts for the chosen number of minutes.
+            BlitzMessage::Snooze(minutes) => {
+                self.snoozed_until = Some(Instant::now() + Duration::from_secs(minutes * 60));
+                log::info!("Alerts snoozed for {minutes} minutes.");
+            },
+            // Turn alerts back on before the snooze expires.
+            BlitzMessage::Unsnooze => {
+                self.snoozed_until = None;
+            },
+            // Count the snooze down, and clear it once it expires.
+            BlitzMessage::SnoozeTick(now) => {
+                if !alerts::is_snoozed(self.snoozed_until, now) {
+                    self.snoozed_until = None;
+                }
+            },
+            // Delete and download an asset again in the background.
+            BlitzMessage::Redownload(asset) => {
+                self.set_asset_status(asset, String::from("Downloading..."));
+                let download = self.runtime.spawn(paths::redownload_asset(asset));
+                return Command::perform(
+                    async move {
+                        match download.await {
+                            Ok(result) => result.map_err(|err| err.to_string()),
+                            Err(err) => Err(err.to_string()),
+                        }
+                    },
+                    move |result| BlitzMessage::RedownloadFinished(asset, result),
+                );
+            },
+            // Report how the download went.
+            BlitzMessage::RedownloadFinished(asset, result) => {
+                match result {
+                    Ok(()) => {
+                        if asset == Asset::Banner {
+                            self.banner = load_banner(&self.config.banner);
+                        }
+                        self.set_asset_status(asset, String::from("Downloaded"));
+                    }
+                    Err(err) => {
+                        log::error!("Unable to download the {} again: {err}", asset.to_string().to_lowercase());
+                        self.set_asset_status(asset, format!("Failed: {err}"));
+                    }
+                }
+            },
+            // Dump the internals of the most recent scan to a timestamped report file.
+            BlitzMessage::ExportReport => {
+                match self.export_report() {
+                    Ok(report_path) => {
+                        log::info!("Exported the scan report to {}.", report_path.display());
                         self.error = None;
                     }
                     Err(err) => {
-                        self.error = Some(String::from(err.to_string()));
+                        self.error = Some(err.to_string());
                     }
                 }
             }
         }
+
+        Command::none()
+    }
+
+    fn subscription(&self) -> Subscription<BlitzMessage> {
+        let watch = if self.config.watch_mode {
+            iced::time::every(watch::WATCH_POLL_INTERVAL).map(BlitzMessage::WatchTick)
+        } else {
+            Subscription::none()
+        };
+        let snooze = if self.snoozed_until.is_some() {
+            iced::time::every(Duration::from_secs(1)).map(BlitzMessage::SnoozeTick)
+        } else {
+            Subscription::none()
+        };
+
+        let auto_clear = if self.config.auto_clear_secs.is_some() && self.last_match.is_some() {
+            iced::time::every(Duration::from_secs(1)).map(BlitzMessage::AutoClearTick)
+        } else {
+            Subscription::none()
+        };
+
+        let auto_scan = if self.config.auto_scan {
+            iced::time::every(Duration::from_secs(self.config.auto_scan_interval_secs)).map(BlitzMessage::AutoScanTick)
+        } else {
+            Subscription::none()
+        };
+
+        let hotkey = if self.scan_hotkey.is_some() {
+            iced::time::every(hotkey::HOTKEY_POLL_INTERVAL).map(|_| BlitzMessage::HotkeyTick)
+        } else {
+            Subscription::none()
+        };
+
+        let logs = if self.show_logs && !self.logs_paused {
+            iced::time::every(logging::LOG_PANEL_REFRESH_INTERVAL).map(|_| BlitzMessage::LogsTick)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([watch, snooze, auto_clear, auto_scan, hotkey, logs])
     }
 
     fn view(&self) -> Element<BlitzMessage> {
-        let banner_row_maybe = create_banner_row();
-        let button_row = create_button_row();
-        let scan_row = create_scan_row(self.done_initial_scan, &self.scans);
+        let banner_row_maybe = create_banner_row(self.banner.clone());
+        let button_row = create_button_row(self.config.theme);
+        let tools_row = create_tools_row(self.view, self.config.watch_mode, self.config.auto_scan);
+        let content_row = match self.view {
+            View::Scan => Column::new()
+                .align_items(Alignment::Center)
+                .push(create_threshold_row(self.config.similarity_threshold))
+                .push(create_snooze_row(alerts::snooze_remaining(self.snoozed_until, Instant::now())))
+                .push(create_danger_row(self.done_initial_scan, &self.scans, &self.config))
+                .push(create_diff_row(self.previous_scans.as_deref(), &self.scans, self.config.similarity_threshold))
+                .push(create_scan_row(
+                    self.done_initial_scan,
+                    &self.scans,
+                    ResultsDisplay::from_config(&self.config),
+                    self.scanning
+                ))
+                .push(create_unflagged_row(&self.detected_names, &self.scans, self.config.similarity_threshold))
+                .into(),
+            View::Preview => create_preview_panel(&self.scan_config(), self.preview.clone()),
+            View::Leaderboard => create_leaderboard_panel(&self.leaderboard),
+            View::Blacklist => create_blacklist_panel(&self.morons, self.config.min_username_length, &self.moron_draft),
+            View::Session => create_session_panel(&self.session_stats),
+            View::Maintenance => create_maintenance_panel(&self.asset_statuses),
+        };
         let error_row = create_error_row(self.error.as_deref());
 
         // Push the master column with all the UI elements into the container and publish.
@@ -87,11 +791,26 @@ impl Sandbox for BlitzApp {
             master_column = master_column.push(banner_row);
         };
 
+        // The banner and buttons stay pinned at the top, while the content scrolls beneath them.
+        let scrollable_content = scrollable(container(content_row).width(Length::Fill).center_x())
+            .width(Length::Fill)
+            .height(Length::Fill);
+
         master_column = master_column
         .push(button_row)
-        .push(scan_row)
+        .push(tools_row)
+        .push(scrollable_content)
         .push(error_row);
 
+        if self.show_logs {
+            let log_records = if self.logs_paused {
+                self.paused_logs.clone()
+            } else {
+                logging::recent_records()
+            };
+            master_column = master_column.push(create_log_panel(log_records, self.logs_paused));
+        }
+
         container(master_column)
             .width(Length::Fill)
             .height(Length::Fill)
@@ -100,125 +819,1328 @@ impl Sandbox for BlitzApp {
     }
 
     fn theme(&self) -> Theme {
-        Theme::KanagawaDragon
+        self.theme.clone()
     }
 }
 
 impl BlitzApp {
+    /// Marks a scan as in flight. The previous results stay on screen, dimmed, until the new ones
+    /// arrive, unless the config asks for them to be cleared. Cleared results are still kept to
+    /// compare the new ones against.
+    fn start_scan(&mut self) {
+        self.scanning = true;
+        self.error = Some(String::from("Scanning - Please wait."));
+        if !self.config.keep_previous_results {
+            if self.done_initial_scan {
+                self.cleared_scans = Some(std::mem::take(&mut self.scans));
+            }
+            self.done_initial_scan = false;
+        }
+    }
 
-}
+    /// Runs a scan off the UI thread, reporting the outcome as [`BlitzMessage::ScanCompleted`].
+    fn spawn_scan(&self) -> Command<BlitzMessage> {
+        let scan_config = self.scan_config();
+        self.spawn_blocking_scan(move || detector::scan(&scan_config), BlitzMessage::ScanCompleted)
+    }
 
-/// Creates the banner [`Row`] for the application view. If the banner path cannot be constructed,
-/// this function returns [`None`].
-fn create_banner_row() -> Option<Element<'static, BlitzMessage>> {
-    let banner_path = match paths::banner_path() {
-        Some(banner_path) => banner_path,
-        None => {
-            // It's not the end of the world if we can't retrieve this.
-            eprintln!("Unable to construct the banner path.");
-            return None;
+    /// Runs a scan of the bundled sample lobby off the UI thread, reporting the outcome as
+    /// [`BlitzMessage::DemoScanCompleted`].
+    fn spawn_demo_scan(&self) -> Command<BlitzMessage> {
+        let scan_config = self.scan_config();
+        self.spawn_blocking_scan(move || detector::demo_scan(&scan_config), BlitzMessage::DemoScanCompleted)
+    }
+
+    /// Runs a scan on the blocking thread pool, catching its panics if configured to.
+    ///
+    /// # Arguments
+    /// * `scan` - The scan to run.
+    /// * `on_completed` - Wraps the outcome of the scan in the message reporting it.
+    fn spawn_blocking_scan<S>(
+        &self,
+        scan: S,
+        on_completed: fn(Result<detector::ScanResult, String>) -> BlitzMessage,
+    ) -> Command<BlitzMessage>
+    where
+        S: FnOnce() -> anyhow::Result<detector::ScanResult> + Send + 'static,
+    {
+        // OCR takes seconds, so the scan runs off the UI thread to keep the window painting.
+        let catch_scan_panics = self.config.catch_scan_panics;
+        let scan = self.runtime.spawn_blocking(move || detector::guard_panics(catch_scan_panics, scan));
+        Command::perform(
+            async move {
+                match scan.await {
+                    Ok(scan_result) => scan_result.map_err(|err| err.to_string()),
+                    Err(err) => Err(err.to_string()),
+                }
+            },
+            on_completed,
+        )
+    }
+
+    /// Replaces the previous results with those of the finished scan, or reports why it failed.
+    ///
+    /// # Arguments
+    /// * `scan_result` - The outcome of the scan.
+    fn finish_scan(&mut self, scan_result: Result<detector::ScanResult, String>) -> Command<BlitzMessage> {
+        self.scanning = false;
+        self.last_scan_finished = Some(Instant::now());
+        match scan_result {
+            Ok(scan_result) => {
+                self.session_stats.record(&scan_result, self.config.similarity_threshold);
+                if alerts::should_alert(&scan_result.scans, self.config.similarity_threshold) {
+                    self.last_match = Some(Instant::now());
+                }
+                self.show_scan_results(scan_result.scans);
+                self.detected_names = scan_result.detected_names;
+                self.error = scan_result.warning;
+                self.record_history(scan_result.report.timestamp);
+                self.remember_window(scan_result.window);
+                self.last_report = Some(scan_result.report);
+                self.alert()
+            }
+            Err(err) => {
+                log::error!("Scan failed: {err}");
+                self.error = Some(err);
+                Command::none()
+            }
         }
-    };
+    }
 
-    let banner_image = widget::Image::new(Handle::from_path(&banner_path))
-        .width(Length::Shrink)
-        .height(Length::Shrink);
-    let banner_row = Row::new()
-        .align_items(Alignment::Center)
-        .padding(pad(18, 14, 14, 0))
-        .push(banner_image)
-        .into();
+    /// Shows the results of a finished scan, keeping the results of the scan before to show who
+    /// joined and left since, even if they were cleared when the scan started.
+    ///
+    /// # Arguments
+    /// * `scans` - The results of the finished scan.
+    fn show_scan_results(&mut self, scans: Vec<ScanInfo>) {
+        let shown_scans = std::mem::replace(&mut self.scans, scans);
+        self.previous_scans = self.cleared_scans.take().or(self.done_initial_scan.then_some(shown_scans));
+        self.done_initial_scan = true;
+    }
 
-    Some(banner_row)
-}
+    /// Alerts the user to the results of the last scan by flashing the window, and with a desktop
+    /// notification if the window is minimized, unless nothing was flagged (or, if so configured,
+    /// nothing newly flagged) or alerts are snoozed.
+    fn alert(&self) -> Command<BlitzMessage> {
+        let should_alert = if self.config.alert_only_new {
+            alerts::should_alert_new(self.previous_scans.as_deref(), &self.scans, self.config.similarity_threshold)
+        } else {
+            alerts::should_alert(&self.scans, self.config.similarity_threshold)
+        };
+        if !should_alert {
+            return Command::none();
+        }
 
-/// Creates the button [`Row`] for the application view that contains the blacklist,
-/// scan, and support buttons.
-fn create_button_row() -> Element<'static, BlitzMessage> {
-    let blacklist_button = widget::Button::new("Blacklist")
-        .on_press(BlitzMessage::OpenBlacklistFIle);
-    let scan_button = widget::Button::new("Scan")
-        .on_press(BlitzMessage::ScanRisk);
-    let support_button = widget::Button::new("Support")
-        .on_press(BlitzMessage::OpenSupportUrl);
+        if alerts::is_snoozed(self.snoozed_until, Instant::now()) {
+            log::debug!("Not alerting because alerts are snoozed.");
+            return Command::none();
+        }
 
-    widget::Row::new()
-        .align_items(Alignment::Center)
-        .spacing(10)
-        .padding(pad(6, 14, 14, 0))
-        .push(blacklist_button)
-        .push(scan_button)
-        .push(support_button)
-        .into()
-}
+        let request_attention = window::request_user_attention(window::Id::MAIN, Some(UserAttention::Critical));
+        if !self.config.desktop_notifications {
+            return request_attention;
+        }
+        Command::batch([
+            request_attention,
+            window::fetch_minimized(window::Id::MAIN, BlitzMessage::WindowMinimized),
+        ])
+    }
 
-/// Creates the scan [`Row`] for the application view that contains the list of 
-/// scanned morons, a message that says no morons were found, or a prompt to scan.
-fn create_scan_row(done_initial_scan: bool, scans: &Vec<ScanInfo> ) -> Element<'static, BlitzMessage> {
-    let mut scan_row = Row::new()
-        .align_items(Alignment::Start)
-        .padding(pad(10, 14, 14, 0));
+    /// Sets the status shown beside an asset in the maintenance panel.
+    ///
+    /// # Arguments
+    /// * `asset` - The asset the status is for.
+    /// * `status` - The status to show.
+    fn set_asset_status(&mut self, asset: Asset, status: String) {
+        match self.asset_statuses.iter_mut().find(|(a, _)| *a == asset) {
+            Some((_, asset_status)) => *asset_status = status,
+            None => self.asset_statuses.push((asset, status)),
+        }
+    }
 
-    if done_initial_scan == false {
-        scan_row = scan_row.push(text("Press SCAN to start detecting morons.").shaping(text::Shaping::Advanced));
-        return scan_row.into()
+    /// Saves the current [`Config`] to the config file, reporting any failure in the error row.
+    fn save_config(&mut self) {
+        let saved = paths::config_path()
+            .ok_or(anyhow::anyhow!("Unable to construct the config path."))
+            .and_then(|config_path| self.config.save(&config_path));
+        if let Err(err) = saved {
+            self.error = Some(err.to_string());
+        }
     }
 
-    let mut similar_scans: Vec<_> = scans
-        .iter()
-        .filter(|s| s.similarity >= 70)
-        .collect();
+    /// Remembers the window a scan used, so later scans prefer it, if remembering is on and it isn't
+    /// already remembered.
+    ///
+    /// # Arguments
+    /// * `window` - The window the scan used, if it captured one.
+    fn remember_window(&mut self, window: Option<WindowIdentity>) {
+        if !self.config.remember_window || window.is_none() || window == self.config.remembered_window {
+            return;
+        }
 
-    if similar_scans.is_empty() {
-        scan_row = scan_row.push(text("No Morons Here (✿◠‿◠)").shaping(text::Shaping::Advanced));
-        return scan_row.into()
+        self.config.remembered_window = window;
+        self.save_config();
     }
 
-    // Sort the scans in descending order and push them into their individual columns.
-    similar_scans.sort_by(|a, b| b.similarity.cmp(&a.similarity));
+    /// Writes the report of the most recent scan to a timestamped file in the app directory.
+    fn export_report(&self) -> anyhow::Result<std::path::PathBuf> {
+        let report = self.last_report.as_ref().ok_or(anyhow::anyhow!("Scan before exporting a report."))?;
+        let report_path = paths::report_path(report.timestamp)
+            .ok_or(anyhow::anyhow!("Unable to construct the report path."))?;
+        report.save(&report_path)?;
+        Ok(report_path)
+    }
 
-    let mut warning_column = widget::Column::new()
-        .align_items(Alignment::Start)
-        .padding(5);
+    /// Appends the flagged players of the last scan to the scan history (and the CSV scan log and
+    /// auto-export file, if enabled), and refreshes the leaderboard to include them.
+    ///
+    /// # Arguments
+    /// * `timestamp` - When the scan ran, in seconds since the Unix epoch.
+    fn record_history(&mut self, timestamp: u64) {
+        let entries = history::entries_for_scan(&self.scans, self.config.similarity_threshold, timestamp);
+        let recorded = paths::history_path()
+            .ok_or(anyhow::anyhow!("Unable to construct the history path."))
+            .and_then(|history_path| history::append(&history_path, &entries));
+        if let Err(err) = recorded {
+            log::warn!("Unable to record the scan history: {err}");
+        }
 
-    let mut username_column = widget::Column::new()
-        .align_items(Alignment::Start)
-        .padding(5);
+        let rows = scan_log::rows_for_scan(&self.scans, self.config.similarity_threshold, timestamp);
+        if self.config.csv_scan_log {
+            let logged = paths::scan_log_path()
+                .ok_or(anyhow::anyhow!("Unable to construct the scan log path."))
+                .and_then(|scan_log_path| scan_log::append(&scan_log_path, &rows));
+            if let Err(err) = logged {
+                log::warn!("Unable to append to the scan log: {err}");
+            }
+        }
 
-    let mut similarity_column = widget::Column::new()
-        .align_items(Alignment::Start)
-        .padding(5);
+        if let Some(auto_export) = &self.config.auto_export {
+            if let Err(err) = scan_log::auto_export(auto_export, &rows) {
+                log::warn!("Unable to auto-export the scan to {}: {err}", auto_export.path.display());
+            }
+        }
 
-    for similar_scan in similar_scans {
-        warning_column = warning_column.push(text("MORON?").style(red()).font(bold()));
-        username_column = username_column.push(text(&similar_scan.username).style(silver()));
-        similarity_column = similarity_column.push(text(format!("({}%)", &similar_scan.similarity)).font(italic()))
+        self.record_telemetry(timestamp);
+        self.refresh_leaderboard();
     }
 
-    scan_row
-        .push(warning_column)
-        .push(username_column)
-        .push(similarity_column)
-        .into()
-}
+    /// Records the anonymous stats for the last scan, and uploads them in the background, if the
+    /// user has opted in to telemetry.
+    ///
+    /// # Arguments
+    /// * `timestamp` - When the scan ran, in seconds since the Unix epoch.
+    fn record_telemetry(&self, timestamp: u64) {
+        let Some(telemetry_config) = &self.config.telemetry else {
+            return;
+        };
 
-/// Creates the button [`Row`] for the application view that contains the blacklist,
-/// scan, and support buttons.
-fn create_error_row(error: Option<&str>) -> Element<'static, BlitzMessage> {
-    let message = match error {
-        Some(error) => error,
-        None => ""
-    };
+        let stats = telemetry::aggregate(&self.scans, self.config.similarity_threshold, timestamp);
+        let recorded = paths::telemetry_path()
+            .ok_or(anyhow::anyhow!("Unable to construct the telemetry path."))
+            .and_then(|telemetry_path| telemetry::append(&telemetry_path, &stats));
+        if let Err(err) = recorded {
+            log::warn!("Unable to record the telemetry: {err}");
+        }
 
-    widget::Row::new()
-        .align_items(Alignment::Center)
-        .push(text(message).style(red()))
-        .into()
-}
+        if let Some(upload_url) = telemetry_config.upload_url.clone() {
+            self.runtime.spawn(async move {
+                if let Err(err) = telemetry::upload(upload_url, stats).await {
+                    log::warn!("Unable to upload the telemetry: {err}");
+                }
+            });
+        }
+    }
 
+    /// Reloads the scan history and aggregates it into the leaderboard.
+    fn refresh_leaderboard(&mut self) {
+        let entries = paths::history_path()
+            .ok_or(anyhow::anyhow!("Unable to construct the history path."))
+            .and_then(|history_path| history::load(&history_path));
+        match entries {
+            Ok(entries) => self.leaderboard = history::leaderboard(&entries),
+            Err(err) => self.error = Some(format!("History Error: {err}")),
+        }
+    }
 
-/// Constructs a new [`iced::Padding`] with the specified padding values.
-///
+    /// Adds a moron to the blacklist file, unless the username is empty or already blacklisted.
+    ///
+    /// # Arguments
+    /// * `username` - The username of the moron to add.
+    /// * `reason` - Why the moron is blacklisted.
+    fn add_moron(&self, username: &str, reason: &str) -> anyhow::Result<()> {
+        let username = username.trim();
+        if username.is_empty() {
+            anyhow::bail!("Enter a username to add.");
+        }
+
+        let blacklist_path = paths::blacklist_path()
+            .ok_or(anyhow::anyhow!("Unable to find the path to the blacklist."))?;
+        let mut blacklist = Blacklist::load(&blacklist_path)
+            .map_err(|err| anyhow::anyhow!("Blacklist Error: {err}"))?;
+        if blacklist.contains_moron(username) {
+            anyhow::bail!("'{username}' is already on the blacklist.");
+        }
+        blacklist.add_moron(Moron::new(username.to_string(), reason.trim().to_string()))?;
+        blacklist.save(&blacklist_path)
+    }
+
+    /// Removes a moron from the blacklist file, returning whether they were on it.
+    ///
+    /// # Arguments
+    /// * `username` - The username of the moron to remove.
+    fn remove_moron(&self, username: &str) -> anyhow::Result<bool> {
+        let blacklist_path = paths::blacklist_path()
+            .ok_or(anyhow::anyhow!("Unable to find the path to the blacklist."))?;
+        let mut blacklist = Blacklist::load(&blacklist_path)
+            .map_err(|err| anyhow::anyhow!("Blacklist Error: {err}"))?;
+        if !blacklist.remove_moron(username) {
+            return Ok(false);
+        }
+        blacklist.save(&blacklist_path)?;
+        Ok(true)
+    }
+
+    /// Imports morons from a CSV file into the blacklist file, returning how many were added.
+    ///
+    /// # Arguments
+    /// * `csv_path` - The path to the CSV file to import.
+    fn import_blacklist_csv(&self, csv_path: &std::path::PathBuf) -> anyhow::Result<usize> {
+        let blacklist_path = paths::blacklist_path()
+            .ok_or(anyhow::anyhow!("Unable to find the path to the blacklist."))?;
+        let mut blacklist = Blacklist::load(&blacklist_path)
+            .map_err(|err| anyhow::anyhow!("Blacklist Error: {err}"))?;
+        let added = blacklist.import_csv(csv_path)?;
+        if added > 0 {
+            blacklist.save(&blacklist_path)?;
+        }
+        Ok(added)
+    }
+
+    /// Exports the blacklist file to a CSV file.
+    ///
+    /// # Arguments
+    /// * `csv_path` - The path to write the CSV file to.
+    fn export_blacklist_csv(&self, csv_path: &std::path::PathBuf) -> anyhow::Result<()> {
+        let blacklist_path = paths::blacklist_path()
+            .ok_or(anyhow::anyhow!("Unable to find the path to the blacklist."))?;
+        let blacklist = Blacklist::load(&blacklist_path)
+            .map_err(|err| anyhow::anyhow!("Blacklist Error: {err}"))?;
+        blacklist.export_csv(csv_path)
+    }
+
+    /// Reloads the blacklist for the blacklist viewer.
+    fn refresh_morons(&mut self) {
+        let blacklist_path = match paths::blacklist_path() {
+            Some(blacklist_path) => blacklist_path,
+            None => {
+                self.error = Some(String::from("Unable to find the path to the blacklist."));
+                return;
+            }
+        };
+        match Blacklist::load(&blacklist_path) {
+            Ok(blacklist) => self.morons = blacklist.morons,
+            Err(err) => self.error = Some(format!("Blacklist Error: {err}")),
+        }
+    }
+
+    /// Writes the leaderboard to a timestamped file in the app directory.
+    fn export_leaderboard(&self) -> anyhow::Result<std::path::PathBuf> {
+        let leaderboard_path = paths::leaderboard_path(report::unix_timestamp())
+            .ok_or(anyhow::anyhow!("Unable to construct the leaderboard path."))?;
+        let leaderboard_json = serde_json::to_string_pretty(&self.leaderboard)?;
+        std::fs::write(&leaderboard_path, leaderboard_json)?;
+        Ok(leaderboard_path)
+    }
+
+    /// Gets the [`Config`] to scan with: the saved config, with the region selected this session (if
+    /// any) in place of the saved region.
+    fn scan_config(&self) -> Config {
+        let mut scan_config = self.config.clone();
+        if let Some(region) = self.session_region {
+            scan_config.player_list_region = Some(region);
+        }
+        scan_config
+    }
+}
+
+/// Loads the [`Config`] from the config file.
+fn load_config() -> Result<Config, String> {
+    let config_path = paths::config_path().ok_or(String::from("Unable to construct the config path."))?;
+    Config::load(&config_path).map_err(|err| format!("Config Error: {err}"))
+}
+
+/// Creates the [`Watcher`] for watch mode from the debounce and fallback interval in the [`Config`].
+fn create_watcher(config: &Config) -> Watcher {
+    Watcher::new(
+        Duration::from_secs(config.watch_debounce_secs),
+        Duration::from_secs(config.watch_fallback_interval_secs),
+    )
+}
+
+/// Loads the configured banner, decoding it up front so a corrupt or unsupported file is skipped
+/// rather than shown broken. Animated banners (e.g. GIFs) show their first frame. A custom banner
+/// that can't be decoded falls back to the default banner. If the banner is disabled or can't be
+/// decoded, this function returns [`None`].
+///
+/// # Arguments
+/// * `banner` - The configured banner.
+fn load_banner(banner: &Banner) -> Option<Handle> {
+    // It's not the end of the world if we can't retrieve this.
+    let Some(banner_path) = paths::banner_source(banner) else {
+        if *banner != Banner::Disabled {
+            log::warn!("Unable to construct the banner path.");
+        }
+        return None;
+    };
+
+    match decode_banner(&banner_path) {
+        Ok(banner_image) => Some(Handle::from_pixels(banner_image.width(), banner_image.height(), banner_image.into_raw())),
+        Err(err) => {
+            log::warn!("Unable to decode the banner at {}: {err}", banner_path.display());
+            match banner {
+                Banner::Local(_) => load_banner(&Banner::Default),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Decodes a banner image into pixels. Only the first frame of an animated image is decoded.
+///
+/// # Arguments
+/// * `banner_path` - The path of the banner image.
+fn decode_banner(banner_path: &std::path::Path) -> anyhow::Result<image::RgbaImage> {
+    Ok(image::io::Reader::open(banner_path)?
+        .with_guessed_format()?
+        .decode()?
+        .to_rgba8())
+}
+
+/// Creates the banner [`Row`] for the application view. If there is no banner to show, this
+/// function returns [`None`].
+///
+/// # Arguments
+/// * `banner` - The decoded banner, if there is one.
+fn create_banner_row(banner: Option<Handle>) -> Option<Element<'static, BlitzMessage>> {
+    let banner_image = widget::Image::new(banner?)
+        .width(Length::Shrink)
+        .height(Length::Shrink);
+    let banner_row = Row::new()
+        .align_items(Alignment::Center)
+        .padding(pad(18, 14, 14, 0))
+        .push(banner_image)
+        .into();
+
+    Some(banner_row)
+}
+
+/// Creates a link that removes a moron from the blacklist, or an empty placeholder if the result
+/// can't be removed.
+///
+/// # Arguments
+/// * `username` - The username of the moron to remove, if any.
+fn create_remove_link(username: Option<&str>) -> Element<'static, BlitzMessage> {
+    match username {
+        Some(username) => widget::Button::new(text("remove").style(silver()).font(italic()))
+            .padding(0)
+            .style(iced::theme::Button::Text)
+            .on_press(BlitzMessage::RemoveMoron(username.to_string()))
+            .into(),
+        None => text("").into(),
+    }
+}
+
+/// Creates the button [`Row`] for the application view that contains the blacklist,
+/// scan, and support buttons, and the theme picker.
+///
+/// # Arguments
+/// * `theme` - The configured theme.
+fn create_button_row(theme: AppTheme) -> Element<'static, BlitzMessage> {
+    let blacklist_button = widget::Button::new("Blacklist")
+        .on_press(BlitzMessage::OpenBlacklistFIle);
+    let scan_button = widget::Button::new("Scan")
+        .on_press(BlitzMessage::ScanRisk);
+    let support_button = widget::Button::new("Support")
+        .on_press(BlitzMessage::OpenSupportUrl);
+    let theme_pick_list = widget::pick_list(&AppTheme::ALL[..], Some(theme), BlitzMessage::ThemeSelected);
+
+    widget::Row::new()
+        .align_items(Alignment::Center)
+        .spacing(10)
+        .padding(pad(6, 14, 14, 0))
+        .push(blacklist_button)
+        .push(scan_button)
+        .push(support_button)
+        .push(theme_pick_list)
+        .into()
+}
+
+/// Creates the tools [`Row`] for the application view that contains the view picker, and the log,
+/// report, watch mode, and auto-scan buttons.
+///
+/// # Arguments
+/// * `view` - The view currently shown.
+/// * `watch_mode` - Whether watch mode is on.
+/// * `auto_scan` - Whether auto-scan is on.
+fn create_tools_row(view: View, watch_mode: bool, auto_scan: bool) -> Element<'static, BlitzMessage> {
+    let view_pick_list = widget::pick_list(&View::ALL[..], Some(view), BlitzMessage::ViewSelected);
+    let logs_button = widget::Button::new("Logs")
+        .on_press(BlitzMessage::ToggleLogs);
+    let report_button = widget::Button::new("Report")
+        .on_press(BlitzMessage::ExportReport);
+    let watch_button = widget::Button::new(if watch_mode { "Watching" } else { "Watch" })
+        .on_press(BlitzMessage::ToggleWatch);
+    let auto_scan_button = widget::Button::new(if auto_scan { "Auto-scanning" } else { "Auto-scan" })
+        .on_press(BlitzMessage::ToggleAutoScan);
+
+    widget::Row::new()
+        .align_items(Alignment::Center)
+        .spacing(10)
+        .padding(pad(0, 14, 14, 0))
+        .push(view_pick_list)
+        .push(logs_button)
+        .push(report_button)
+        .push(watch_button)
+        .push(auto_scan_button)
+        .into()
+}
+
+/// Creates the threshold [`Row`] for the application view that contains the similarity threshold
+/// slider. Dragging it re-filters the results of the last scan without scanning again.
+fn create_threshold_row(similarity_threshold: u8) -> Element<'static, BlitzMessage> {
+    let threshold_slider = widget::slider(0..=100, similarity_threshold, BlitzMessage::ThresholdChanged)
+        .on_release(BlitzMessage::ThresholdReleased)
+        .width(Length::Fixed(200f32));
+
+    widget::Row::new()
+        .align_items(Alignment::Center)
+        .spacing(10)
+        .padding(pad(6, 14, 14, 0))
+        .push(text(format!("Threshold: {similarity_threshold}%")).style(silver()))
+        .push(threshold_slider)
+        .into()
+}
+
+/// Creates the snooze [`Row`] for the application view that offers to snooze alerts, or counts down
+/// the snooze in progress.
+///
+/// # Arguments
+/// * `snooze_remaining` - How long remains of the snooze, or [`None`] if alerts aren't snoozed.
+fn create_snooze_row(snooze_remaining: Option<Duration>) -> Element<'static, BlitzMessage> {
+    let mut snooze_row = Row::new()
+        .align_items(Alignment::Center)
+        .spacing(6)
+        .padding(pad(4, 14, 14, 0));
+
+    match snooze_remaining {
+        Some(snooze_remaining) => {
+            let remaining_secs = snooze_remaining.as_secs();
+            snooze_row = snooze_row
+                .push(text(format!("Alerts snoozed: {}:{:02}", remaining_secs / 60, remaining_secs % 60)).style(silver()))
+                .push(widget::Button::new("Resume").on_press(BlitzMessage::Unsnooze));
+        }
+        None => {
+            snooze_row = snooze_row.push(text("Snooze alerts:").style(silver()));
+            for minutes in alerts::SNOOZE_OPTIONS_MINS {
+                snooze_row = snooze_row.push(widget::Button::new(text(format!("{minutes}m"))).on_press(BlitzMessage::Snooze(minutes)));
+            }
+        }
+    }
+
+    snooze_row.into()
+}
+
+/// Creates the danger [`Row`] for the application view that shows the danger score of the last scan
+/// as a gauge with a label. Empty before the first scan.
+///
+/// # Arguments
+/// * `done_initial_scan` - Whether a scan has completed.
+/// * `scans` - The results of the last scan.
+/// * `config` - The [`Config`] holding the threshold and danger weights.
+fn create_danger_row(done_initial_scan: bool, scans: &[ScanInfo], config: &Config) -> Element<'static, BlitzMessage> {
+    let mut danger_row = Row::new()
+        .align_items(Alignment::Center)
+        .spacing(8)
+        .padding(pad(4, 14, 14, 0));
+
+    if !done_initial_scan {
+        return danger_row.into()
+    }
+
+    let score = danger::danger_score(scans, config.similarity_threshold, &config.danger_weights);
+    let label_color = if score >= 50 { red() } else if score > 0 { silver() } else { green() };
+
+    danger_row = danger_row
+        .push(text("Danger:").style(silver()))
+        .push(widget::progress_bar(0.0..=100.0, score as f32).width(Length::Fixed(150f32)).height(Length::Fixed(10f32)))
+        .push(text(format!("{score} {}", danger::danger_label(score))).style(label_color).font(bold()));
+
+    danger_row.into()
+}
+
+/// Creates the diff [`Row`] for the application view that lists who was newly flagged ("+") and who
+/// is no longer flagged ("-") since the previous scan. Empty before the second scan or when nothing
+/// changed.
+///
+/// # Arguments
+/// * `previous_scans` - The results of the previous scan, if there was one.
+/// * `scans` - The results of the current scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+fn create_diff_row(
+    previous_scans: Option<&[ScanInfo]>,
+    scans: &[ScanInfo],
+    similarity_threshold: u8
+) -> Element<'static, BlitzMessage> {
+    let mut diff_row = Row::new()
+        .align_items(Alignment::Center)
+        .spacing(8);
+
+    let Some(previous_scans) = previous_scans else {
+        return diff_row.into()
+    };
+
+    let scan_diff = history::diff_flagged(previous_scans, scans, similarity_threshold);
+    for joined in scan_diff.joined {
+        diff_row = diff_row.push(text(format!("+{joined}")).style(red()).font(bold()));
+    }
+    for left in scan_diff.left {
+        diff_row = diff_row.push(text(format!("-{left}")).style(green()));
+    }
+
+    diff_row.into()
+}
+
+/// Creates the scan [`Row`] for the application view that lists the flagged results of the last
+/// scan, or says no morons were found, or prompts to scan.
+///
+/// # Arguments
+/// * `done_initial_scan` - Whether a scan has finished yet.
+/// * `scans` - The results of the last scan.
+/// * `results_display` - How the results are listed: the minimum similarity for a result to be
+///   listed, the most results listed before the rest are hidden, how each similarity shows, and
+///   the read quality below which a result is greyed out.
+/// * `dimmed` - Whether the results are dimmed because a new scan is in flight.
+fn create_scan_row(
+    done_initial_scan: bool,
+    scans: &[ScanInfo],
+    results_display: ResultsDisplay,
+    dimmed: bool
+) -> Element<'static, BlitzMessage> {
+    let ResultsDisplay {
+        similarity_threshold,
+        max_results,
+        similarity_display,
+        similarity_labels,
+        min_read_quality,
+    } = results_display;
+    // Results from before an in-flight scan are greyed out until the new ones arrive.
+    let (red, green, silver) = if dimmed {
+        (grey(), grey(), grey())
+    } else {
+        (red(), green(), silver())
+    };
+
+    let mut scan_row = Row::new()
+        .align_items(Alignment::Start)
+        .padding(pad(10, 14, 14, 0));
+
+    if done_initial_scan == false {
+        let demo_button = widget::Button::new("Demo")
+            .on_press(BlitzMessage::DemoScan);
+        scan_row = scan_row
+            .align_items(Alignment::Center)
+            .spacing(10)
+            .push(text("Press SCAN to start detecting morons.").shaping(text::Shaping::Advanced))
+            .push(demo_button);
+        return scan_row.into()
+    }
+
+    let (similar_scans, hidden_count) = displayed_results(scans, similarity_threshold, max_results);
+
+    if similar_scans.is_empty() && hidden_count == 0 {
+        scan_row = scan_row.push(text("No Morons Here (✿◠‿◠)").shaping(text::Shaping::Advanced));
+        return Column::new()
+            .align_items(Alignment::Center)
+            .push(scan_row)
+            .push(create_copy_link("copy results", BlitzMessage::CopyResults, silver))
+            .into()
+    }
+
+    let multiple_windows = similar_scans.iter().any(|similar_scan| similar_scan.window_index > 0);
+
+    let mut seat_column = widget::Column::new()
+        .align_items(Alignment::Start)
+        .padding(5);
+
+    let mut warning_column = widget::Column::new()
+        .align_items(Alignment::Start)
+        .padding(5);
+
+    let mut username_column = widget::Column::new()
+        .align_items(Alignment::Start)
+        .padding(5);
+
+    let mut reason_column = widget::Column::new()
+        .align_items(Alignment::Start)
+        .padding(5);
+
+    let mut similarity_column = widget::Column::new()
+        .align_items(Alignment::Start)
+        .padding(5);
+
+    let mut evidence_column = widget::Column::new()
+        .align_items(Alignment::Start)
+        .padding(5);
+
+    let mut remove_column = widget::Column::new()
+        .align_items(Alignment::Start)
+        .padding(5);
+
+    for similar_scan in similar_scans {
+        // Results with low read quality are likely misreads, so they're greyed out.
+        let low_read_quality = min_read_quality.is_some_and(|min_read_quality| similar_scan.read_quality < min_read_quality);
+        let (red, silver) = if low_read_quality { (grey(), grey()) } else { (red, silver) };
+
+        let seat = if multiple_windows {
+            format!("Lobby {} Seat {}", similar_scan.window_index + 1, similar_scan.card_index + 1)
+        } else {
+            format!("Seat {}", similar_scan.card_index + 1)
+        };
+        seat_column = seat_column.push(text(seat).style(silver));
+
+        // Allowlisted players are marked as friendly rather than flagged.
+        match &similar_scan.friendly {
+            Some(friend) => {
+                warning_column = warning_column.push(text("FRIENDLY").style(green).font(bold()));
+                username_column = username_column.push(text(friend).style(silver));
+            }
+            None => match &similar_scan.clan {
+                Some(clan) => {
+                    warning_column = warning_column.push(text(format!("CLAN [{clan}]")).style(red).font(bold()));
+                    username_column = username_column.push(text(&similar_scan.username).style(silver));
+                }
+                None if similar_scan.pattern.is_some() => {
+                    let pattern = similar_scan.pattern.as_deref().unwrap_or_default();
+                    warning_column = warning_column.push(text(format!("PATTERN {pattern}")).style(red).font(bold()));
+                    username_column = username_column.push(create_username(&similar_scan.username, similar_scan.note.as_deref(), silver, iced::Font::default()));
+                }
+                None if similar_scan.pinned => {
+                    warning_column = warning_column.push(text("PINNED").style(red).font(bold()));
+                    username_column = username_column.push(create_username(&similar_scan.username, similar_scan.note.as_deref(), silver, bold()));
+                }
+                None => {
+                    let severity_color = if dimmed || low_read_quality { grey() } else { severity_color(similar_scan.severity) };
+                    warning_column = warning_column.push(text("MORON?").style(severity_color).font(bold()));
+                    username_column = username_column.push(create_username(&similar_scan.username, similar_scan.note.as_deref(), silver, iced::Font::default()));
+                }
+            }
+        }
+        reason_column = reason_column.push(text(result_reason(similar_scan)).style(silver).shaping(text::Shaping::Advanced));
+        similarity_column = similarity_column.push(text(format_similarity(similar_scan.similarity, similarity_display, similarity_labels)).style(silver).font(italic()));
+        evidence_column = evidence_column.push(create_evidence_link(similar_scan.evidence_url.as_deref()));
+        // Clan and pattern results show the detected name, which isn't a blacklist entry.
+        let removable = similar_scan.friendly.is_none() && similar_scan.clan.is_none() && similar_scan.pattern.is_none();
+        remove_column = remove_column.push(create_remove_link(removable.then_some(similar_scan.username.as_str())));
+    }
+
+    scan_row = scan_row
+        .push(seat_column)
+        .push(warning_column)
+        .push(username_column)
+        .push(reason_column)
+        .push(similarity_column)
+        .push(evidence_column)
+        .push(remove_column);
+
+    let copy_row = Row::new()
+        .spacing(10)
+        .push(create_copy_link("copy results", BlitzMessage::CopyResults, silver))
+        .push(create_copy_link("copy as markdown", BlitzMessage::CopyMarkdown, silver));
+
+    let mut results_column = Column::new()
+        .align_items(Alignment::Center)
+        .push(scan_row);
+    if hidden_count > 0 {
+        results_column = results_column.push(text(format!("+{hidden_count} more")).style(silver).font(italic()));
+    }
+    results_column.push(copy_row).into()
+}
+
+/// Creates a link-styled button that copies the results to the clipboard.
+///
+/// # Arguments
+/// * `label` - The text of the link.
+/// * `message` - The message that copies the results.
+/// * `color` - The colour of the link.
+fn create_copy_link(label: &str, message: BlitzMessage, color: iced::Color) -> Element<'static, BlitzMessage> {
+    widget::Button::new(text(label).style(color).font(italic()))
+        .padding(0)
+        .style(iced::theme::Button::Text)
+        .on_press(message)
+        .into()
+}
+
+/// Fetches the shared blacklist and merges it into the blacklist file, returning how many entries
+/// were added. The blacklist file is left untouched if the fetch fails.
+///
+/// # Arguments
+/// * `sync_url` - The URL of the shared blacklist JSON file.
+/// * `strategy` - How entries on both blacklists are merged.
+async fn sync_blacklist(sync_url: String, strategy: MergeStrategy) -> anyhow::Result<usize> {
+    let remote = paths::fetch_remote_blacklist(&sync_url).await?;
+    let blacklist_path = paths::blacklist_path()
+        .ok_or(anyhow::anyhow!("Unable to find the path to the blacklist."))?;
+    let mut blacklist = Blacklist::load(&blacklist_path)
+        .map_err(|err| anyhow::anyhow!("Blacklist Error: {err}"))?;
+    let added = blacklist.merge(remote, strategy);
+    blacklist.save(&blacklist_path)?;
+    Ok(added)
+}
+
+/// Creates the unflagged [`Row`] for the application view that lists the players read cleanly from
+/// cards without a flagged result, each with a button to blacklist them. Nothing is shown if every
+/// card was flagged or no names were read.
+///
+/// # Arguments
+/// * `detected_names` - The names read from the cards by the last scan.
+/// * `scans` - The results of the last scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+fn create_unflagged_row(
+    detected_names: &[DetectedName],
+    scans: &[ScanInfo],
+    similarity_threshold: u8
+) -> Element<'static, BlitzMessage> {
+    let flagged_cards: Vec<(usize, usize)> = detector::similar_scans(scans, similarity_threshold)
+        .iter()
+        .map(|scan| (scan.window_index, scan.card_index))
+        .collect();
+    let unflagged: Vec<&DetectedName> = detected_names
+        .iter()
+        .filter(|detected_name| !flagged_cards.contains(&(detected_name.window_index, detected_name.card_index)))
+        .collect();
+    if unflagged.is_empty() {
+        return Row::new().into();
+    }
+
+    let multiple_windows = unflagged.iter().any(|detected_name| detected_name.window_index > 0);
+    let mut seat_column = Column::new().align_items(Alignment::Start).spacing(4).padding(5);
+    let mut name_column = Column::new().align_items(Alignment::Start).spacing(4).padding(5);
+    let mut add_column = Column::new().align_items(Alignment::Start).spacing(4).padding(5);
+    for detected_name in unflagged {
+        let seat = if multiple_windows {
+            format!("Lobby {} Seat {}", detected_name.window_index + 1, detected_name.card_index + 1)
+        } else {
+            format!("Seat {}", detected_name.card_index + 1)
+        };
+        seat_column = seat_column.push(text(seat).style(grey()));
+        name_column = name_column.push(text(&detected_name.name).style(silver()).shaping(text::Shaping::Advanced));
+        add_column = add_column.push(
+            widget::Button::new(text("add to blacklist").style(silver()).font(italic()))
+                .padding(0)
+                .style(iced::theme::Button::Text)
+                .on_press(BlitzMessage::BlacklistDetected(detected_name.name.clone()))
+        );
+    }
+
+    Column::new()
+        .align_items(Alignment::Center)
+        .padding(pad(6, 14, 14, 0))
+        .push(text("Unflagged players").style(grey()).font(italic()))
+        .push(Row::new().push(seat_column).push(name_column).push(add_column))
+        .into()
+}
+
+/// Whether the cooldown since the last scan finished has elapsed, so another scan may start.
+///
+/// # Arguments
+/// * `last_scan_finished` - When the last scan finished, or [`None`] if nothing has been scanned.
+/// * `now` - When the new scan was triggered.
+/// * `cooldown` - The minimum time between scans.
+fn cooldown_elapsed(last_scan_finished: Option<Instant>, now: Instant, cooldown: Duration) -> bool {
+    match last_scan_finished {
+        Some(last_scan_finished) => now.saturating_duration_since(last_scan_finished) >= cooldown,
+        None => true,
+    }
+}
+
+/// Sorts results for display: pinned results first, then by window and seat, then by descending similarity
+/// within each seat.
+///
+/// # Arguments
+/// * `results` - The results to sort.
+fn sort_results(results: &mut [&ScanInfo]) {
+    results.sort_by(|a, b| {
+        b.pinned.cmp(&a.pinned)
+            .then(a.window_index.cmp(&b.window_index))
+            .then(a.card_index.cmp(&b.card_index))
+            .then(b.similarity.cmp(&a.similarity))
+    });
+}
+
+/// Whether the results should be cleared because nothing has matched for the timeout.
+///
+/// # Arguments
+/// * `last_match` - When a scan last flagged anyone, or [`None`] if no results are shown.
+/// * `now` - The time to check.
+/// * `timeout` - How long results are kept without a new match, or [`None`] to keep them forever.
+fn should_auto_clear(last_match: Option<Instant>, now: Instant, timeout: Option<Duration>) -> bool {
+    match (last_match, timeout) {
+        (Some(last_match), Some(timeout)) => now.saturating_duration_since(last_match) >= timeout,
+        _ => false,
+    }
+}
+
+/// Filters the results by the threshold for display, returning the ones to list and how many more
+/// were hidden by the limit. The pinned and strongest matches are kept, then listed with the pinned
+/// ones first and the rest grouped by seat, each seat in descending order. This is pure over the
+/// results, so the threshold can be tuned live without re-scanning.
+///
+/// # Arguments
+/// * `scans` - The results of the last scan, unfiltered.
+/// * `similarity_threshold` - The minimum similarity for a result to be listed.
+/// * `max_results` - The most results to list.
+fn displayed_results(scans: &[ScanInfo], similarity_threshold: u8, max_results: usize) -> (Vec<&ScanInfo>, usize) {
+    let mut similar_scans = detector::similar_scans(scans, similarity_threshold);
+    similar_scans.sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.similarity.cmp(&a.similarity)));
+    let (mut similar_scans, hidden_count) = limit_results(similar_scans, max_results);
+    sort_results(&mut similar_scans);
+    (similar_scans, hidden_count)
+}
+
+/// Truncates sorted results to the limit, returning the kept results and how many were dropped.
+///
+/// # Arguments
+/// * `results` - The results, sorted with the ones to keep first.
+/// * `limit` - The most results to keep.
+fn limit_results<T>(mut results: Vec<T>, limit: usize) -> (Vec<T>, usize) {
+    let hidden_count = results.len().saturating_sub(limit);
+    results.truncate(limit);
+    (results, hidden_count)
+}
+
+/// Labels a similarity by the range it falls in.
+///
+/// # Arguments
+/// * `similarity` - The similarity to label.
+/// * `labels` - The lowest similarities labelled "Likely" and "Possible".
+fn similarity_label(similarity: u8, labels: &SimilarityLabels) -> &'static str {
+    if similarity >= labels.likely {
+        "Likely"
+    } else if similarity >= labels.possible {
+        "Possible"
+    } else {
+        "Unlikely"
+    }
+}
+
+/// Formats a similarity for display beside a result.
+///
+/// # Arguments
+/// * `similarity` - The similarity to format.
+/// * `display` - Whether to show the percentage, the label, or both.
+/// * `labels` - The lowest similarities labelled "Likely" and "Possible".
+fn format_similarity(similarity: u8, display: SimilarityDisplay, labels: &SimilarityLabels) -> String {
+    match display {
+        SimilarityDisplay::Percent => format!("({similarity}%)"),
+        SimilarityDisplay::Qualitative => format!("({})", similarity_label(similarity, labels)),
+        SimilarityDisplay::Both => format!("({}, {similarity}%)", similarity_label(similarity, labels)),
+    }
+}
+
+/// Creates a clickable "evidence" link that opens the given URL, or an empty placeholder that keeps
+/// the rows aligned when there is no evidence.
+///
+/// # Arguments
+/// * `evidence_url` - The link to open, if any.
+fn create_evidence_link(evidence_url: Option<&str>) -> Element<'static, BlitzMessage> {
+    match evidence_url {
+        Some(evidence_url) => widget::Button::new(text("evidence").style(silver()).font(italic()))
+            .padding(0)
+            .style(iced::theme::Button::Text)
+            .on_press(BlitzMessage::OpenEvidence(evidence_url.to_string()))
+            .into(),
+        None => text("").into(),
+    }
+}
+
+/// Creates the button [`Row`] for the application view that contains the blacklist,
+/// scan, and support buttons.
+fn create_error_row(error: Option<&str>) -> Element<'static, BlitzMessage> {
+    let message = match error {
+        Some(error) => error,
+        None => ""
+    };
+
+    widget::Row::new()
+        .align_items(Alignment::Center)
+        .push(text(message).style(red()))
+        .into()
+}
+
+/// Creates the preview panel that shows the last capture with the player-list crop region outlined.
+/// The region can be nudged with the arrow buttons, or replaced by dragging out a new one.
+///
+/// # Arguments
+/// * `config` - The [`Config`] holding the current crop region and offsets.
+/// * `preview` - The last capture, or [`None`] if nothing has been captured yet.
+fn create_preview_panel(config: &Config, preview: Option<PreviewCapture>) -> Element<'static, BlitzMessage> {
+    let capture_button = widget::Button::new("Capture")
+        .on_press(BlitzMessage::CapturePreview);
+    let reset_button = widget::Button::new("Reset")
+        .on_press(BlitzMessage::ResetCropOffsets);
+    let nudge_row = Row::new()
+        .align_items(Alignment::Center)
+        .spacing(6)
+        .push(capture_button)
+        .push(widget::Button::new("<").on_press(BlitzMessage::NudgeCrop(-CROP_NUDGE_STEP, 0)))
+        .push(widget::Button::new(">").on_press(BlitzMessage::NudgeCrop(CROP_NUDGE_STEP, 0)))
+        .push(widget::Button::new("^").on_press(BlitzMessage::NudgeCrop(0, -CROP_NUDGE_STEP)))
+        .push(widget::Button::new("v").on_press(BlitzMessage::NudgeCrop(0, CROP_NUDGE_STEP)))
+        .push(reset_button);
+
+    let save_region_button = widget::Button::new("Save Region")
+        .on_press(BlitzMessage::SaveRegion);
+    let clear_region_button = widget::Button::new("Clear Region")
+        .on_press(BlitzMessage::ClearRegion);
+    let region_row = Row::new()
+        .align_items(Alignment::Center)
+        .spacing(6)
+        .push(save_region_button)
+        .push(clear_region_button);
+
+    let offset_text = text(format!("Offset: x {}px, y {}px", config.crop_offset_x, config.crop_offset_y))
+        .style(silver());
+
+    let mut preview_column = Column::new()
+        .align_items(Alignment::Center)
+        .spacing(6)
+        .padding(pad(10, 14, 14, 0))
+        .push(nudge_row)
+        .push(region_row)
+        .push(offset_text);
+
+    preview_column = match preview {
+        Some(preview) => {
+            let player_list_rect = detector::resolve_player_list_rect(preview.width, preview.height, &preview.content, config);
+            let highlight = Region::from_rect(&player_list_rect, preview.width, preview.height);
+            preview_column.push(
+                RegionSelector::new(preview.handle, preview.width, preview.height, BlitzMessage::RegionSelected)
+                    .highlight(Some(highlight))
+                    .height(150f32)
+            )
+        }
+        None => preview_column.push(text("Press CAPTURE, then drag out the player list to crop.")),
+    };
+
+    preview_column.into()
+}
+
+/// Creates the leaderboard panel that lists how often each blacklisted player has been flagged
+/// across all scans, most flagged first.
+///
+/// # Arguments
+/// * `leaderboard` - The leaderboard rows to display.
+fn create_leaderboard_panel(leaderboard: &[LeaderboardRow]) -> Element<'static, BlitzMessage> {
+    let export_button = widget::Button::new("Export")
+        .on_press(BlitzMessage::ExportLeaderboard);
+
+    let leaderboard_column = Column::new()
+        .align_items(Alignment::Center)
+        .spacing(6)
+        .padding(pad(10, 14, 14, 0))
+        .push(export_button);
+
+    if leaderboard.is_empty() {
+        return leaderboard_column
+            .push(text("Nobody has been flagged yet.").shaping(text::Shaping::Advanced))
+            .into();
+    }
+
+    let mut username_column = Column::new().align_items(Alignment::Start).padding(5);
+    let mut count_column = Column::new().align_items(Alignment::End).padding(5);
+    let mut last_seen_column = Column::new().align_items(Alignment::Start).padding(5);
+
+    let now = report::unix_timestamp();
+    for row in leaderboard {
+        username_column = username_column.push(text(&row.username).style(silver()));
+        count_column = count_column.push(text(format!("{}x", row.times_flagged)).font(bold()));
+        last_seen_column = last_seen_column.push(text(format_age(now.saturating_sub(row.last_seen))).font(italic()));
+    }
+
+    let leaderboard_row = Row::new()
+        .push(username_column)
+        .push(count_column)
+        .push(last_seen_column);
+
+    leaderboard_column
+        .push(scrollable(leaderboard_row).height(Length::Fixed(150f32)))
+        .into()
+}
+
+/// Gets the reason shown in a result row. The moron's note is left out to keep the row compact, and
+/// friendly players aren't blacklisted, so they have no reason.
+///
+/// # Arguments
+/// * `scan` - The result to show.
+fn result_reason(scan: &ScanInfo) -> &str {
+    if scan.friendly.is_some() { "" } else { scan.reason.as_str() }
+}
+
+/// Gets the reason shown in the blacklist panel, followed by the moron's note, if they have one.
+///
+/// # Arguments
+/// * `moron` - The blacklisted player to show.
+fn blacklist_reason(moron: &Moron) -> String {
+    match &moron.note {
+        Some(note) => format!("{} ({note})", moron.reason),
+        None => moron.reason.clone(),
+    }
+}
+
+/// Creates a flagged result's username. If the moron has a note, it's shown when hovering over the
+/// username, keeping the result row compact.
+///
+/// # Arguments
+/// * `username` - The detected username.
+/// * `note` - The moron's private note, if it has one.
+/// * `color` - The color to display the username in.
+/// * `font` - The font to display the username in.
+fn create_username(username: &str, note: Option<&str>, color: iced::Color, font: iced::Font) -> Element<'static, BlitzMessage> {
+    let username = text(username).style(color).font(font);
+    match note {
+        Some(note) => widget::tooltip(username, text(note).style(silver()).font(italic()), widget::tooltip::Position::Bottom)
+            .style(iced::theme::Container::Box)
+            .into(),
+        None => username.into(),
+    }
+}
+
+/// Creates the blacklist panel that lists every blacklisted player with why they were blacklisted
+/// (followed by their note, if any), how long ago they were added if that's known, and, where there
+/// is one, a link to the evidence. Fuzzy entries whose names are too generic are marked in red with
+/// a suggestion to use exact mode. Morons can be imported from, and exported to, CSV files, and a
+/// shared blacklist can be synced in. A moron can also be added by typing their username and
+/// reason.
+///
+/// # Arguments
+/// * `morons` - The blacklisted players to display.
+/// * `min_username_length` - The shortest username matched fuzzily without a warning.
+/// * `moron_draft` - The moron being typed in, before it's added.
+fn create_blacklist_panel(morons: &[Moron], min_username_length: usize, moron_draft: &MoronDraft) -> Element<'static, BlitzMessage> {
+    let blacklist_column = Column::new()
+        .align_items(Alignment::Center)
+        .padding(pad(10, 14, 14, 0))
+        .push(create_add_moron_row(moron_draft));
+
+    let csv_row = Row::new()
+        .spacing(10)
+        .push(widget::Button::new("Import CSV").on_press(BlitzMessage::ImportBlacklistCsv))
+        .push(widget::Button::new("Export CSV").on_press(BlitzMessage::ExportBlacklistCsv))
+        .push(widget::Button::new("Sync").on_press(BlitzMessage::SyncBlacklist));
+
+    if morons.is_empty() {
+        return blacklist_column
+            .spacing(10)
+            .push(text("The blacklist is empty.").shaping(text::Shaping::Advanced))
+            .push(csv_row)
+            .into();
+    }
+
+    let mut username_column = Column::new().align_items(Alignment::Start).padding(5);
+    let mut reason_column = Column::new().align_items(Alignment::Start).padding(5);
+    let mut added_column = Column::new().align_items(Alignment::Start).padding(5);
+    let mut evidence_column = Column::new().align_items(Alignment::Start).padding(5);
+
+    let now = chrono::Utc::now();
+    for moron in morons {
+        // Pattern entries' usernames are only labels, so they can't be too generic.
+        let generic_name = match moron.mode {
+            MatchMode::Fuzzy if moron.pattern.is_none() => blacklist::generic_name(&moron.username, min_username_length),
+            MatchMode::Fuzzy | MatchMode::Exact => None,
+        };
+        match generic_name {
+            Some(generic_name) => {
+                username_column = username_column.push(text(&moron.username).style(red()).font(bold()));
+                reason_column = reason_column.push(text(format!("Name {generic_name} - use exact mode")).style(red()));
+            }
+            None => {
+                username_column = username_column.push(text(&moron.username).style(silver()).font(bold()));
+                reason_column = reason_column.push(text(blacklist_reason(moron)).style(silver()));
+            }
+        }
+        // Dates in the future (e.g. from a skewed clock) count as just now.
+        let added = moron.added
+            .map(|added| format_age((now - added).num_seconds().max(0) as u64))
+            .unwrap_or_default();
+        added_column = added_column.push(text(added).style(silver()).font(italic()));
+        evidence_column = evidence_column.push(create_evidence_link(moron.evidence_url.as_deref()));
+    }
+
+    let blacklist_row = Row::new()
+        .push(username_column)
+        .push(reason_column)
+        .push(added_column)
+        .push(evidence_column);
+
+    blacklist_column
+        .spacing(10)
+        .push(scrollable(blacklist_row).height(Length::Fixed(150f32)))
+        .push(csv_row)
+        .into()
+}
+
+/// Creates the row for adding a moron to the blacklist, with inputs for their username and reason,
+/// and why the last attempt failed, if it did.
+///
+/// # Arguments
+/// * `moron_draft` - The moron being typed in, before it's added.
+fn create_add_moron_row(moron_draft: &MoronDraft) -> Element<'static, BlitzMessage> {
+    let username_input = widget::text_input("Username", &moron_draft.username)
+        .on_input(BlitzMessage::DraftUsernameChanged)
+        .on_submit(BlitzMessage::AddMoron)
+        .width(Length::Fixed(160f32));
+    let reason_input = widget::text_input("Reason", &moron_draft.reason)
+        .on_input(BlitzMessage::DraftReasonChanged)
+        .on_submit(BlitzMessage::AddMoron)
+        .width(Length::Fixed(220f32));
+    let add_button = widget::Button::new("Add")
+        .on_press(BlitzMessage::AddMoron);
+
+    let input_row = Row::new()
+        .align_items(Alignment::Center)
+        .spacing(10)
+        .push(username_input)
+        .push(reason_input)
+        .push(add_button);
+
+    let mut add_moron_column = Column::new()
+        .align_items(Alignment::Center)
+        .spacing(4)
+        .push(input_row);
+    if let Some(error) = &moron_draft.error {
+        add_moron_column = add_moron_column.push(text(error).style(red()));
+    }
+    add_moron_column.into()
+}
+
+/// Creates the session panel that shows the statistics of the scans run this session, with a button
+/// to reset them.
+///
+/// # Arguments
+/// * `session_stats` - The statistics of the session.
+fn create_session_panel(session_stats: &SessionStats) -> Element<'static, BlitzMessage> {
+    let average_scan_time = session_stats
+        .average_scan_time()
+        .map(|average_scan_time| format!("{:.1}s", average_scan_time.as_secs_f32()))
+        .unwrap_or_else(|| String::from("-"));
+    let stats = [
+        ("Scans", session_stats.scans.to_string()),
+        ("Players seen", session_stats.players_seen.to_string()),
+        ("Morons flagged", session_stats.morons_flagged.to_string()),
+        ("Average scan time", average_scan_time),
+    ];
+
+    let mut label_column = Column::new().align_items(Alignment::Start).padding(5);
+    let mut value_column = Column::new().align_items(Alignment::End).padding(5);
+    for (label, value) in stats {
+        label_column = label_column.push(text(label).style(silver()));
+        value_column = value_column.push(text(value).font(bold()));
+    }
+
+    let reset_button = widget::Button::new("Reset")
+        .on_press(BlitzMessage::ResetSessionStats);
+
+    Column::new()
+        .align_items(Alignment::Center)
+        .padding(pad(10, 14, 14, 0))
+        .push(Row::new().push(label_column).push(value_column))
+        .push(reset_button)
+        .into()
+}
+
+/// Creates the maintenance panel that offers to download each asset again, with the status of the
+/// last download of each.
+///
+/// # Arguments
+/// * `asset_statuses` - The status of each asset downloaded this session.
+fn create_maintenance_panel(asset_statuses: &[(Asset, String)]) -> Element<'static, BlitzMessage> {
+    let mut name_column = Column::new().align_items(Alignment::Start).spacing(6).padding(5);
+    let mut button_column = Column::new().align_items(Alignment::Start).spacing(6).padding(5);
+    let mut status_column = Column::new().align_items(Alignment::Start).spacing(6).padding(5);
+
+    for asset in Asset::ALL {
+        let status = asset_statuses
+            .iter()
+            .find(|(a, _)| *a == asset)
+            .map(|(_, status)| status.clone())
+            .unwrap_or_default();
+        let downloading = status == "Downloading...";
+
+        name_column = name_column.push(text(asset.to_string()).style(silver()).height(Length::Fixed(30f32)));
+        button_column = button_column.push(
+            widget::Button::new("Re-download")
+                .on_press_maybe((!downloading).then_some(BlitzMessage::Redownload(asset)))
+                .height(Length::Fixed(30f32))
+        );
+        status_column = status_column.push(text(status).font(italic()).height(Length::Fixed(30f32)));
+    }
+
+    Row::new()
+        .align_items(Alignment::Start)
+        .padding(pad(10, 14, 14, 0))
+        .push(name_column)
+        .push(button_column)
+        .push(status_column)
+        .into()
+}
+
+/// Formats an age in seconds as a short, human-readable "time ago".
+///
+/// # Arguments
+/// * `age_secs` - The age, in seconds.
+fn format_age(age_secs: u64) -> String {
+    match age_secs {
+        0..=59 => String::from("just now"),
+        60..=3599 => format!("{}m ago", age_secs / 60),
+        3600..=86399 => format!("{}h ago", age_secs / 3600),
+        _ => format!("{}d ago", age_secs / 86400),
+    }
+}
+
+/// Creates the collapsible log panel that tails the most recent log records. The records are
+/// anchored to the bottom so new lines scroll into view, unless the panel is paused.
+///
+/// # Arguments
+/// * `log_records` - The log records to display, oldest first.
+/// * `paused` - Whether the panel is frozen on a snapshot of the records.
+fn create_log_panel(log_records: Vec<String>, paused: bool) -> Element<'static, BlitzMessage> {
+    let pause_button = widget::Button::new(if paused { "Resume" } else { "Pause" })
+        .on_press(BlitzMessage::ToggleLogsPaused);
+
+    let mut log_column = Column::new()
+        .padding(pad(0, 0, 12, 0));
+    for log_record in log_records {
+        log_column = log_column.push(text(log_record).size(12).style(silver()));
+    }
+
+    let log_scrollable = scrollable(log_column)
+        .height(Length::Fixed(110f32))
+        .direction(scrollable::Direction::Vertical(
+            scrollable::Properties::new().alignment(scrollable::Alignment::End)
+        ));
+
+    Column::new()
+        .align_items(Alignment::Start)
+        .spacing(4)
+        .padding(pad(6, 14, 14, 6))
+        .push(pause_button)
+        .push(log_scrollable)
+        .into()
+}
+
+/// Constructs a new [`iced::Padding`] with the specified padding values.
+///
 /// # Arguments
 /// * `top` - The value for the top edge of the padding.
 /// * `left` - The value for the left edge of the padding.
@@ -238,9 +2160,64 @@ fn red() -> iced::Color {
     color!(255, 0, 0)
 }
 
-/// Constructs a grey [`iced::Color`].
+/// Constructs a yellow [`iced::Color`].
+fn yellow() -> iced::Color {
+    color!(255, 193, 7)
+}
+
+/// Constructs an orange [`iced::Color`].
+fn orange() -> iced::Color {
+    color!(255, 128, 0)
+}
+
+/// Gets the [`iced::Color`] a moron of the given severity is labelled with.
+///
+/// # Arguments
+/// * `severity` - How much of a threat the moron is.
+fn severity_color(severity: Severity) -> iced::Color {
+    match severity {
+        Severity::Low => yellow(),
+        Severity::Medium => orange(),
+        Severity::High => red(),
+    }
+}
+
+/// Constructs a green [`iced::Color`].
+fn green() -> iced::Color {
+    color!(0, 200, 83)
+}
+
+/// Constructs a dim grey [`iced::Color`], for content that is out of date.
+fn grey() -> iced::Color {
+    color!(110, 110, 110)
+}
+
+/// Constructs a grey [`iced::Color`] for text, dark enough to read on the light theme.
 fn silver() -> iced::Color {
-    color!(237, 237, 237)
+    if LIGHT_THEME.load(Ordering::Relaxed) {
+        color!(60, 60, 60)
+    } else {
+        color!(237, 237, 237)
+    }
+}
+
+/// Resolves the configured theme to the [`Theme`] to show, asking the OS for the system theme.
+/// Text colours are switched to match.
+///
+/// # Arguments
+/// * `theme` - The configured theme.
+fn resolve_theme(theme: AppTheme) -> Theme {
+    let light = match theme {
+        AppTheme::Dark => false,
+        AppTheme::Light => true,
+        AppTheme::System => dark_light::detect() == dark_light::Mode::Light,
+    };
+    LIGHT_THEME.store(light, Ordering::Relaxed);
+    if light {
+        Theme::KanagawaLotus
+    } else {
+        Theme::KanagawaDragon
+    }
 }
 
 /// Creates a bold [`iced::Font`].
@@ -257,4 +2234,255 @@ fn italic() -> iced::Font {
         style: Style::Italic,
         ..Default::default()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::tests::scan_info;
+
+    fn usernames(results: &[&ScanInfo]) -> Vec<String> {
+        results.iter().map(|result| result.username.clone()).collect()
+    }
+
+    /// Creates an app that has already shown the results of a scan. Must be called within a Tokio
+    /// runtime.
+    fn app_with_results(config: Config, scans: Vec<ScanInfo>) -> BlitzApp {
+        BlitzApp {
+            view: View::Scan,
+            error: None,
+            scans,
+            detected_names: Vec::new(),
+            previous_scans: None,
+            cleared_scans: None,
+            last_report: None,
+            done_initial_scan: true,
+            show_logs: false,
+            logs_paused: false,
+            paused_logs: Vec::new(),
+            preview: None,
+            session_region: None,
+            leaderboard: Vec::new(),
+            morons: Vec::new(),
+            watcher: create_watcher(&config),
+            last_scan_finished: None,
+            snoozed_until: None,
+            scanning: false,
+            asset_statuses: Vec::new(),
+            runtime: tokio::runtime::Handle::current(),
+            banner: None,
+            session_stats: SessionStats::default(),
+            last_match: None,
+            scan_hotkey: None,
+            moron_draft: MoronDraft::default(),
+            theme: Theme::Dark,
+            config,
+        }
+    }
+
+    #[tokio::test]
+    async fn previous_results_stay_while_scanning() {
+        let config = Config { keep_previous_results: true, ..Config::default() };
+        let mut app = app_with_results(config, vec![scan_info("Moron", 90)]);
+
+        app.start_scan();
+        assert!(app.scanning);
+        assert!(app.done_initial_scan);
+        assert_eq!(app.scans.len(), 1);
+
+        // A failed scan leaves the previous results in place.
+        let _ = app.finish_scan(Err(String::from("Unable to find RISK window.")));
+        assert!(!app.scanning);
+        assert_eq!(app.scans.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn previous_results_clear_while_scanning_when_configured() {
+        let config = Config { keep_previous_results: false, ..Config::default() };
+        let mut app = app_with_results(config, vec![scan_info("Moron", 90)]);
+
+        app.start_scan();
+        assert!(app.scanning);
+        assert!(!app.done_initial_scan);
+        assert!(app.scans.is_empty());
+    }
+
+    #[tokio::test]
+    async fn cleared_results_are_still_compared_against() {
+        let config = Config { keep_previous_results: false, ..Config::default() };
+        let similarity_threshold = config.similarity_threshold;
+        let mut app = app_with_results(config, Vec::new());
+        app.done_initial_scan = false;
+
+        app.start_scan();
+        app.show_scan_results(vec![scan_info("Moron", 90)]);
+        assert!(app.previous_scans.is_none());
+        assert!(alerts::should_alert_new(app.previous_scans.as_deref(), &app.scans, similarity_threshold));
+
+        app.start_scan();
+        assert!(app.scans.is_empty());
+        app.show_scan_results(vec![scan_info("Moron", 90), scan_info("Newcomer", 90)]);
+        let scan_diff = history::diff_flagged(app.previous_scans.as_deref().unwrap(), &app.scans, similarity_threshold);
+        assert_eq!(scan_diff.joined, vec!["Newcomer"]);
+        assert!(scan_diff.left.is_empty());
+        assert!(alerts::should_alert_new(app.previous_scans.as_deref(), &app.scans, similarity_threshold));
+
+        app.start_scan();
+        app.show_scan_results(vec![scan_info("Newcomer", 90)]);
+        let scan_diff = history::diff_flagged(app.previous_scans.as_deref().unwrap(), &app.scans, similarity_threshold);
+        assert!(scan_diff.joined.is_empty());
+        assert_eq!(scan_diff.left, vec!["Moron"]);
+        assert!(!alerts::should_alert_new(app.previous_scans.as_deref(), &app.scans, similarity_threshold));
+    }
+
+    #[tokio::test]
+    async fn results_cleared_before_a_failed_scan_are_compared_against_the_next() {
+        let config = Config { keep_previous_results: false, ..Config::default() };
+        let mut app = app_with_results(config, vec![scan_info("Moron", 90)]);
+
+        app.start_scan();
+        let _ = app.finish_scan(Err(String::from("Unable to find RISK window.")));
+        app.start_scan();
+        app.show_scan_results(vec![scan_info("Moron", 90)]);
+
+        assert_eq!(app.previous_scans.as_ref().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn threshold_refilters_the_cached_results() {
+        let scans = vec![scan_info("Strong", 95), scan_info("Middling", 75), scan_info("Weak", 55)];
+
+        let (strict, _) = displayed_results(&scans, 90, 10);
+        assert_eq!(usernames(&strict), vec!["Strong"]);
+        let (loose, _) = displayed_results(&scans, 50, 10);
+        assert_eq!(usernames(&loose), vec!["Strong", "Middling", "Weak"]);
+        // The cached results themselves are untouched.
+        assert_eq!(scans.len(), 3);
+    }
+
+    #[test]
+    fn cooldown_drops_scans_until_it_elapses() {
+        let finished = Instant::now();
+        let cooldown = Duration::from_secs(5);
+        assert!(cooldown_elapsed(None, finished, cooldown));
+        assert!(!cooldown_elapsed(Some(finished), finished + Duration::from_secs(4), cooldown));
+        assert!(cooldown_elapsed(Some(finished), finished + Duration::from_secs(5), cooldown));
+        // A trigger timestamped before the last scan finished is still within the cooldown.
+        assert!(!cooldown_elapsed(Some(finished + Duration::from_secs(1)), finished, cooldown));
+    }
+
+    #[test]
+    fn limit_results_below_the_limit_keeps_everything() {
+        assert_eq!(limit_results(vec![1, 2, 3], 5), (vec![1, 2, 3], 0));
+        assert_eq!(limit_results(vec![1, 2, 3], 3), (vec![1, 2, 3], 0));
+    }
+
+    #[test]
+    fn limit_results_above_the_limit_counts_the_hidden() {
+        assert_eq!(limit_results(vec![1, 2, 3, 4, 5], 2), (vec![1, 2], 3));
+        assert_eq!(limit_results(vec![1, 2], 0), (vec![], 2));
+    }
+
+    #[test]
+    fn similarity_label_boundaries_belong_to_the_higher_label() {
+        let labels = SimilarityLabels::default();
+        assert_eq!(similarity_label(100, &labels), "Likely");
+        assert_eq!(similarity_label(90, &labels), "Likely");
+        assert_eq!(similarity_label(89, &labels), "Possible");
+        assert_eq!(similarity_label(75, &labels), "Possible");
+        assert_eq!(similarity_label(74, &labels), "Unlikely");
+        assert_eq!(similarity_label(0, &labels), "Unlikely");
+    }
+
+    #[test]
+    fn similarity_formats_in_each_display_mode() {
+        let labels = SimilarityLabels::default();
+        assert_eq!(format_similarity(87, SimilarityDisplay::Percent, &labels), "(87%)");
+        assert_eq!(format_similarity(87, SimilarityDisplay::Qualitative, &labels), "(Possible)");
+        assert_eq!(format_similarity(87, SimilarityDisplay::Both, &labels), "(Possible, 87%)");
+    }
+
+    #[test]
+    fn animated_banner_shows_its_first_frame() {
+        use image::codecs::gif::GifEncoder;
+        use image::{Frame, Rgba, RgbaImage};
+
+        let banner_path = crate::paths::tests::temp_path("banner.gif");
+        {
+            let mut gif_encoder = GifEncoder::new(std::fs::File::create(&banner_path).unwrap());
+            for color in [Rgba([255, 0, 0, 255]), Rgba([0, 0, 255, 255])] {
+                gif_encoder.encode_frame(Frame::new(RgbaImage::from_pixel(8, 4, color))).unwrap();
+            }
+        }
+
+        let banner_image = decode_banner(&banner_path).unwrap();
+        std::fs::remove_file(&banner_path).unwrap();
+        assert_eq!(banner_image.dimensions(), (8, 4));
+        assert_eq!(*banner_image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn corrupt_banner_is_skipped() {
+        let banner_path = crate::paths::tests::temp_path("corrupt-banner.gif");
+        std::fs::write(&banner_path, b"GIF89a not really").unwrap();
+
+        let decoded = decode_banner(&banner_path);
+        std::fs::remove_file(&banner_path).unwrap();
+        assert!(decoded.is_err());
+    }
+
+    #[test]
+    fn pinned_result_sorts_above_a_stronger_unpinned_one() {
+        let scans = [
+            ScanInfo { card_index: 0, ..scan_info("Stronger", 98) },
+            ScanInfo { card_index: 4, pinned: true, ..scan_info("Pinned", 72) },
+        ];
+
+        let mut results: Vec<&ScanInfo> = scans.iter().collect();
+        sort_results(&mut results);
+        assert_eq!(usernames(&results), vec!["Pinned", "Stronger"]);
+    }
+
+    #[test]
+    fn pinned_result_survives_the_result_limit() {
+        let scans = vec![
+            scan_info("Stronger", 98),
+            ScanInfo { card_index: 1, ..scan_info("Strong", 95) },
+            ScanInfo { card_index: 4, pinned: true, ..scan_info("Pinned", 72) },
+        ];
+
+        let (results, hidden_count) = displayed_results(&scans, 70, 2);
+        assert_eq!(usernames(&results), vec!["Pinned", "Stronger"]);
+        assert_eq!(hidden_count, 1);
+    }
+
+    #[test]
+    fn results_clear_once_nothing_has_matched_for_the_timeout() {
+        let last_match = Instant::now();
+        let timeout = Some(Duration::from_secs(60));
+        assert!(!should_auto_clear(Some(last_match), last_match + Duration::from_secs(59), timeout));
+        assert!(should_auto_clear(Some(last_match), last_match + Duration::from_secs(60), timeout));
+    }
+
+    #[test]
+    fn results_never_clear_without_a_timeout_or_a_match() {
+        let now = Instant::now();
+        assert!(!should_auto_clear(Some(now), now + Duration::from_secs(3600), None));
+        assert!(!should_auto_clear(None, now, Some(Duration::ZERO)));
+    }
+
+    #[test]
+    fn note_shows_in_the_blacklist_panel_but_not_the_result_row() {
+        let moron = Moron {
+            note: Some(String::from("toxic in chat")),
+            ..Moron::new(String::from("Some Moron"), String::from("Backstabber"))
+        };
+        let scan = ScanInfo {
+            reason: moron.reason.clone(),
+            note: moron.note.clone(),
+            ..scan_info("Some Moron", 90)
+        };
+
+        assert_eq!(blacklist_reason(&moron), "Backstabber (toxic in chat)");
+        assert_eq!(result_reason(&scan), "Backstabber");
+    }
+}
@@ -0,0 +1,184 @@
+//! Module for the headless `bench` subcommand, which times the scan pipeline against a fixture.
+//!
+//! The subcommand runs the crop, OCR, and match phases of [`detector::scan_image`] repeatedly on a
+//! saved screenshot and reports the min/median/max/mean time of each phase, giving a repeatable
+//! number to compare model or code changes against.
+//!
+//! # Example
+//!
+//! ```text
+//! blitz-app bench --image lobby.png --iterations 20
+//! ```
+
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::config::Config;
+use crate::detector::{self, ScanSource, ScanTimings};
+
+/// The number of iterations run when `--iterations` isn't given.
+const DEFAULT_ITERATIONS: usize = 10;
+
+/// The arguments to the `bench` subcommand.
+#[derive(Debug)]
+pub(crate) struct BenchArgs {
+    /// The screenshot to run the pipeline against.
+    pub image: PathBuf,
+    /// How many times to run the pipeline.
+    pub iterations: usize,
+}
+
+/// Summary statistics over a set of timings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TimingStats {
+    /// The shortest timing.
+    pub min: Duration,
+    /// The middle timing, or the mean of the two middle timings for an even count.
+    pub median: Duration,
+    /// The longest timing.
+    pub max: Duration,
+    /// The mean timing.
+    pub mean: Duration,
+}
+
+impl BenchArgs {
+    /// Parses the arguments that follow `bench` on the command line.
+    ///
+    /// # Arguments
+    /// * `args` - The arguments after the subcommand name.
+    pub(crate) fn parse(args: &[String]) -> anyhow::Result<Self> {
+        let mut image = None;
+        let mut iterations = DEFAULT_ITERATIONS;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--image" => {
+                    let value = args.next().ok_or(anyhow::anyhow!("--image requires a path."))?;
+                    image = Some(PathBuf::from(value));
+                }
+                "--iterations" => {
+                    let value = args.next().ok_or(anyhow::anyhow!("--iterations requires a number."))?;
+                    iterations = value.parse()
+                        .map_err(|_| anyhow::anyhow!("--iterations must be a positive number, not '{value}'."))?;
+                }
+                other => anyhow::bail!("Unknown bench argument '{other}'."),
+            }
+        }
+
+        let image = image.ok_or(anyhow::anyhow!("Usage: bench --image <path> [--iterations N]"))?;
+        if iterations == 0 {
+            anyhow::bail!("--iterations must be a positive number.");
+        }
+
+        Ok(Self { image, iterations })
+    }
+}
+
+/// Computes the summary statistics of a set of timings, or [`None`] if there are none.
+///
+/// # Arguments
+/// * `durations` - The timings to summarise.
+pub(crate) fn timing_stats(durations: &[Duration]) -> Option<TimingStats> {
+    if durations.is_empty() {
+        return None;
+    }
+
+    let mut sorted = durations.to_vec();
+    sorted.sort();
+
+    let middle = sorted.len() / 2;
+    let median = if sorted.len() % 2 == 0 {
+        (sorted[middle - 1] + sorted[middle]) / 2
+    } else {
+        sorted[middle]
+    };
+
+    let total: Duration = sorted.iter().sum();
+
+    Some(TimingStats {
+        min: sorted[0],
+        median,
+        max: sorted[sorted.len() - 1],
+        mean: total / sorted.len() as u32,
+    })
+}
+
+/// Runs the `bench` subcommand, printing the timing statistics of each phase to stdout.
+///
+/// # Arguments
+/// * `args` - The arguments after the subcommand name.
+/// * `config` - A reference to the [`Config`] to scan with.
+pub(crate) fn run(args: &[String], config: &Config) -> anyhow::Result<()> {
+    let bench_args = BenchArgs::parse(args)?;
+
+    let mut runs: Vec<ScanTimings> = Vec::with_capacity(bench_args.iterations);
+    for iteration in 0..bench_args.iterations {
+        let scan_result = detector::scan_image(&bench_args.image, ScanSource::File, config)?;
+        log::debug!("Bench iteration {} took {:?}.", iteration + 1, scan_result.timings);
+        runs.push(scan_result.timings);
+    }
+
+    println!("{} iterations of {}", bench_args.iterations, bench_args.image.display());
+    println!("{:<10} {:>12} {:>12} {:>12} {:>12}", "phase", "min", "median", "max", "mean");
+
+    let phases: [(&str, fn(&ScanTimings) -> Duration); 3] = [
+        ("crop", |timings| timings.crop),
+        ("ocr", |timings| timings.ocr),
+        ("matching", |timings| timings.matching),
+    ];
+    for (phase, phase_duration) in phases {
+        let durations: Vec<Duration> = runs.iter().map(phase_duration).collect();
+        if let Some(stats) = timing_stats(&durations) {
+            println!(
+                "{:<10} {:>12} {:>12} {:>12} {:>12}",
+                phase,
+                format!("{:.2?}", stats.min),
+                format!("{:.2?}", stats.median),
+                format!("{:.2?}", stats.max),
+                format!("{:.2?}", stats.mean),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(durations: &[u64]) -> Vec<Duration> {
+        durations.iter().map(|duration| Duration::from_millis(*duration)).collect()
+    }
+
+    #[test]
+    fn timing_stats_over_an_odd_count() {
+        assert_eq!(timing_stats(&millis(&[30, 10, 20])), Some(TimingStats {
+            min: Duration::from_millis(10),
+            median: Duration::from_millis(20),
+            max: Duration::from_millis(30),
+            mean: Duration::from_millis(20),
+        }));
+    }
+
+    #[test]
+    fn timing_stats_over_an_even_count_average_the_middle_pair() {
+        assert_eq!(timing_stats(&millis(&[40, 10, 20, 10])), Some(TimingStats {
+            min: Duration::from_millis(10),
+            median: Duration::from_millis(15),
+            max: Duration::from_millis(40),
+            mean: Duration::from_millis(20),
+        }));
+    }
+
+    #[test]
+    fn timing_stats_over_nothing() {
+        assert_eq!(timing_stats(&[]), None);
+    }
+
+    #[test]
+    fn parse_rejects_zero_iterations() {
+        let args = ["--image", "lobby.png", "--iterations", "0"].map(String::from);
+        assert!(BenchArgs::parse(&args).is_err());
+    }
+}
@@ -1,24 +1,40 @@
 //! This module provides structures and methods for managing a blacklist of users.
 //!
 //! The [`Blacklist`] struct represents a list of blacklisted users, where each user is represented
-//! by a [`Moron`] struct containing their username and the reason for blacklisting.
+//! by a [`Moron`] struct containing their username, the reason for blacklisting, and optionally a
+//! link to evidence (e.g. a clip or screenshot). Whole clans can be blacklisted by tag with a
+//! [`ClanEntry`], flagging any player whose name carries the tag in brackets (e.g. `[BAD]SomeName`).
+//!
+//! Each moron is matched fuzzily by default. Short or ambiguous names can instead use
+//! [`MatchMode::Exact`], optionally case-sensitive to tell apart names like "IL" and "Il". Morons
+//! who play under several names can list the others as `aliases`. Entries with a `pattern` flag
+//! every name matching a wildcard pattern instead, e.g. `*bot`.
 //!
 //! # Examples
 //!
 //! ```rust
-//! use crate::blacklist::{Blacklist, Moron};
+//! use crate::blacklist::{Blacklist, MatchMode, Moron, Severity};
 //!
 //! fn main() -> anyhow::Result<()> {
 //!     // Load existing blacklist from file
 //!     let blacklist_path = std::path::PathBuf::from("blacklist.json");
-//!     let blacklist = Blacklist::load(&blacklist_path)?;
+//!     let mut blacklist = Blacklist::load(&blacklist_path)?;
 //!
-//!     // Add a new moron to the blacklist
+//!     // Add a new moron to the blacklist, dated as it's added
 //!     let new_moron = Moron {
 //!         username: String::from("New Moron"),
 //!         reason: String::from("Repeated spamming"),
+//!         evidence_url: Some(String::from("https://example.com/clip")),
+//!         mode: MatchMode::Fuzzy,
+//!         case_sensitive: false,
+//!         severity: Severity::Low,
+//!         pinned: false,
+//!         pattern: None,
+//!         note: None,
+//!         aliases: vec![String::from("New Moron 2")],
+//!         added: None,
 //!     };
-//!     blacklist.add_moron(new_moron);
+//!     blacklist.add_moron(new_moron)?;
 //!
 //!     // Save the updated blacklist to file
 //!     blacklist.save(&blacklist_path)?;
@@ -27,7 +43,10 @@
 //! }
 //! ```
 
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
 use std::path::PathBuf;
+use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 
 /// Blacklist containing a list of [`Moron`].
@@ -35,27 +54,348 @@ use serde::{Serialize, Deserialize};
 pub struct Blacklist {
     /// The list of blacklisted morons.
     pub morons: Vec<Moron>,
+    /// The prefixes (ranks, clan markers, decorations) stripped from detected text before matching.
+    ///
+    /// Blacklists written before this field existed fall back to [`default_prefixes`].
+    #[serde(default = "default_prefixes")]
+    pub prefixes: Vec<String>,
+    /// The blacklisted clans. Blacklists written before this field existed have none.
+    #[serde(default)]
+    pub clans: Vec<ClanEntry>,
 }
 
 /// A blacklisted moron.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Moron {
     /// The moron's username.
     pub username: String,
     /// Why the moron is blacklisted.
-    pub reason: String
+    pub reason: String,
+    /// A link to proof of the moron's behaviour, such as a clip or screenshot.
+    ///
+    /// Entries written before this field existed have no evidence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence_url: Option<String>,
+    /// How detections are compared against the moron's username.
+    #[serde(default)]
+    pub mode: MatchMode,
+    /// Whether an [`MatchMode::Exact`] entry must also match case. Ignored for fuzzy entries.
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// How much of a threat the moron is. Entries written before this field existed are medium.
+    #[serde(default)]
+    pub severity: Severity,
+    /// Whether the moron is a priority target whose matches are listed above all others.
+    #[serde(default)]
+    pub pinned: bool,
+    /// A glob-style pattern (e.g. `troll*` or `*bot`) that flags any matching name instead of
+    /// comparing against the username, which then only labels the entry. `*` matches any run of
+    /// characters and `?` any single character. Patterns ignore case and spaces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// A private note about the moron (e.g. "disputed"). It never affects matching, and is only
+    /// shown in the blacklist view and when hovering over a result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Other names the moron plays under. Detections are matched against the username and every
+    /// alias, but results always show the username.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// When the moron was added to the blacklist. Entries written before this field existed, or
+    /// added by editing the file, have no date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<DateTime<Utc>>,
+}
+
+impl Moron {
+    /// Creates a moron matched fuzzily at medium severity, with no evidence, note, aliases, or date
+    /// added.
+    ///
+    /// # Arguments
+    /// * `username` - The moron's username.
+    /// * `reason` - Why the moron is blacklisted.
+    pub fn new(username: String, reason: String) -> Self {
+        Self {
+            username,
+            reason,
+            evidence_url: None,
+            mode: MatchMode::default(),
+            case_sensitive: false,
+            severity: Severity::default(),
+            pinned: false,
+            pattern: None,
+            note: None,
+            aliases: Vec::new(),
+            added: None,
+        }
+    }
+
+    /// Gets every name the moron is matched by: the username, then each alias.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.username.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// How much of a threat a moron is.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// A mild nuisance, such as a spammer.
+    Low,
+    /// A regular moron.
+    #[default]
+    Medium,
+    /// A serious threat, such as a backstabber or griefer.
+    High,
+}
+
+/// How detections are compared against a moron's username.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// Scored by fuzzy similarity, so misreads still match.
+    #[default]
+    Fuzzy,
+    /// Matched only by an identical (normalized) name.
+    Exact,
+}
+
+/// What happens to an entry on both blacklists when merging one into another.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// The local entry is kept as it is.
+    #[default]
+    KeepLocal,
+    /// The local entry is replaced by the other blacklist's.
+    PreferRemote,
+    /// The local entry is kept, with the other blacklist's reason appended if it's different.
+    AppendReasons,
+}
+
+/// A blacklisted clan, identified by the tag its members wear in front of their names.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClanEntry {
+    /// The clan tag, without brackets.
+    pub tag: String,
+    /// Why the clan is blacklisted.
+    pub reason: String,
 }
 
 impl Blacklist {
-    /// Loads and deserializes an existing [`Blacklist`] JSON file into a new [`Blacklist`].
+    /// Loads and deserializes an existing [`Blacklist`] JSON file into a new [`Blacklist`]. Evidence
+    /// links that aren't well-formed URLs are dropped with a warning. Invalid JSON is reported with
+    /// the line and column to fix.
     ///
     /// # Arguments
     /// * `blacklist_path` - A reference to the [`PathBuf`] representing the path to the blacklist file.
     pub fn load(blacklist_path: &PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
         let content = std::fs::read_to_string(blacklist_path)?;
-        let blacklist: Blacklist = serde_json::from_str(&content)?;
+        Self::parse(&content)
+    }
+
+    /// Deserializes a [`Blacklist`] from JSON, such as a shared blacklist fetched for syncing. Evidence
+    /// links that aren't well-formed URLs are dropped with a warning, so one mistyped link doesn't
+    /// break the whole blacklist.
+    ///
+    /// # Arguments
+    /// * `content` - The blacklist JSON.
+    pub fn parse(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut blacklist: Blacklist = serde_json::from_str(content).map_err(|err| describe_json_error(&err))?;
+        for moron in blacklist.morons.iter_mut() {
+            drop_invalid_evidence_url(moron);
+        }
         Ok(blacklist)
     }
+
+    /// Adds a moron to the end of the blacklist, rejecting it if its evidence link isn't a
+    /// well-formed URL. The moron is dated now, unless it already has a date.
+    ///
+    /// # Arguments
+    /// * `moron` - The [`Moron`] to add.
+    pub fn add_moron(&mut self, mut moron: Moron) -> anyhow::Result<()> {
+        if let Some(evidence_url) = &moron.evidence_url {
+            validate_evidence_url(evidence_url)
+                .map_err(|err| anyhow::anyhow!("{}: {err}", moron.username))?;
+        }
+        moron.added.get_or_insert_with(Utc::now);
+        self.morons.push(moron);
+        Ok(())
+    }
+
+    /// Imports morons from a two-column `username,reason` CSV file (e.g. exported from a spreadsheet),
+    /// appending them to the blacklist and returning how many were added. A header row is skipped if
+    /// the file has one. Usernames already on the blacklist, or repeated in the file, are skipped,
+    /// ignoring case. Imported morons are matched fuzzily at medium severity.
+    ///
+    /// # Arguments
+    /// * `csv_path` - A reference to the [`PathBuf`] representing the path to the CSV file.
+    pub fn import_csv(&mut self, csv_path: &PathBuf) -> anyhow::Result<usize> {
+        let mut csv_reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_path(csv_path)
+            .map_err(|err| anyhow::anyhow!("Unable to read {}: {err}", csv_path.display()))?;
+
+        let mut usernames: HashSet<String> = self.morons
+            .iter()
+            .map(|moron| entry_key(&moron.username))
+            .collect();
+        let mut added = 0;
+        for (index, record) in csv_reader.records().enumerate() {
+            let record = record.map_err(|err| anyhow::anyhow!("Invalid CSV on line {}: {err}", index + 1))?;
+            let username = record.get(0).unwrap_or_default();
+            let reason = record.get(1).unwrap_or_default();
+            if index == 0 && username.eq_ignore_ascii_case("username") {
+                continue;
+            }
+            if username.is_empty() || !usernames.insert(entry_key(username)) {
+                continue;
+            }
+
+            self.add_moron(Moron::new(username.to_string(), reason.to_string()))?;
+            added += 1;
+        }
+
+        Ok(added)
+    }
+
+    /// Merges another blacklist into this one, returning how many morons and clans were added. Morons
+    /// are matched by username and clans by tag, ignoring case and surrounding spaces, so "Bob" and
+    /// "bob " are the same entry. The strategy decides what happens to entries on both blacklists.
+    /// Merged-in evidence links that aren't well-formed URLs are dropped with a warning.
+    ///
+    /// # Arguments
+    /// * `other` - The blacklist to merge in, e.g. a shared one fetched for syncing.
+    /// * `strategy` - How entries on both blacklists are merged.
+    pub fn merge(&mut self, other: Blacklist, strategy: MergeStrategy) -> usize {
+        let mut moron_indices: HashMap<String, usize> = self.morons
+            .iter()
+            .enumerate()
+            .map(|(index, moron)| (entry_key(&moron.username), index))
+            .collect();
+        let mut clan_indices: HashMap<String, usize> = self.clans
+            .iter()
+            .enumerate()
+            .map(|(index, clan)| (entry_key(&clan.tag), index))
+            .collect();
+
+        let mut added = 0;
+        for mut moron in other.morons {
+            drop_invalid_evidence_url(&mut moron);
+            let key = entry_key(&moron.username);
+            match moron_indices.get(&key) {
+                Some(&index) => match strategy {
+                    MergeStrategy::KeepLocal => {}
+                    MergeStrategy::PreferRemote => self.morons[index] = moron,
+                    MergeStrategy::AppendReasons => {
+                        self.morons[index].reason = append_reason(&self.morons[index].reason, &moron.reason);
+                    }
+                },
+                None => {
+                    moron_indices.insert(key, self.morons.len());
+                    self.morons.push(moron);
+                    added += 1;
+                }
+            }
+        }
+        for clan in other.clans {
+            let key = entry_key(&clan.tag);
+            match clan_indices.get(&key) {
+                Some(&index) => match strategy {
+                    MergeStrategy::KeepLocal => {}
+                    MergeStrategy::PreferRemote => self.clans[index] = clan,
+                    MergeStrategy::AppendReasons => {
+                        self.clans[index].reason = append_reason(&self.clans[index].reason, &clan.reason);
+                    }
+                },
+                None => {
+                    clan_indices.insert(key, self.clans.len());
+                    self.clans.push(clan);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    /// Exports the morons to a two-column `username,reason` CSV file with a header row, the same
+    /// format [`Blacklist::import_csv`] reads. Fields containing commas, quotes, or newlines are quoted.
+    ///
+    /// # Arguments
+    /// * `csv_path` - A reference to the [`PathBuf`] representing the path to write the CSV file to.
+    pub fn export_csv(&self, csv_path: &PathBuf) -> anyhow::Result<()> {
+        let mut csv_writer = csv::Writer::from_path(csv_path)
+            .map_err(|err| anyhow::anyhow!("Unable to write {}: {err}", csv_path.display()))?;
+        csv_writer.write_record(["username", "reason"])?;
+        for moron in self.morons.iter() {
+            csv_writer.write_record([moron.username.as_str(), moron.reason.as_str()])?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    /// Checks whether a moron with the username is on the blacklist, ignoring case and surrounding
+    /// spaces.
+    ///
+    /// # Arguments
+    /// * `username` - The username to look for.
+    pub fn contains_moron(&self, username: &str) -> bool {
+        let username = entry_key(username);
+        self.morons.iter().any(|moron| entry_key(&moron.username) == username)
+    }
+
+    /// Removes every moron whose username matches, ignoring case, returning whether any were removed.
+    ///
+    /// # Arguments
+    /// * `username` - The username of the moron to remove.
+    pub fn remove_moron(&mut self, username: &str) -> bool {
+        let count = self.morons.len();
+        let username = entry_key(username);
+        self.morons.retain(|moron| entry_key(&moron.username) != username);
+        self.morons.len() != count
+    }
+
+    /// Serializes the [`Blacklist`] and writes it to the blacklist file, creating its parent
+    /// directories if they're missing.
+    ///
+    /// The blacklist is written to a temporary file next to the blacklist file, which is then renamed
+    /// over it, so a crash mid-write never leaves a truncated blacklist behind.
+    ///
+    /// # Arguments
+    /// * `blacklist_path` - A reference to the [`PathBuf`] representing the path to the blacklist file.
+    pub fn save(&self, blacklist_path: &PathBuf) -> anyhow::Result<()> {
+        if let Some(parent) = blacklist_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut temp_path = blacklist_path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+
+        let blacklist_json = serde_json::to_string_pretty(self)?;
+        let mut temp_file = std::fs::File::create(&temp_path)?;
+        temp_file.write_all(blacklist_json.as_ref())?;
+        temp_file.sync_all()?;
+        drop(temp_file);
+
+        std::fs::rename(&temp_path, blacklist_path).map_err(|err| anyhow::anyhow!(
+            "The blacklist was written to {} but couldn't replace {}: {err}",
+            temp_path.display(),
+            blacklist_path.display()
+        ))
+    }
+
+    /// Finds the blacklisted clan whose tag the detected text carries, if any. Tags are compared
+    /// case-insensitively.
+    ///
+    /// # Arguments
+    /// * `detection_text` - The raw text detected on a player card.
+    pub fn find_clan(&self, detection_text: &str) -> Option<&ClanEntry> {
+        let tag = clan_tag(detection_text)?;
+        self.clans.iter().find(|clan| clan.tag.trim().eq_ignore_ascii_case(tag))
+    }
 }
 
 impl Default for Blacklist {
@@ -65,10 +405,398 @@ impl Default for Blacklist {
             morons: vec![Moron {
                 username: String::from("Example User #1"),
                 reason: "Copy and paste the { } block to add more entries".to_string(),
+                evidence_url: None,
+                mode: MatchMode::Fuzzy,
+                case_sensitive: false,
+                severity: Severity::Medium,
+                pinned: false,
+                pattern: None,
+                note: None,
+                aliases: Vec::new(),
+                added: None,
             }, Moron {
                 username: String::from("Example User #2"),
                 reason: "Don't forget the comma at the end of the block.".to_string(),
-            }]
+                evidence_url: None,
+                mode: MatchMode::Fuzzy,
+                case_sensitive: false,
+                severity: Severity::Medium,
+                pinned: false,
+                pattern: None,
+                note: None,
+                aliases: Vec::new(),
+                added: None,
+            }],
+            prefixes: default_prefixes(),
+            clans: Vec::new(),
         }
     }
-}
\ No newline at end of file
+}
+
+/// The separator between reasons appended by [`MergeStrategy::AppendReasons`].
+const REASON_SEPARATOR: &str = "; ";
+
+/// Gets the key a username or clan tag is matched by when deduplicating entries: the name without
+/// surrounding spaces, in lowercase.
+///
+/// # Arguments
+/// * `name` - The username or clan tag.
+fn entry_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Appends a reason to another, unless it's empty or already among the appended reasons (ignoring
+/// case).
+///
+/// # Arguments
+/// * `reason` - The reason to append to.
+/// * `other` - The reason to append.
+fn append_reason(reason: &str, other: &str) -> String {
+    let other = other.trim();
+    let already_given = reason
+        .split(REASON_SEPARATOR)
+        .any(|given| given.trim().eq_ignore_ascii_case(other));
+    if other.is_empty() || already_given {
+        return reason.to_string();
+    }
+    if reason.trim().is_empty() {
+        return other.to_string();
+    }
+    format!("{reason}{REASON_SEPARATOR}{other}")
+}
+
+/// Gets the default prefixes stripped from detected text: the in-game ranks shown before a
+/// player's name.
+pub fn default_prefixes() -> Vec<String> {
+    [
+        "General ",
+        "Field Marshal ",
+        "Brigadier ",
+        "Colonel ",
+        "Major ",
+        "Captain ",
+        "Lieutenant ",
+        "Sergeant ",
+        "Corporal ",
+        "Private ",
+    ]
+    .iter()
+    .map(|prefix| prefix.to_string())
+    .collect()
+}
+
+/// Checks that an evidence link is a well-formed web URL: an `http` or `https` scheme followed by a
+/// host, with no whitespace.
+///
+/// # Arguments
+/// * `evidence_url` - The link to check.
+pub fn validate_evidence_url(evidence_url: &str) -> anyhow::Result<()> {
+    let rest = evidence_url
+        .strip_prefix("https://")
+        .or_else(|| evidence_url.strip_prefix("http://"))
+        .ok_or(anyhow::anyhow!("Evidence link '{evidence_url}' must start with http:// or https://."))?;
+
+    if evidence_url.chars().any(char::is_whitespace) {
+        anyhow::bail!("Evidence link '{evidence_url}' must not contain spaces.");
+    }
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if host.is_empty() || host.starts_with('.') || host.ends_with('.') {
+        anyhow::bail!("Evidence link '{evidence_url}' is missing a host.");
+    }
+
+    Ok(())
+}
+
+/// Drops a moron's evidence link with a warning if it isn't a well-formed URL, keeping the moron.
+///
+/// # Arguments
+/// * `moron` - The moron whose link to check.
+fn drop_invalid_evidence_url(moron: &mut Moron) {
+    let Some(evidence_url) = &moron.evidence_url else {
+        return;
+    };
+    if let Err(err) = validate_evidence_url(evidence_url) {
+        log::warn!("Ignoring the evidence link for '{}': {err}", moron.username);
+        moron.evidence_url = None;
+    }
+}
+
+/// Describes a JSON error with the line and column it was found at, for users editing the file by
+/// hand, e.g. "Invalid JSON at line 12, column 3: trailing comma".
+///
+/// # Arguments
+/// * `err` - The error from parsing the JSON.
+pub fn describe_json_error(err: &serde_json::Error) -> String {
+    let message = err.to_string();
+    // Errors reading the file have no position.
+    if err.line() == 0 {
+        return message;
+    }
+
+    let location = format!(" at line {} column {}", err.line(), err.column());
+    let message = message.strip_suffix(&location).unwrap_or(&message);
+    format!("Invalid JSON at line {}, column {}: {message}", err.line(), err.column())
+}
+
+/// Extracts the clan tag from a player's name: the text inside the first pair of square brackets,
+/// e.g. `BAD` in `[BAD]SomeName`. Returns [`None`] if there is no non-empty bracketed tag.
+///
+/// # Arguments
+/// * `detection_text` - The raw text detected on a player card.
+pub fn clan_tag(detection_text: &str) -> Option<&str> {
+    let (_, after_open) = detection_text.split_once('[')?;
+    let (tag, _) = after_open.split_once(']')?;
+    let tag = tag.trim();
+    if tag.is_empty() {
+        None
+    } else {
+        Some(tag)
+    }
+}
+
+/// Common words that make poor fuzzy-matched usernames, because many real names contain them.
+const COMMON_WORDS: [&str; 24] = [
+    "the", "and", "player", "gamer", "king", "queen", "lord", "risk", "pro", "noob", "game", "bot",
+    "master", "boss", "army", "war", "general", "captain", "guest", "user", "admin", "team", "clan", "love",
+];
+
+/// Why a username is too generic to match fuzzily.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenericName {
+    /// The username is shorter than the minimum length.
+    TooShort,
+    /// The username is a common word.
+    CommonWord,
+}
+
+impl std::fmt::Display for GenericName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GenericName::TooShort => "is too short",
+            GenericName::CommonWord => "is a common word",
+        })
+    }
+}
+
+/// Checks whether a username is too generic to match fuzzily, which guarantees false positives.
+/// Such names should use [`MatchMode::Exact`] instead.
+///
+/// # Arguments
+/// * `username` - The proposed username.
+/// * `min_length` - The minimum length, in characters, ignoring spaces.
+pub fn generic_name(username: &str, min_length: usize) -> Option<GenericName> {
+    let compact: String = username.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.chars().count() < min_length {
+        return Some(GenericName::TooShort);
+    }
+
+    let lowercase = compact.to_lowercase();
+    if COMMON_WORDS.contains(&lowercase.as_str()) {
+        return Some(GenericName::CommonWord);
+    }
+
+    None
+}
+
+/// Checks whether text matches a glob-style pattern, where `*` matches any run of characters
+/// (including none) and `?` matches any single character.
+///
+/// # Arguments
+/// * `pattern` - The pattern to match against.
+/// * `text` - The text to check.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut pattern_index, mut text_index) = (0, 0);
+    // Where to resume from if the characters after the last `*` stop matching.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, text_index));
+                pattern_index += 1;
+            }
+            Some(c) if *c == '?' || *c == text[text_index] => {
+                pattern_index += 1;
+                text_index += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character and try again.
+                Some((star_index, star_text_index)) => {
+                    pattern_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                    backtrack = Some((star_index, star_text_index + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector;
+
+    #[test]
+    fn default_prefixes_apply_when_unset() {
+        let blacklist = Blacklist::parse(r#"{ "morons": [] }"#).unwrap();
+        assert_eq!(blacklist.prefixes, default_prefixes());
+        assert_eq!(detector::normalize("General Some Moron", &blacklist.prefixes), "somemoron");
+    }
+
+    #[test]
+    fn custom_prefixes_are_stripped() {
+        let blacklist = Blacklist::parse(r#"{ "morons": [], "prefixes": ["Warlord "] }"#).unwrap();
+        assert_eq!(blacklist.prefixes, vec![String::from("Warlord ")]);
+        assert_eq!(detector::normalize("Warlord Some Moron", &blacklist.prefixes), "somemoron");
+        // The defaults are replaced, not extended.
+        assert_eq!(detector::normalize("General Some Moron", &blacklist.prefixes), "generalsomemoron");
+    }
+
+    #[test]
+    fn evidence_url_defaults_to_none_on_old_files() {
+        let blacklist = Blacklist::parse(r#"{ "morons": [{ "username": "Some Moron", "reason": "Spam" }] }"#).unwrap();
+        assert_eq!(blacklist.morons[0].evidence_url, None);
+    }
+
+    #[test]
+    fn note_defaults_to_none_on_old_files() {
+        let blacklist = Blacklist::parse(r#"{ "morons": [{ "username": "Some Moron", "reason": "Spam" }] }"#).unwrap();
+        assert_eq!(blacklist.morons[0].note, None);
+    }
+
+    #[test]
+    fn malformed_evidence_url_is_dropped_on_load() {
+        let blacklist = Blacklist::parse(r#"{ "morons": [
+            { "username": "Linked", "reason": "Spam", "evidence_url": "https://example.com/clip" },
+            { "username": "Mistyped", "reason": "Spam", "evidence_url": "example.com/clip" }
+        ] }"#).unwrap();
+        assert_eq!(blacklist.morons.len(), 2);
+        assert_eq!(blacklist.morons[0].evidence_url.as_deref(), Some("https://example.com/clip"));
+        assert_eq!(blacklist.morons[1].evidence_url, None);
+    }
+
+    #[test]
+    fn malformed_evidence_url_is_rejected_on_add() {
+        let mut blacklist = Blacklist { morons: Vec::new(), prefixes: default_prefixes(), clans: Vec::new() };
+        for evidence_url in ["example.com/clip", "https://", "https://example .com", "ftp://example.com"] {
+            let moron = Moron { evidence_url: Some(evidence_url.to_string()), ..Moron::new(String::from("Some Moron"), String::new()) };
+            assert!(blacklist.add_moron(moron).is_err(), "{evidence_url} was accepted");
+        }
+        assert!(blacklist.morons.is_empty());
+
+        let moron = Moron { evidence_url: Some(String::from("https://example.com/clip")), ..Moron::new(String::from("Some Moron"), String::new()) };
+        assert!(blacklist.add_moron(moron).is_ok());
+        assert_eq!(blacklist.morons.len(), 1);
+    }
+
+    #[test]
+    fn short_names_are_generic() {
+        assert_eq!(generic_name("Bob", 4), Some(GenericName::TooShort));
+        // Spaces don't count towards the length.
+        assert_eq!(generic_name("B o b", 4), Some(GenericName::TooShort));
+        assert_eq!(generic_name("Bobby", 4), None);
+    }
+
+    #[test]
+    fn common_words_are_generic_in_any_case() {
+        assert_eq!(generic_name("Player", 4), Some(GenericName::CommonWord));
+        assert_eq!(generic_name("GENERAL", 4), Some(GenericName::CommonWord));
+        assert_eq!(generic_name("Some Moron", 4), None);
+    }
+
+    #[test]
+    fn glob_matches_prefixes() {
+        assert!(glob_match("moron*", "moron"));
+        assert!(glob_match("moron*", "moron123"));
+        assert!(!glob_match("moron*", "xmoron"));
+    }
+
+    #[test]
+    fn glob_matches_suffixes() {
+        assert!(glob_match("*moron", "moron"));
+        assert!(glob_match("*moron", "bigmoron"));
+        assert!(!glob_match("*moron", "moronx"));
+    }
+
+    #[test]
+    fn glob_matches_contained_text() {
+        assert!(glob_match("*moron*", "bigmoron99"));
+        assert!(glob_match("*moron*", "moron"));
+        assert!(!glob_match("*moron*", "moran"));
+    }
+
+    #[test]
+    fn glob_matches_single_characters_and_backtracks() {
+        assert!(glob_match("m?ron", "moron"));
+        assert!(!glob_match("m?ron", "mron"));
+        assert!(glob_match("a*b*c", "axxbyybzc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("", "moron"));
+    }
+
+    fn blacklist_with(morons: &[(&str, &str)]) -> Blacklist {
+        Blacklist {
+            morons: morons.iter().map(|(username, reason)| Moron::new(username.to_string(), reason.to_string())).collect(),
+            prefixes: default_prefixes(),
+            clans: Vec::new(),
+        }
+    }
+
+    fn reasons(blacklist: &Blacklist) -> Vec<(&str, &str)> {
+        blacklist.morons.iter().map(|moron| (moron.username.as_str(), moron.reason.as_str())).collect()
+    }
+
+    #[test]
+    fn merge_keeps_local_entries() {
+        let mut local = blacklist_with(&[("Bob", "Spam")]);
+        let added = local.merge(blacklist_with(&[("bob ", "Teaming"), ("Alice", "Stalling")]), MergeStrategy::KeepLocal);
+        assert_eq!(added, 1);
+        assert_eq!(reasons(&local), vec![("Bob", "Spam"), ("Alice", "Stalling")]);
+    }
+
+    #[test]
+    fn merge_prefers_remote_entries() {
+        let mut local = blacklist_with(&[("Bob", "Spam")]);
+        let added = local.merge(blacklist_with(&[("bob ", "Teaming")]), MergeStrategy::PreferRemote);
+        assert_eq!(added, 0);
+        assert_eq!(reasons(&local), vec![("bob ", "Teaming")]);
+    }
+
+    #[test]
+    fn merge_appends_distinct_reasons() {
+        let mut local = blacklist_with(&[("Bob", "Spam"), ("Carol", "Teaming")]);
+        let added = local.merge(blacklist_with(&[("bob ", "Teaming"), ("CAROL", "teaming")]), MergeStrategy::AppendReasons);
+        assert_eq!(added, 0);
+        assert_eq!(reasons(&local), vec![("Bob", &*format!("Spam{REASON_SEPARATOR}Teaming")), ("Carol", "Teaming")]);
+    }
+
+    #[test]
+    fn merge_drops_malformed_evidence_urls() {
+        let mut local = blacklist_with(&[]);
+        let mut remote = blacklist_with(&[("Bob", "Spam")]);
+        remote.morons[0].evidence_url = Some(String::from("example.com/clip"));
+
+        local.merge(remote, MergeStrategy::KeepLocal);
+        assert_eq!(local.morons.len(), 1);
+        assert_eq!(local.morons[0].evidence_url, None);
+    }
+
+    #[test]
+    fn added_moron_is_dated_unless_it_has_a_date() {
+        let mut blacklist = blacklist_with(&[]);
+        let dated = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let before = Utc::now();
+        blacklist.add_moron(Moron::new(String::from("New Moron"), String::new())).unwrap();
+        blacklist.add_moron(Moron { added: Some(dated), ..Moron::new(String::from("Old Moron"), String::new()) }).unwrap();
+
+        assert!(blacklist.morons[0].added.is_some_and(|added| added >= before));
+        assert_eq!(blacklist.morons[1].added, Some(dated));
+    }
+}
@@ -0,0 +1,401 @@
+//! This module provides the user-adjustable application configuration.
+//!
+//! The [`Config`] struct is persisted as JSON in the app directory. Every field has a default, so
+//! config files written by older versions of the app (or trimmed by hand) still load.
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! use crate::config::Config;
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let config_path = std::path::PathBuf::from("config.json");
+//!     let mut config = Config::load(&config_path)?;
+//!
+//!     // Nudge the crop region 10px to the right.
+//!     config.crop_offset_x += 10;
+//!     config.save(&config_path)?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Write;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use crate::blacklist::MergeStrategy;
+use crate::crop::{AspectPreset, Region};
+use crate::danger::DangerWeights;
+use crate::matcher;
+use crate::windows::WindowIdentity;
+
+/// The user-adjustable application configuration.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// The horizontal adjustment, in pixels, applied to the player-list crop region.
+    pub crop_offset_x: i32,
+    /// The vertical adjustment, in pixels, applied to the player-list crop region.
+    pub crop_offset_y: i32,
+    /// The player-list region selected on a capture, used instead of the centered default.
+    pub player_list_region: Option<Region>,
+    /// How the lobby is laid out for the capture's aspect ratio: "16:9", "21:9", "4:3", or "auto" to
+    /// pick from each capture. Ignored when a player-list region is selected.
+    pub aspect_preset: AspectPreset,
+    /// The recognition model used for a fallback pass over cards the primary model can't read.
+    ///
+    /// When unset, no fallback pass is made.
+    pub secondary_recognition_model: Option<PathBuf>,
+    /// The minimum similarity, as a percentage, for a result to be shown as a match. Defaults to 70%,
+    /// and is set with the threshold slider.
+    pub similarity_threshold: u8,
+    /// How long, in seconds, to wait for the lobby to load before capturing.
+    ///
+    /// When unset, scans capture immediately.
+    pub pre_scan_wait_secs: Option<u64>,
+    /// The number of threads the OCR engine runs its models on.
+    ///
+    /// When unset, the engine uses one thread per CPU core. Takes effect on the next scan, which
+    /// reloads the OCR models.
+    pub ocr_threads: Option<usize>,
+    /// Whether to append each scan's flagged results to a CSV file for spreadsheets.
+    pub csv_scan_log: bool,
+    /// Whether to scan automatically when the RISK window appears or is restored.
+    pub watch_mode: bool,
+    /// The minimum time, in seconds, between scans triggered by watch mode.
+    pub watch_debounce_secs: u64,
+    /// The time, in seconds, between watch mode scans when window events aren't available.
+    pub watch_fallback_interval_secs: u64,
+    /// Whether to scan again on a fixed interval, for keeping up with a lobby as it fills.
+    pub auto_scan: bool,
+    /// The time, in seconds, between auto-scans.
+    pub auto_scan_interval_secs: u64,
+    /// The global hotkey that scans without focusing the app, e.g. "ctrl+shift+S". Takes effect on
+    /// the next launch.
+    ///
+    /// When unset, no hotkey is registered.
+    pub scan_hotkey: Option<String>,
+    /// The most results shown at once. The strongest matches are kept.
+    pub max_results: usize,
+    /// The minimum time, in seconds, between the end of one scan and the start of the next,
+    /// however the scan was triggered.
+    pub scan_cooldown_secs: u64,
+    /// Which banner to show above the buttons, if any.
+    pub banner: Banner,
+    /// The name of the strategy fuzzy entries are scored with: `ratio`, `jaro-winkler`, `token-sort`,
+    /// `partial-ratio`, or `token-set`.
+    pub matcher: String,
+    /// The shortest username, in characters, that is matched fuzzily without a warning.
+    pub min_username_length: usize,
+    /// The weights of the lobby danger score.
+    pub danger_weights: DangerWeights,
+    /// Where every scan automatically writes its flagged results, if anywhere.
+    pub auto_export: Option<AutoExport>,
+    /// The DPI written into saved screenshots and crops.
+    ///
+    /// When unset, saved PNGs carry no DPI metadata at all, so editors fall back to their default.
+    pub png_dpi: Option<u32>,
+    /// Whether the previous results stay on screen, dimmed, while a new scan runs.
+    pub keep_previous_results: bool,
+    /// Whether runs of three or more of the same character are collapsed to one before matching, so
+    /// padded names like "mooooron" still match "moron".
+    pub collapse_repeated_chars: bool,
+    /// Whether fuzzy entries are also searched for within each card's text joined into one line, for
+    /// when OCR merges a name with the text around it (e.g. "General Moron 34 troops").
+    pub partial_card_matching: bool,
+    /// How similarities are shown beside each result.
+    pub similarity_display: SimilarityDisplay,
+    /// The similarities at which results are labelled "Likely" or "Possible".
+    pub similarity_labels: SimilarityLabels,
+    /// The opt-in, anonymous match-quality telemetry. Off unless set.
+    ///
+    /// When set, each scan records only similarity scores and counts (never usernames, detections,
+    /// or blacklist contents) to a local file, and uploads them only if an upload URL is given.
+    pub telemetry: Option<Telemetry>,
+    /// Whether fuzzy entries are also scored against the detections as read, keeping the higher of
+    /// the raw and normalized scores, for names that normalization over-collapses.
+    pub match_raw_form: bool,
+    /// Whether a panic during a scan is caught and shown as an error rather than closing the app.
+    pub catch_scan_panics: bool,
+    /// The confidence, from 0 to 1, below which all of a card's detections are discarded as noise.
+    ///
+    /// When unset, cards are never discarded as a whole.
+    pub min_card_confidence: Option<f32>,
+    /// How each player card is prepared before it's read.
+    pub ocr_preprocessing: OcrPreprocessing,
+    /// The read quality, from 0 to 1, below which a result is greyed out as a likely misread, however
+    /// similar the name. Read quality is a heuristic over the characters read, not a score from the
+    /// OCR engine, which doesn't report one.
+    ///
+    /// When unset, results are shown alike whatever their read quality.
+    pub min_read_quality: Option<f32>,
+    /// Whether results below `min_read_quality` are hidden rather than greyed out. Hidden results
+    /// never alert.
+    pub hide_low_read_quality: bool,
+    /// Whether a scan only alerts when it flags someone the previous scan didn't. The results still
+    /// list everyone flagged.
+    pub alert_only_new: bool,
+    /// Whether an alert also shows a desktop notification of who was flagged, when the app is
+    /// minimized. Clicking the notification restores the app, where the desktop supports it.
+    pub desktop_notifications: bool,
+    /// Which digits are stripped before matching, for players who decorate their names with numbers.
+    pub strip_digits: DigitStripping,
+    /// Whether the window a scan used is remembered, so later scans prefer it when several RISK
+    /// windows are open.
+    pub remember_window: bool,
+    /// The window remembered from the last scan, if any.
+    pub remembered_window: Option<WindowIdentity>,
+    /// The fewest cards a scan must read before it's trusted as complete. Scans that read fewer
+    /// still show their results, with a warning to check the alignment.
+    ///
+    /// When unset, scans never warn about how many cards they read.
+    pub min_occupied_cards: Option<usize>,
+    /// How long, in seconds, the OCR of a single card may take before the card is abandoned and the
+    /// scan carries on without it.
+    ///
+    /// When unset, the OCR of each card runs for as long as it takes.
+    pub ocr_timeout_secs: Option<u64>,
+    /// Whether glyphs OCR commonly confuses ("rn" and "m", "0" and "O", "1" and "l", ...) are treated
+    /// as the same when matching fuzzy entries.
+    pub ocr_confusions: bool,
+    /// Whether each scan also saves `players-debug.png`, the screenshot with the player-list region
+    /// and the six card regions outlined, and the six card crops, for checking the crop alignment.
+    /// When set, the screenshot and crops are also kept after the scan instead of being deleted.
+    pub save_crop_debug: bool,
+    /// Whether every RISK window is scanned, rather than just one, with the results combined and
+    /// tagged by window.
+    pub scan_all_windows: bool,
+    /// How long, in seconds, results stay on screen without a new match before they're cleared.
+    ///
+    /// When unset, results stay until the next scan.
+    pub auto_clear_secs: Option<u64>,
+    /// The URL of a shared blacklist JSON file (e.g. a GitHub raw URL) that syncing merges into the
+    /// local blacklist.
+    pub blacklist_sync_url: Option<String>,
+    /// What happens to entries on both the local and shared blacklists when syncing:
+    /// "keep-local", "prefer-remote", or "append-reasons".
+    pub blacklist_sync_strategy: MergeStrategy,
+    /// The colour theme of the app.
+    pub theme: AppTheme,
+}
+
+/// Which digits are stripped from detections and usernames before matching.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DigitStripping {
+    /// Digits are kept, for names where the number matters.
+    #[default]
+    None,
+    /// The run of digits at the end is stripped, so "moron99" matches "moron".
+    Trailing,
+    /// Every digit is stripped.
+    All,
+}
+
+/// The opt-in, anonymous match-quality telemetry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Telemetry {
+    /// Where the anonymous stats are posted after each scan. They're only kept locally when unset.
+    #[serde(default)]
+    pub upload_url: Option<String>,
+}
+
+/// How similarities are shown beside each result.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SimilarityDisplay {
+    /// The raw percentage, e.g. "(87%)".
+    #[default]
+    Percent,
+    /// A label for the range the similarity falls in, e.g. "(Likely)".
+    Qualitative,
+    /// Both, e.g. "(Likely, 87%)".
+    Both,
+}
+
+/// The similarities at which results are labelled "Likely" or "Possible". Anything lower is
+/// "Unlikely".
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct SimilarityLabels {
+    /// The lowest similarity labelled "Likely".
+    pub likely: u8,
+    /// The lowest similarity labelled "Possible".
+    pub possible: u8,
+}
+
+impl Default for SimilarityLabels {
+    fn default() -> Self {
+        Self {
+            likely: 90,
+            possible: 75,
+        }
+    }
+}
+
+/// How each player card is prepared before it's read, to help the OCR with light-grey names on the
+/// dark card background. Every step is off by default.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct OcrPreprocessing {
+    /// Whether the card is converted to grayscale.
+    pub grayscale: bool,
+    /// Whether the card's contrast is boosted.
+    pub boost_contrast: bool,
+    /// Whether the card is upscaled 2x, for small names on low-resolution captures.
+    pub upscale: bool,
+}
+
+/// Where and how every scan automatically writes its flagged results.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AutoExport {
+    /// The file the flagged results are written to.
+    pub path: PathBuf,
+    /// The format the flagged results are written in.
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Whether each scan appends to the file rather than overwriting it.
+    #[serde(default)]
+    pub append: bool,
+}
+
+/// The format flagged results are exported in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// A JSON array when overwriting, or one JSON object per line when appending.
+    #[default]
+    Json,
+    /// A CSV table with a header row.
+    Csv,
+}
+
+/// The colour theme of the app.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AppTheme {
+    /// The dark Kanagawa Dragon theme.
+    #[default]
+    Dark,
+    /// The light Kanagawa Lotus theme, for bright surroundings and overlays.
+    Light,
+    /// Dark or light, following the OS setting when the app starts.
+    System,
+}
+
+impl AppTheme {
+    /// Every theme, in the order they are listed in the theme picker.
+    pub const ALL: [AppTheme; 3] = [AppTheme::Dark, AppTheme::Light, AppTheme::System];
+}
+
+impl std::fmt::Display for AppTheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AppTheme::Dark => "Dark",
+            AppTheme::Light => "Light",
+            AppTheme::System => "System",
+        })
+    }
+}
+
+/// Which banner to show above the buttons.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Banner {
+    /// The Blitz banner, downloaded on first launch.
+    #[default]
+    Default,
+    /// An image on disk.
+    Local(PathBuf),
+    /// No banner, and nothing is downloaded.
+    Disabled,
+}
+
+impl Config {
+    /// Loads and deserializes an existing [`Config`] JSON file into a new [`Config`]. A similarity
+    /// threshold above 100% is capped, as the threshold slider can't show it.
+    ///
+    /// # Arguments
+    /// * `config_path` - A reference to the [`PathBuf`] representing the path to the config file.
+    pub fn load(config_path: &PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(config_path)?;
+        let mut config: Config = serde_json::from_str(&content)?;
+        if config.similarity_threshold > 100 {
+            log::warn!("The similarity threshold {}% is above 100%. Using 100%.", config.similarity_threshold);
+            config.similarity_threshold = 100;
+        }
+        if config.auto_scan_interval_secs == 0 {
+            log::warn!("The auto-scan interval can't be 0 seconds. Using 1 second.");
+            config.auto_scan_interval_secs = 1;
+        }
+        Ok(config)
+    }
+
+    /// Serializes the [`Config`] and writes it to the config file.
+    ///
+    /// # Arguments
+    /// * `config_path` - A reference to the [`PathBuf`] representing the path to the config file.
+    pub fn save(&self, config_path: &PathBuf) -> anyhow::Result<()> {
+        let config_json = serde_json::to_string_pretty(self)?;
+        let mut config_file = std::fs::File::create(config_path)?;
+        config_file.write_all(config_json.as_ref())?;
+        Ok(())
+    }
+}
+
+impl Default for Config {
+    /// Creates a new [`Config`] with the default settings.
+    fn default() -> Self {
+        Config {
+            crop_offset_x: 0,
+            crop_offset_y: 0,
+            player_list_region: None,
+            aspect_preset: AspectPreset::Widescreen,
+            secondary_recognition_model: None,
+            similarity_threshold: 70,
+            pre_scan_wait_secs: None,
+            ocr_threads: None,
+            csv_scan_log: false,
+            watch_mode: false,
+            watch_debounce_secs: 10,
+            watch_fallback_interval_secs: 30,
+            auto_scan: false,
+            auto_scan_interval_secs: 5,
+            scan_hotkey: Some(String::from("ctrl+shift+S")),
+            max_results: 10,
+            scan_cooldown_secs: 3,
+            banner: Banner::Default,
+            matcher: String::from(matcher::DEFAULT_MATCHER),
+            min_username_length: 4,
+            danger_weights: DangerWeights::default(),
+            auto_export: None,
+            png_dpi: None,
+            keep_previous_results: true,
+            collapse_repeated_chars: false,
+            partial_card_matching: false,
+            similarity_display: SimilarityDisplay::Percent,
+            similarity_labels: SimilarityLabels::default(),
+            telemetry: None,
+            match_raw_form: false,
+            catch_scan_panics: true,
+            min_card_confidence: None,
+            ocr_preprocessing: OcrPreprocessing::default(),
+            min_read_quality: None,
+            hide_low_read_quality: false,
+            alert_only_new: false,
+            desktop_notifications: true,
+            strip_digits: DigitStripping::None,
+            remember_window: false,
+            remembered_window: None,
+            min_occupied_cards: None,
+            ocr_timeout_secs: None,
+            ocr_confusions: false,
+            save_crop_debug: false,
+            scan_all_windows: false,
+            auto_clear_secs: None,
+            blacklist_sync_url: None,
+            blacklist_sync_strategy: MergeStrategy::KeepLocal,
+            theme: AppTheme::Dark,
+        }
+    }
+}
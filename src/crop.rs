@@ -0,0 +1,424 @@
+//! Module for computing where the player cards sit within a capture of the RISK window.
+//!
+//! The lobby lays the six player cards out in a 2x3 grid centered in the window:
+//!
+//! ```text
+//! =============================
+//! ||| [Player 1] [Player 2] |||
+//! ||| [Player 3] [Player 4] |||
+//! ||| [Player 5] [Player 6] |||
+//! =============================
+//! ```
+//!
+//! The grid is sized for a 1920x1080 capture and scales with the resolution, so it lands on the same
+//! part of the lobby at 1440p and 4K. Other aspect ratios scale the lobby differently: ultrawide
+//! (21:9) captures pillarbox it, scaling with the height, and 4:3 captures letterbox it, scaling with
+//! the width. The [`AspectPreset`] is picked from the capture dimensions or set in the config.
+//!
+//! Monitors wider than the game (e.g. 3440x1440) often show it pillarboxed between uniform bars. The
+//! bars are detected with [`content_rect`] and the grid is placed within the game content between
+//! them, with the preset picked from the content's dimensions rather than the whole capture's.
+//!
+//! HUD elements shift the grid on some resolutions, so the player-list region can be nudged by a
+//! configurable pixel offset, or replaced entirely by a [`Region`] the user selects on a capture.
+//! The region is always clamped to lie within the capture. For calibrating, the regions can be
+//! outlined on the capture with [`draw_overlay`].
+
+use image::{GenericImageView, Rgba, RgbaImage};
+use serde::{Serialize, Deserialize};
+
+/// The width of the player list, in pixels, at 1920x1080.
+pub(crate) const PLAYER_LIST_WIDTH: u32 = 1200;
+
+/// The height of the player list, in pixels, at 1920x1080.
+pub(crate) const PLAYER_LIST_HEIGHT: u32 = 550;
+
+/// The width of a single player card, in pixels, at 1920x1080.
+pub(crate) const PLAYER_CARD_WIDTH: u32 = 600;
+
+/// The height of a single player card, in pixels, at 1920x1080.
+pub(crate) const PLAYER_CARD_HEIGHT: u32 = 180;
+
+/// How far, per channel, a pixel may be from the bar colour and still count as part of a bar.
+const BAR_COLOR_TOLERANCE: u8 = 8;
+
+/// How many pixels along each row or column are sampled to check whether it's part of a bar.
+const BAR_SAMPLES: u32 = 64;
+
+/// How the lobby is laid out for the aspect ratio of the capture.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum AspectPreset {
+    /// Picked from the dimensions of each capture.
+    #[serde(rename = "auto")]
+    Auto,
+    /// The grid scales with the capture, fitting within both its width and height, at the center.
+    #[default]
+    #[serde(rename = "16:9")]
+    Widescreen,
+    /// The grid scales with the height and is pillarboxed at the center of the capture.
+    #[serde(rename = "21:9")]
+    Ultrawide,
+    /// The grid scales with the width and is letterboxed at the center of the capture.
+    #[serde(rename = "4:3")]
+    Standard,
+}
+
+/// Picks the preset for a capture: the configured one, or for [`AspectPreset::Auto`], the one
+/// closest to the capture's aspect ratio.
+///
+/// # Arguments
+/// * `preset` - The configured preset.
+/// * `image_width` - The width of the capture.
+/// * `image_height` - The height of the capture.
+pub(crate) fn resolve_aspect_preset(preset: AspectPreset, image_width: u32, image_height: u32) -> AspectPreset {
+    if preset != AspectPreset::Auto {
+        return preset;
+    }
+
+    // Split the ratios halfway between 4:3 (1.33), 16:9 (1.78), and 21:9 (2.33).
+    let aspect_ratio = image_width as f32 / image_height.max(1) as f32;
+    if aspect_ratio >= 2.05 {
+        AspectPreset::Ultrawide
+    } else if aspect_ratio <= 1.55 {
+        AspectPreset::Standard
+    } else {
+        AspectPreset::Widescreen
+    }
+}
+
+/// A rectangular region of an image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CropRect {
+    /// The x coordinate of the left edge.
+    pub x: u32,
+    /// The y coordinate of the top edge.
+    pub y: u32,
+    /// The width of the region.
+    pub width: u32,
+    /// The height of the region.
+    pub height: u32,
+}
+
+/// A region of a capture expressed as fractions of its width and height, so that it applies to
+/// captures of any size.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Region {
+    /// The left edge, as a fraction of the capture width.
+    pub x: f32,
+    /// The top edge, as a fraction of the capture height.
+    pub y: f32,
+    /// The width, as a fraction of the capture width.
+    pub width: f32,
+    /// The height, as a fraction of the capture height.
+    pub height: f32,
+}
+
+impl Region {
+    /// Creates a [`Region`] spanning two opposite corners, given in either order. The corners are
+    /// clamped to the capture.
+    ///
+    /// # Arguments
+    /// * `from` - One corner, as fractions of the capture width and height.
+    /// * `to` - The opposite corner, as fractions of the capture width and height.
+    pub(crate) fn from_corners(from: (f32, f32), to: (f32, f32)) -> Self {
+        let (left, right) = (from.0.min(to.0).clamp(0.0, 1.0), from.0.max(to.0).clamp(0.0, 1.0));
+        let (top, bottom) = (from.1.min(to.1).clamp(0.0, 1.0), from.1.max(to.1).clamp(0.0, 1.0));
+
+        Self {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        }
+    }
+
+    /// Creates the [`Region`] covering a rectangle of a capture.
+    ///
+    /// # Arguments
+    /// * `rect` - The rectangle to cover.
+    /// * `image_width` - The width of the capture.
+    /// * `image_height` - The height of the capture.
+    pub(crate) fn from_rect(rect: &CropRect, image_width: u32, image_height: u32) -> Self {
+        let image_width = image_width.max(1) as f32;
+        let image_height = image_height.max(1) as f32;
+
+        Self {
+            x: rect.x as f32 / image_width,
+            y: rect.y as f32 / image_height,
+            width: rect.width as f32 / image_width,
+            height: rect.height as f32 / image_height,
+        }
+    }
+}
+
+/// Computes the player-list region of a capture: sized for the aspect preset, centered within the
+/// game content, shifted by the offsets, and clamped so that it lies entirely within the content.
+///
+/// # Arguments
+/// * `preset` - The aspect preset, which must already be resolved from [`AspectPreset::Auto`].
+/// * `content` - The game content of the capture, from [`content_rect`].
+/// * `offset_x` - The horizontal adjustment, in pixels. Positive values move the region right.
+/// * `offset_y` - The vertical adjustment, in pixels. Positive values move the region down.
+pub(crate) fn player_list_rect(
+    preset: AspectPreset,
+    content: &CropRect,
+    offset_x: i32,
+    offset_y: i32,
+) -> CropRect {
+    let scale = match preset {
+        AspectPreset::Auto | AspectPreset::Widescreen => (content.width as f32 / 1920.0).min(content.height as f32 / 1080.0),
+        AspectPreset::Ultrawide => content.height as f32 / 1080.0,
+        AspectPreset::Standard => content.width as f32 / 1920.0,
+    };
+    let scaled = |extent: u32| ((extent as f32 * scale).round() as u32).max(1);
+    let width = scaled(PLAYER_LIST_WIDTH).min(content.width);
+    let height = scaled(PLAYER_LIST_HEIGHT).min(content.height);
+
+    CropRect {
+        x: content.x + offset_origin(content.width, width, offset_x),
+        y: content.y + offset_origin(content.height, height, offset_y),
+        width,
+        height,
+    }
+}
+
+/// Finds the game content of a capture: the region left after trimming the uniform bars that
+/// pillarbox (or letterbox) the game. The bar colour is taken from the top-left corner. The whole
+/// capture is returned if it has no bars, or if trimming would leave less than half of it, which
+/// means the "bars" are more likely part of the game.
+///
+/// # Arguments
+/// * `capture` - The capture of the RISK window.
+pub(crate) fn content_rect(capture: &impl GenericImageView<Pixel = Rgba<u8>>) -> CropRect {
+    let (width, height) = capture.dimensions();
+    let whole = CropRect { x: 0, y: 0, width, height };
+    if width == 0 || height == 0 {
+        return whole;
+    }
+
+    let bar_color = capture.get_pixel(0, 0);
+    let is_bar = |x: u32, y: u32| {
+        let pixel = capture.get_pixel(x, y);
+        pixel.0[..3]
+            .iter()
+            .zip(bar_color.0[..3].iter())
+            .all(|(channel, bar_channel)| channel.abs_diff(*bar_channel) <= BAR_COLOR_TOLERANCE)
+    };
+    // Samples spread evenly from the start to the end of a span, inclusive.
+    let samples = |start: u32, end: u32| {
+        let last = end - start - 1;
+        (0..BAR_SAMPLES).map(move |sample| start + sample * last / (BAR_SAMPLES - 1))
+    };
+
+    let is_bar_column = |x: u32| samples(0, height).all(|y| is_bar(x, y));
+    let Some(left) = (0..width).find(|x| !is_bar_column(*x)) else {
+        return whole;
+    };
+    let right = (0..width).rev().find(|x| !is_bar_column(*x)).unwrap_or(left) + 1;
+
+    let is_bar_row = |y: u32| samples(left, right).all(|x| is_bar(x, y));
+    let top = (0..height).find(|y| !is_bar_row(*y)).unwrap_or(0);
+    let bottom = (0..height).rev().find(|y| !is_bar_row(*y)).map_or(height, |y| y + 1).max(top + 1);
+
+    let content = CropRect { x: left, y: top, width: right - left, height: bottom - top };
+    if content.width < width / 2 || content.height < height / 2 {
+        return whole;
+    }
+    content
+}
+
+/// Converts a selected [`Region`] into a rectangle of a capture, shifted by the offsets and clamped
+/// so that it lies entirely within the capture. The rectangle is always at least one pixel wide and
+/// tall.
+///
+/// # Arguments
+/// * `region` - The selected region.
+/// * `image_width` - The width of the capture.
+/// * `image_height` - The height of the capture.
+/// * `offset_x` - The horizontal adjustment, in pixels. Positive values move the region right.
+/// * `offset_y` - The vertical adjustment, in pixels. Positive values move the region down.
+pub(crate) fn region_rect(
+    region: &Region,
+    image_width: u32,
+    image_height: u32,
+    offset_x: i32,
+    offset_y: i32,
+) -> CropRect {
+    let to_pixels = |fraction: f32, extent: u32| (fraction.clamp(0.0, 1.0) * extent as f32).round() as u32;
+
+    let width = to_pixels(region.width, image_width).clamp(1, image_width.max(1));
+    let height = to_pixels(region.height, image_height).clamp(1, image_height.max(1));
+    let max_x = image_width.saturating_sub(width) as i64;
+    let max_y = image_height.saturating_sub(height) as i64;
+
+    CropRect {
+        x: (to_pixels(region.x, image_width) as i64 + offset_x as i64).clamp(0, max_x) as u32,
+        y: (to_pixels(region.y, image_height) as i64 + offset_y as i64).clamp(0, max_y) as u32,
+        width,
+        height,
+    }
+}
+
+/// Computes the region of each player card within the player list, in card order. The cards are
+/// scaled in proportion to the player list, so a selected region of any size is split the same way.
+///
+/// # Arguments
+/// * `player_list` - The player-list region the cards are laid out in.
+pub(crate) fn player_card_rects(player_list: &CropRect) -> Vec<CropRect> {
+    let width = (player_list.width * PLAYER_CARD_WIDTH / PLAYER_LIST_WIDTH).max(1);
+    let height = (player_list.height * PLAYER_CARD_HEIGHT / PLAYER_LIST_HEIGHT).max(1);
+
+    let mut card_rects = Vec::with_capacity(6);
+    for row in 0..3 {
+        for col in 0..2 {
+            card_rects.push(CropRect {
+                x: player_list.x + col * width,
+                y: player_list.y + row * height,
+                width,
+                height,
+            });
+        }
+    }
+
+    card_rects
+}
+
+/// Outlines rectangles on a copy of a capture, for checking the crop regions line up with the lobby.
+/// Rectangles are clipped to the capture.
+///
+/// # Arguments
+/// * `capture` - The capture to draw on.
+/// * `rects` - The rectangles to outline.
+/// * `color` - The colour of the outlines.
+/// * `thickness` - The thickness of the outlines, in pixels, drawn inside each rectangle.
+pub(crate) fn draw_overlay(capture: &RgbaImage, rects: &[CropRect], color: Rgba<u8>, thickness: u32) -> RgbaImage {
+    let mut overlay = capture.clone();
+    for rect in rects {
+        let right = (rect.x + rect.width).min(overlay.width());
+        let bottom = (rect.y + rect.height).min(overlay.height());
+        for y in rect.y..bottom {
+            for x in rect.x..right {
+                let on_edge = x < rect.x + thickness
+                    || y < rect.y + thickness
+                    || x + thickness >= rect.x + rect.width
+                    || y + thickness >= rect.y + rect.height;
+                if on_edge {
+                    overlay.put_pixel(x, y, color);
+                }
+            }
+        }
+    }
+    overlay
+}
+
+/// Computes the offset origin of a centered span along one axis, clamped to the image bounds.
+///
+/// # Arguments
+/// * `image_extent` - The extent of the image along the axis.
+/// * `span` - The extent of the centered span along the axis.
+/// * `offset` - The adjustment applied to the centered origin.
+fn offset_origin(image_extent: u32, span: u32, offset: i32) -> u32 {
+    let max_origin = image_extent.saturating_sub(span) as i64;
+    let centered_origin = max_origin / 2;
+    (centered_origin + offset as i64).clamp(0, max_origin) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL_HD: CropRect = CropRect { x: 0, y: 0, width: 1920, height: 1080 };
+
+    #[test]
+    fn offsets_shift_the_player_list_origin() {
+        let centered = player_list_rect(AspectPreset::Widescreen, &FULL_HD, 0, 0);
+        assert_eq!(centered, CropRect { x: 360, y: 265, width: 1200, height: 550 });
+
+        let shifted = player_list_rect(AspectPreset::Widescreen, &FULL_HD, 10, -20);
+        assert_eq!(shifted, CropRect { x: 370, y: 245, ..centered });
+    }
+
+    #[test]
+    fn offsets_clamp_the_player_list_to_the_content() {
+        let content = CropRect { x: 440, y: 0, width: 1920, height: 1080 };
+
+        let far_right = player_list_rect(AspectPreset::Widescreen, &content, 10_000, 10_000);
+        assert_eq!((far_right.x, far_right.y), (440 + 720, 530));
+
+        let far_left = player_list_rect(AspectPreset::Widescreen, &content, -10_000, -10_000);
+        assert_eq!((far_left.x, far_left.y), (440, 0));
+    }
+
+    #[test]
+    fn selection_converts_to_a_crop_rect() {
+        let region = Region::from_corners((0.75, 0.5), (0.25, 0.25));
+        assert_eq!(region, Region { x: 0.25, y: 0.25, width: 0.5, height: 0.25 });
+        assert_eq!(region_rect(&region, 1920, 1080, 0, 0), CropRect { x: 480, y: 270, width: 960, height: 270 });
+    }
+
+    #[test]
+    fn selection_clamps_to_the_capture() {
+        let region = Region::from_corners((-0.5, 0.5), (0.5, 1.5));
+        assert_eq!(region, Region { x: 0.0, y: 0.5, width: 0.5, height: 0.5 });
+
+        // Offsets can't push the selection off the capture.
+        assert_eq!(region_rect(&region, 1920, 1080, -100, 100), CropRect { x: 0, y: 540, width: 960, height: 540 });
+        // An empty selection is still a pixel.
+        let empty = Region { x: 1.0, y: 1.0, width: 0.0, height: 0.0 };
+        assert_eq!(region_rect(&empty, 1920, 1080, 0, 0), CropRect { x: 1919, y: 1079, width: 1, height: 1 });
+    }
+
+    #[test]
+    fn auto_preset_follows_the_capture_aspect_ratio() {
+        assert_eq!(resolve_aspect_preset(AspectPreset::Auto, 3440, 1440), AspectPreset::Ultrawide);
+        assert_eq!(resolve_aspect_preset(AspectPreset::Auto, 1920, 1080), AspectPreset::Widescreen);
+        assert_eq!(resolve_aspect_preset(AspectPreset::Auto, 1600, 1200), AspectPreset::Standard);
+        // A configured preset is kept whatever the capture.
+        assert_eq!(resolve_aspect_preset(AspectPreset::Standard, 3440, 1440), AspectPreset::Standard);
+    }
+
+    #[test]
+    fn ultrawide_capture_scales_the_player_list_with_its_height() {
+        let ultrawide = CropRect { x: 0, y: 0, width: 3440, height: 1440 };
+        let preset = resolve_aspect_preset(AspectPreset::Auto, ultrawide.width, ultrawide.height);
+        assert_eq!(player_list_rect(preset, &ultrawide, 0, 0), CropRect { x: 920, y: 353, width: 1600, height: 733 });
+    }
+
+    #[test]
+    fn overlay_is_the_size_of_the_capture() {
+        let black = Rgba([0, 0, 0, 255]);
+        let red = Rgba([255, 0, 0, 255]);
+        let capture = RgbaImage::from_pixel(40, 30, black);
+        // The second rectangle runs off the capture and is clipped.
+        let rects = [CropRect { x: 5, y: 5, width: 10, height: 10 }, CropRect { x: 30, y: 20, width: 50, height: 50 }];
+
+        let overlay = draw_overlay(&capture, &rects, red, 2);
+        assert_eq!(overlay.dimensions(), capture.dimensions());
+        assert_eq!(*overlay.get_pixel(5, 5), red);
+        assert_eq!(*overlay.get_pixel(14, 14), red);
+        assert_eq!(*overlay.get_pixel(10, 10), black);
+        assert_eq!(*overlay.get_pixel(30, 29), red);
+        // The capture itself is left untouched.
+        assert_eq!(*capture.get_pixel(5, 5), black);
+    }
+
+    #[test]
+    fn pillarboxed_ultrawide_capture_finds_the_centered_content() {
+        let mut capture = RgbaImage::from_pixel(3440, 1440, Rgba([0, 0, 0, 255]));
+        for y in 0..1440 {
+            for x in 440..440 + 2560 {
+                capture.put_pixel(x, y, Rgba([40, 60, 80, 255]));
+            }
+        }
+
+        let content = content_rect(&capture);
+        assert_eq!(content, CropRect { x: 440, y: 0, width: 2560, height: 1440 });
+        assert_eq!(player_list_rect(AspectPreset::Widescreen, &content, 0, 0), CropRect { x: 920, y: 353, width: 1600, height: 733 });
+    }
+
+    #[test]
+    fn capture_without_bars_is_all_content() {
+        let capture = RgbaImage::from_pixel(1920, 1080, Rgba([40, 60, 80, 255]));
+        assert_eq!(content_rect(&capture), FULL_HD);
+    }
+}
@@ -0,0 +1,118 @@
+//! Module for summarising a scan as a single 0-100 "lobby danger score".
+//!
+//! Each seat contributes its strongest flagged result, weighted by the moron's severity and scaled
+//! by how confident the match is. The weighted sum is then squashed into 0-100, so more (or worse)
+//! morons always raise the score but it never overflows the gauge. Friendly players never count.
+
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use crate::blacklist::Severity;
+use crate::detector::{self, ScanInfo};
+
+/// The weights of the danger score formula.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DangerWeights {
+    /// The weight of a low-severity moron.
+    pub low: f32,
+    /// The weight of a medium-severity moron.
+    pub medium: f32,
+    /// The weight of a high-severity moron.
+    pub high: f32,
+    /// The weighted sum at which the score reaches about 63. Smaller values make the score climb
+    /// faster.
+    pub saturation: f32,
+}
+
+impl Default for DangerWeights {
+    fn default() -> Self {
+        Self {
+            low: 1.0,
+            medium: 2.0,
+            high: 4.0,
+            saturation: 4.0,
+        }
+    }
+}
+
+impl DangerWeights {
+    /// Gets the weight of a moron of the given severity.
+    fn weight(&self, severity: Severity) -> f32 {
+        match severity {
+            Severity::Low => self.low,
+            Severity::Medium => self.medium,
+            Severity::High => self.high,
+        }
+    }
+}
+
+/// Computes the danger score of a scan, from 0 (clean) to 100.
+///
+/// # Arguments
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+/// * `weights` - The weights of the formula.
+pub(crate) fn danger_score(scans: &[ScanInfo], similarity_threshold: u8, weights: &DangerWeights) -> u8 {
+    // Only the strongest flagged result on each seat counts, so one name resembling several
+    // blacklist entries isn't counted several times.
+    let mut seat_contributions: BTreeMap<usize, f32> = BTreeMap::new();
+    for scan in detector::similar_scans(scans, similarity_threshold) {
+        if scan.friendly.is_some() {
+            continue;
+        }
+
+        let contribution = weights.weight(scan.severity) * scan.similarity as f32 / 100.0;
+        let seat_contribution = seat_contributions.entry(scan.card_index).or_insert(0.0);
+        *seat_contribution = seat_contribution.max(contribution);
+    }
+
+    let weighted_sum: f32 = seat_contributions.values().sum();
+    if weighted_sum <= 0.0 || weights.saturation <= 0.0 {
+        return 0;
+    }
+
+    (100.0 * (1.0 - (-weighted_sum / weights.saturation).exp())).round().clamp(0.0, 100.0) as u8
+}
+
+/// Gets the label shown beside a danger score.
+///
+/// # Arguments
+/// * `score` - The danger score, from 0 to 100.
+pub(crate) fn danger_label(score: u8) -> &'static str {
+    match score {
+        0 => "Clean",
+        1..=24 => "Low",
+        25..=49 => "Moderate",
+        50..=74 => "High",
+        _ => "Extreme",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::tests::scan_info;
+
+    fn seat(card_index: usize, severity: Severity) -> ScanInfo {
+        ScanInfo { card_index, severity, ..scan_info("Moron", 100) }
+    }
+
+    #[test]
+    fn danger_score_orders_lobbies_by_how_bad_they_are() {
+        let weights = DangerWeights::default();
+        let clean = danger_score(&[scan_info("Moron", 40)], 70, &weights);
+        let several_low = danger_score(&[seat(0, Severity::Low), seat(1, Severity::Low), seat(2, Severity::Low)], 70, &weights);
+        let one_high = danger_score(&[seat(0, Severity::High)], 70, &weights);
+
+        assert_eq!(clean, 0);
+        assert_eq!(danger_label(clean), "Clean");
+        assert!(0 < several_low && several_low < one_high && one_high < 100);
+    }
+
+    #[test]
+    fn seat_counts_only_its_strongest_result() {
+        let weights = DangerWeights::default();
+        let one_high = danger_score(&[seat(0, Severity::High)], 70, &weights);
+        assert_eq!(danger_score(&[seat(0, Severity::High), seat(0, Severity::Low)], 70, &weights), one_high);
+    }
+}
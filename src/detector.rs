@@ -4,8 +4,9 @@
 //! 1. Finds the RISK window from all active windows.
 //! 2. Screenshots and crops the player cards from the RISK window.
 //! 3. Creates an OCR engine, loads the images, and extracts the text.
-//! 4. Loads the blacklist.
-//! 5. Fuzzy matches the detections against the blacklist.
+//! 4. Loads the blacklist and allowlist.
+//! 5. Fuzzy matches the detections against the blacklist, marking allowlisted players as friendly.
+//!    Players wearing a blacklisted clan tag are flagged as clan matches.
 //!
 //! The module also contains utility functions for capturing screenshots, cropping player cards,
 //! creating an OCR engine, and detecting text from images.
@@ -16,8 +17,8 @@
 //! use crate::detector;
 //!
 //! # async fn example_usage() -> anyhow::Result<()> {
-//! let scans = detector::scan()?;
-//! for scan_info in scans {
+//! let scan_result = detector::scan(&Config::default())?;
+//! for scan_info in scan_result.scans {
 //!     println!("Username: {}, Score: {}", scan_info.username, scan_info.score);
 //! }
 //! # Ok(())
@@ -26,22 +27,51 @@
 //!
 //! # Warning
 //!
-//! - The [`crop_player_cards_1920_1080`] function assumes the dimension of the screenshot is 1920x1080 pixels.
-//!   Adjustments might be necessary for different monitor aspect ratios.
-//! - The `crop_and_save_player_cards_dynamic` function is a placeholder and not yet implemented.
+//! - The player cards are located by scaling the 1920x1080 layout to the screenshot. Lobbies with HUD
+//!   elements that don't scale may need the crop offsets or a selected region.
 //!
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::result::Result::Ok;
+use std::sync::Mutex;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, SendError, Sender};
+use std::time::{Duration, Instant, SystemTime};
 use anyhow::bail;
+use image::{DynamicImage, RgbImage, RgbaImage};
+use image::imageops::FilterType;
 use iced::subscription;
 use ocrs::{OcrEngine, OcrEngineParams};
+use rayon::prelude::*;
 use rten::Model;
-use rten_tensor::AsView;
+use rten_tensor::{AsView, NdTensor, NdTensorView};
 use xcap::Window;
-use crate::{blacklist, paths};
+use crate::{allowlist, blacklist, crop, lobby, paths, report, windows};
+use crate::paths::Asset;
+use crate::lobby::CaptureLobbyClassifier;
+use crate::blacklist::{MatchMode, Moron, Severity};
+use crate::matcher::{self, ConfusionMatcher, Matcher, RawFormMatcher};
+use crate::report::{CardReport, MatchScore, ScanReport};
+use crate::config::{Config, DigitStripping, OcrPreprocessing};
+use crate::crop::{AspectPreset, CropRect};
+use crate::windows::{WindowCandidate, WindowIdentity};
 
-#[derive(Debug)]
+/// How long to wait between checks for the lobby when waiting out a loading screen.
+const LOBBY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The marker the lobby prints after the player's own name.
+const OWN_CARD_MARKER: &str = "(You)";
+
+/// How much [`preprocess_for_ocr`] boosts the contrast of a card, as a percentage.
+const OCR_CONTRAST_BOOST: f32 = 40.0;
+
+/// The confidence below which a detection is considered a misread.
+const MIN_DETECTION_CONFIDENCE: f32 = 0.6;
+
+/// The error reported when the only RISK window is minimized.
+const MINIMIZED_ERROR: &str = "RISK is minimized. Restore it to scan.";
+
+#[derive(Debug, Clone, Default)]
 /// Information about a scan result, including the detected username and the matching score.
 pub(crate) struct ScanInfo {
     /// The likely username match detected during the scan.
@@ -51,72 +81,822 @@ pub(crate) struct ScanInfo {
     /// This similarity represents the degree of similarity between the detected text and the username
     /// in the blacklist. Higher similarities indicate stronger matches.
     pub similarity: u8,
+    /// The allowlisted username the detected text belongs to, if the player is verified friendly.
+    ///
+    /// Friendly results are shown distinctly and never raise an alert, regardless of similarity.
+    pub friendly: Option<String>,
+    /// The blacklisted clan tag the player carries, if the result is a clan match rather than an
+    /// individual match. Clan matches hold the detected name in `username`.
+    pub clan: Option<String>,
+    /// Why the player (or their clan) is blacklisted.
+    pub reason: String,
+    /// How much of a threat the blacklisted player is. Clan matches are medium.
+    pub severity: Severity,
+    /// A link to proof of the blacklisted player's behaviour, if the blacklist has one.
+    pub evidence_url: Option<String>,
+    /// The index of the player card (and so the seat) the detected text was read from.
+    pub card_index: usize,
+    /// Whether the blacklisted player is pinned, listing the result above all others. Clan matches
+    /// are never pinned.
+    pub pinned: bool,
+    /// The blacklisted pattern the player's name matches, if the result is a pattern match rather
+    /// than a username match. Pattern matches hold the detected name in `username`.
+    pub pattern: Option<String>,
+    /// The position of the RISK window the result was read from, when scanning every window.
+    pub window_index: usize,
+    /// The private note about the blacklisted player, if the blacklist has one.
+    pub note: Option<String>,
+    /// How plausible, from 0 to 1, the text the result was matched in looks as a username (see
+    /// [`detection_confidence`]). This is a heuristic, as `ocrs` doesn't report recognition scores.
+    /// Poor reads are the likeliest to be spurious matches.
+    pub read_quality: f32,
+}
+
+/// Where the screenshot being scanned came from. Each source crops to its own files, so scans from
+/// different sources never overwrite each other's crops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScanSource {
+    /// A capture of the RISK window.
+    Live,
+    /// A capture of one of several RISK windows, by its position among them.
+    Window(usize),
+    /// A screenshot saved to disk, e.g. by the benchmark.
+    File,
+    /// The bundled sample lobby.
+    Demo,
+}
+
+impl std::fmt::Display for ScanSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanSource::Live => f.write_str("live"),
+            ScanSource::Window(window_index) => write!(f, "window{window_index}"),
+            ScanSource::File => f.write_str("file"),
+            ScanSource::Demo => f.write_str("demo"),
+        }
+    }
+}
+
+/// The outcome of a scan: the scored results, and the internals that produced them.
+#[derive(Debug, Clone)]
+pub(crate) struct ScanResult {
+    /// The scored results of the scan.
+    pub scans: Vec<ScanInfo>,
+    /// The internals of the scan, for diagnosing missed or false matches.
+    pub report: ScanReport,
+    /// How long each phase of the scan took.
+    pub timings: ScanTimings,
+    /// The window that was scanned, or [`None`] if the scan didn't capture a window.
+    pub window: Option<WindowIdentity>,
+    /// A warning that the results may be incomplete, e.g. because too few cards could be read.
+    pub warning: Option<String>,
+    /// The number of player cards with confident text on them.
+    pub occupied_cards: usize,
+    /// The names read cleanly from the player cards, whether or not they matched anyone.
+    pub detected_names: Vec<DetectedName>,
+}
+
+/// A name read cleanly from a player card, which can be blacklisted straight from the results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DetectedName {
+    /// The name, as read, without the noise around it.
+    pub name: String,
+    /// The index of the player card (and so the seat) the name was read from.
+    pub card_index: usize,
+    /// The position of the RISK window the name was read from, when scanning every window.
+    pub window_index: usize,
+}
+
+/// How long each phase of a scan took.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ScanTimings {
+    /// Cropping the player cards out of the screenshot.
+    pub crop: Duration,
+    /// Loading the OCR engine and reading the text from the player cards.
+    pub ocr: Duration,
+    /// Matching the detections against the blacklist.
+    pub matching: Duration,
 }
 
 /// Scans the RISK lobby for players and determines whether they are likely blacklisted.
-pub fn scan() -> anyhow::Result<Vec<ScanInfo>> {
+///
+/// # Arguments
+/// * `config`: A reference to the [`Config`] to scan with.
+pub fn scan(config: &Config) -> anyhow::Result<ScanResult> {
+    let scan_result = if config.scan_all_windows {
+        scan_all_windows(config)
+    } else {
+        scan_live_window(config)
+    };
+
+    // The screenshot and crops are only needed while detecting, unless they're kept for debugging.
+    if !config.save_crop_debug {
+        paths::cleanup_scan_artifacts();
+    }
+    scan_result
+}
+
+/// Scans the RISK window, preferring the remembered one.
+///
+/// # Arguments
+/// * `config`: A reference to the [`Config`] to scan with.
+fn scan_live_window(config: &Config) -> anyhow::Result<ScanResult> {
+    log::info!("Scanning the RISK lobby.");
+    let (risk_window, window_identity) = identify_risk_window(config.remembered_window.as_ref())
+        .map_err(|err| capture_error(err, is_wayland_session()))?
+        .ok_or(anyhow::anyhow!("Unable to find RISK window."))?;
+
+    scan_window(&risk_window, window_identity, ScanSource::Live, config)
+}
+
+/// Scans every RISK window, combining the results. Each result is tagged with the window it came
+/// from, and only the best result per window, seat, and blacklisted name is kept. Minimized windows
+/// are skipped.
+///
+/// # Arguments
+/// * `config`: A reference to the [`Config`] to scan with.
+fn scan_all_windows(config: &Config) -> anyhow::Result<ScanResult> {
+    let risk_windows = identify_risk_windows()
+        .map_err(|err| capture_error(err, is_wayland_session()))?;
+    if risk_windows.is_empty() {
+        bail!("Unable to find RISK window.");
+    }
+    log::info!("Scanning {} RISK lobbies.", risk_windows.len());
+
+    scan_windows(risk_windows, |risk_window, window_identity, source| {
+        scan_window(risk_window, window_identity, source, config)
+    })
+}
+
+/// Scans each window in turn with the given scan, combining the results like [`scan_all_windows`].
+///
+/// # Arguments
+/// * `risk_windows`: The windows to scan, with their identities.
+/// * `scan_window`: Scans a single window.
+fn scan_windows<W: MinimizableWindow>(
+    risk_windows: Vec<(W, WindowIdentity)>,
+    mut scan_window: impl FnMut(&W, WindowIdentity, ScanSource) -> anyhow::Result<ScanResult>,
+) -> anyhow::Result<ScanResult> {
+    let mut combined: Option<ScanResult> = None;
+    for (window_index, (risk_window, window_identity)) in risk_windows.into_iter().enumerate() {
+        if risk_window.is_minimized() {
+            log::warn!("Skipping RISK window {} because it's minimized.", window_index + 1);
+            continue;
+        }
+
+        let mut scan_result = scan_window(&risk_window, window_identity, ScanSource::Window(window_index))?;
+        for scan in scan_result.scans.iter_mut() {
+            scan.window_index = window_index;
+        }
+        for card in scan_result.report.cards.iter_mut() {
+            card.window_index = window_index;
+        }
+        for detected_name in scan_result.detected_names.iter_mut() {
+            detected_name.window_index = window_index;
+        }
+
+        combined = Some(match combined {
+            None => scan_result,
+            Some(mut combined) => {
+                combined.scans.extend(scan_result.scans);
+                combined.report.cards.extend(scan_result.report.cards);
+                combined.timings.crop += scan_result.timings.crop;
+                combined.timings.ocr += scan_result.timings.ocr;
+                combined.timings.matching += scan_result.timings.matching;
+                combined.occupied_cards += scan_result.occupied_cards;
+                combined.detected_names.extend(scan_result.detected_names);
+                combined.warning = combined.warning.or(scan_result.warning);
+                combined
+            }
+        });
+    }
+
+    let mut combined = combined.ok_or(anyhow::anyhow!(MINIMIZED_ERROR))?;
+    combined.scans = best_per_window(combined.scans);
+    Ok(combined)
+}
+
+/// Keeps only the best result for each window, seat, and blacklisted name (or clan or pattern).
+///
+/// # Arguments
+/// * `scans` - The results to deduplicate.
+fn best_per_window(scans: Vec<ScanInfo>) -> Vec<ScanInfo> {
+    let mut best: HashMap<(usize, usize, String, Option<String>), ScanInfo> = HashMap::new();
+    for scan in scans {
+        let key = (scan.window_index, scan.card_index, scan.username.clone(), scan.clan.clone().or(scan.pattern.clone()));
+        match best.get(&key) {
+            Some(existing) if existing.similarity >= scan.similarity => {}
+            _ => {
+                best.insert(key, scan);
+            }
+        }
+    }
+
+    let mut scans: Vec<ScanInfo> = best.into_values().collect();
+    scans.sort_by_key(|scan| (scan.window_index, scan.card_index));
+    scans
+}
+
+/// Captures and scans a single RISK window.
+///
+/// # Arguments
+/// * `risk_window`: The RISK window to scan.
+/// * `window_identity`: The identity of the window, for remembering it.
+/// * `source`: Which window the capture is from, which names the crop files.
+/// * `config`: A reference to the [`Config`] to scan with.
+fn scan_window(
+    risk_window: &Window,
+    window_identity: WindowIdentity,
+    source: ScanSource,
+    config: &Config
+) -> anyhow::Result<ScanResult> {
+    ensure_not_minimized(risk_window)?;
+
+    // Wait out any loading screen before capturing for real.
+    if let Some(pre_scan_wait_secs) = config.pre_scan_wait_secs {
+        let mut classifier = CaptureLobbyClassifier::new(risk_window, config);
+        let in_lobby = lobby::wait_for_lobby(
+            &mut classifier,
+            Duration::from_secs(pre_scan_wait_secs),
+            LOBBY_POLL_INTERVAL
+        )?;
+        if !in_lobby {
+            bail!("Timed out waiting for the RISK lobby to load.");
+        }
+    }
+
+    let scrshot_path = paths::scrshot_path()
+        .ok_or(anyhow::anyhow!("Unable to construct screenshot path."))?;
+
+    scrshot_window(risk_window, &scrshot_path, config)?;
+    let mut scan_result = scan_image(&scrshot_path, source, config)?;
+    scan_result.window = Some(window_identity);
+    Ok(scan_result)
+}
+
+/// A window whose minimized state can be checked before capturing it.
+trait MinimizableWindow {
+    /// Whether the window is minimized.
+    fn is_minimized(&self) -> bool;
+}
+
+impl MinimizableWindow for Window {
+    fn is_minimized(&self) -> bool {
+        Window::is_minimized(self)
+    }
+}
+
+/// Fails if a window is minimized, as it would capture as stale or black frames and scan as an
+/// empty lobby.
+///
+/// # Arguments
+/// * `window`: The window about to be captured.
+fn ensure_not_minimized(window: &impl MinimizableWindow) -> anyhow::Result<()> {
+    if window.is_minimized() {
+        bail!(MINIMIZED_ERROR);
+    }
+    Ok(())
+}
+
+/// Runs a scan, turning any panic within it into an error, so a bug in the pipeline is reported in
+/// the error row instead of taking down the app.
+///
+/// # Arguments
+/// * `catch_panics` - Whether to catch panics. When false, the scan runs unguarded.
+/// * `scan` - The scan to run.
+pub(crate) fn guard_panics<T>(catch_panics: bool, scan: impl FnOnce() -> anyhow::Result<T>) -> anyhow::Result<T> {
+    if !catch_panics {
+        return scan();
+    }
+
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(scan)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("unknown panic"));
+        log::error!("The scan panicked: {message}");
+        bail!("The scan crashed ({message}). This is a bug, please report it with your blitz.log.")
+    })
+}
+
+/// A sample 1920x1080 lobby bundled with the app, for checking the pipeline works end-to-end.
+const SAMPLE_LOBBY_PNG: &[u8] = include_bytes!("../assets/sample-lobby.png");
+
+/// Memoizes similarity scores within a scan, so a detection read on several cards (or several times
+/// on one card) is only scored against each moron once.
+#[derive(Debug, Default)]
+pub(crate) struct SimilarityCache {
+    /// The scores computed so far, keyed by the detection and username.
+    scores: HashMap<(String, String), u8>,
+    /// The number of scores served from the cache.
+    pub hits: usize,
+    /// The number of scores computed.
+    pub misses: usize,
+}
+
+impl SimilarityCache {
+    /// Gets the similarity between a detection and a moron's username, scoring it with the matcher
+    /// only if the pair hasn't been scored before. A cache must only be used with one matcher.
+    ///
+    /// # Arguments
+    /// * `matcher` - The matcher to score with.
+    /// * `detection` - The detection, as read.
+    /// * `moron` - The moron to compare against.
+    pub(crate) fn score(&mut self, matcher: &dyn Matcher, detection: &str, moron: &Moron) -> u8 {
+        let key = (detection.to_string(), moron.username.clone());
+        if let Some(similarity) = self.scores.get(&key) {
+            self.hits += 1;
+            return *similarity;
+        }
+
+        self.misses += 1;
+        let similarity = matcher.score(detection, moron);
+        self.scores.insert(key, similarity);
+        similarity
+    }
+}
+
+/// Scores a detection against a moron: pattern entries by matching the pattern, exact entries by
+/// comparing names, and fuzzy entries with the matcher. The username and each alias are compared,
+/// and the best score is kept.
+///
+/// # Arguments
+/// * `matcher` - The matcher fuzzy entries are scored with.
+/// * `similarity_cache` - The cache of fuzzy scores for this scan.
+/// * `detection` - The detection, as read.
+/// * `moron` - The moron to compare against.
+/// * `prefixes` - The prefixes (e.g. ranks) to strip before comparing.
+pub(crate) fn score_moron(
+    matcher: &dyn Matcher,
+    similarity_cache: &mut SimilarityCache,
+    detection: &str,
+    moron: &Moron,
+    prefixes: &[String],
+) -> u8 {
+    if let Some(pattern) = &moron.pattern {
+        let matched = blacklist::glob_match(&normalize(pattern, &[]), &normalize(detection, prefixes));
+        return if matched { 100 } else { 0 };
+    }
+
+    // Aliases are scored as if they were the username, keeping the best score.
+    let mut best_similarity = 0;
+    for name in moron.names() {
+        let similarity = match moron.mode {
+            MatchMode::Fuzzy if name == moron.username => similarity_cache.score(matcher, detection, moron),
+            MatchMode::Fuzzy => {
+                let alias = Moron { username: name.to_string(), ..moron.clone() };
+                similarity_cache.score(matcher, detection, &alias)
+            }
+            MatchMode::Exact if moron.case_sensitive => exact_similarity(
+                &normalize_preserving_case(detection, prefixes),
+                &normalize_preserving_case(name, prefixes),
+            ),
+            MatchMode::Exact => exact_similarity(&normalize(detection, prefixes), &normalize(name, prefixes)),
+        };
+        best_similarity = best_similarity.max(similarity);
+    }
+    best_similarity
+}
+
+/// Scans the bundled sample lobby, so users can confirm the models and OCR work before relying on
+/// them in a real lobby. The sample is a 16:9 lobby laid out at the default position, so any
+/// configured region, offsets, or aspect preset are ignored.
+///
+/// # Arguments
+/// * `config`: A reference to the [`Config`] to scan with.
+pub(crate) fn demo_scan(config: &Config) -> anyhow::Result<ScanResult> {
+    log::info!("Scanning the sample lobby.");
+    let demo_scrshot_path = paths::demo_scrshot_path()
+        .ok_or(anyhow::anyhow!("Unable to construct the demo screenshot path."))?;
+    std::fs::write(&demo_scrshot_path, SAMPLE_LOBBY_PNG)?;
+
+    let demo_config = Config {
+        crop_offset_x: 0,
+        crop_offset_y: 0,
+        player_list_region: None,
+        aspect_preset: AspectPreset::Widescreen,
+        ..config.clone()
+    };
+    scan_image(&demo_scrshot_path, ScanSource::Demo, &demo_config)
+}
+
+/// Scans a screenshot of the RISK lobby for players and determines whether they are likely
+/// blacklisted. This runs the crop, OCR, and matching pipeline without capturing the window.
+///
+/// # Arguments
+/// * `scrshot_path`: A reference to the [`PathBuf`] of the screenshot to scan.
+/// * `source`: Where the screenshot came from, which names the crop files.
+/// * `config`: A reference to the [`Config`] to scan with.
+pub(crate) fn scan_image(scrshot_path: &PathBuf, source: ScanSource, config: &Config) -> anyhow::Result<ScanResult> {
     let blacklist_path = paths::blacklist_path().ok_or(anyhow::anyhow!("Unable to construct blacklist path."))?;
     let blacklist = match blacklist::Blacklist::load(&blacklist_path) {
         Ok(blacklist) => blacklist,
         Err(err) => bail!(format!("Blacklist Error: {}", err.to_string()))
     };
 
-    let risk_window = risk_window().ok_or(anyhow::anyhow!("Unable to find RISK window."))?;
+    for moron in blacklist.morons.iter().filter(|moron| moron.mode == MatchMode::Fuzzy && moron.pattern.is_none()) {
+        if let Some(generic_name) = blacklist::generic_name(&moron.username, config.min_username_length) {
+            log::warn!(
+                "'{}' {generic_name} and will cause false positives. Consider using exact mode for it.",
+                moron.username
+            );
+        }
+    }
 
-    let scrshot_path = paths::scrshot_path()
-        .ok_or(anyhow::anyhow!("Unable to construct screenshot path."))?;
+    let allowlist_path = paths::allowlist_path().ok_or(anyhow::anyhow!("Unable to construct allowlist path."))?;
+    let allowlist = match allowlist::Allowlist::load(&allowlist_path) {
+        Ok(allowlist) => allowlist,
+        Err(err) => bail!(format!("Allowlist Error: {}", err.to_string()))
+    };
 
-    scrshot_window(&risk_window, &scrshot_path)?;
-    crop_player_cards_1920_1080(&scrshot_path)?;
+    let mut timings = ScanTimings::default();
 
-    let engine = create_ocr_engine()?;
-    let mut detections: Vec<String> = Vec::new();
-    for i in 0..6 {
-        let player_scrshot_path = paths::player_scrshot_path(i)
-            .ok_or(anyhow::anyhow!("Unable to construct player screenshot path."))?;
+    let crop_started = Instant::now();
+    let player_card_images = crop_player_cards_dynamic(scrshot_path, source, config)?;
+    timings.crop = crop_started.elapsed();
 
-        dbg!(&player_scrshot_path);
+    let ocr_started = Instant::now();
+    let player_cards = read_player_cards(player_card_images, config)?;
+    timings.ocr = ocr_started.elapsed();
 
-        let text = detect_text(&engine, &player_scrshot_path)?;
-        detections.extend(text);
-    }
+    let mut scan_result = match_cards(player_cards, &blacklist, &allowlist, source, config)?;
+    scan_result.timings.crop = timings.crop;
+    scan_result.timings.ocr = timings.ocr;
+    log::info!(
+        "Scan took {:?} to crop, {:?} to read, and {:?} to match.",
+        scan_result.timings.crop,
+        scan_result.timings.ocr,
+        scan_result.timings.matching
+    );
+    Ok(scan_result)
+}
 
+/// Matches the text read from each player card against the blacklist, marking allowlisted players
+/// as friendly, and keeps the best result for each moron. Only the matching time is measured.
+///
+/// # Arguments
+/// * `player_cards`: Each card's image and the lines read from it, in card order.
+/// * `blacklist`: The blacklist to match against.
+/// * `allowlist`: The players to mark as friendly.
+/// * `source`: Where the screenshot came from, which names the crop files in the report.
+/// * `config`: A reference to the [`Config`] to match with.
+fn match_cards(
+    player_cards: Vec<(RgbImage, Vec<String>)>,
+    blacklist: &blacklist::Blacklist,
+    allowlist: &allowlist::Allowlist,
+    source: ScanSource,
+    config: &Config
+) -> anyhow::Result<ScanResult> {
+    let mut timings = ScanTimings::default();
     let mut scans: Vec<ScanInfo> = Vec::new();
-    for detection_text in detections.iter()
-    {
-        let detection_text_normalised = normalize(&detection_text);
-        if detection_text_normalised.len() <= 1 {
-            continue;
+    let mut report = ScanReport::new();
+    let mut matcher = matcher::matcher_by_name(&config.matcher, &blacklist.prefixes)?;
+    if config.ocr_confusions {
+        matcher = Box::new(ConfusionMatcher::new(matcher, &blacklist.prefixes));
+    }
+    if config.match_raw_form {
+        matcher = Box::new(RawFormMatcher::new(matcher));
+    }
+    let mut similarity_cache = SimilarityCache::default();
+    // The morons as they are matched against. Results still show the names as blacklisted.
+    let matching_morons: Vec<Moron> = blacklist.morons
+        .iter()
+        .map(|moron| Moron {
+            username: prepare_for_matching(&moron.username, config),
+            aliases: moron.aliases.iter().map(|alias| prepare_for_matching(alias, config)).collect(),
+            ..moron.clone()
+        })
+        .collect();
+    let mut occupied_cards = 0;
+    let mut detected_names: Vec<DetectedName> = Vec::new();
+    for (i, (player_card_image, raw_detections)) in player_cards.into_iter().enumerate() {
+        if has_confident_detection(&raw_detections) {
+            occupied_cards += 1;
         }
 
-        for moron in blacklist.morons.iter() {
-            // 5.2. Ensure the moron's username is in lowercase.
-            let username_normalised = normalize(&moron.username);
-            let similarity = fuzzywuzzy::fuzz::ratio(&detection_text_normalised, &username_normalised);
-            scans.push(ScanInfo {
-                username: String::from(&moron.username),
-                similarity: similarity
-            });
+        // Drop the whole card when it reads as noise overall, e.g. a graphical card.
+        let mut matching_detections: &[String] = &raw_detections;
+        if let Some(min_card_confidence) = config.min_card_confidence {
+            let confidence = card_confidence(&raw_detections);
+            if confidence < min_card_confidence {
+                log::debug!("Dropping the detections on card {i} with confidence {confidence:.2}.");
+                matching_detections = &[];
+            }
+        }
+
+        // Keep the cleanly read names, leaving out the player's own.
+        detected_names.extend(matching_detections
+            .iter()
+            .filter(|detection_text| !detection_text.to_lowercase().contains(&OWN_CARD_MARKER.to_lowercase()))
+            .map(|detection_text| strip_card_noise(detection_text).trim())
+            .filter(|name| name.chars().count() > 1 && detection_confidence(name) >= MIN_DETECTION_CONFIDENCE)
+            .map(|name| DetectedName { name: name.to_string(), card_index: i, window_index: 0 }));
+
+        let matching_started = Instant::now();
+        let normalized_detections: Vec<String> = matching_detections
+            .iter()
+            .map(|detection_text| normalize(detection_text, &blacklist.prefixes))
+            .collect();
+
+        let mut scores: Vec<MatchScore> = Vec::new();
+        for (detection_text, detection_text_normalised) in matching_detections.iter().zip(normalized_detections.iter()) {
+            if detection_text_normalised.len() <= 1 {
+                continue;
+            }
+
+            let friendly = allowlist.find(detection_text_normalised, &blacklist.prefixes).cloned();
+
+            // Flag anyone wearing a blacklisted clan tag, whatever their own name.
+            if let Some(clan) = blacklist.find_clan(detection_text) {
+                scans.push(ScanInfo {
+                    username: detection_text.trim().to_string(),
+                    similarity: 100,
+                    friendly: friendly.clone(),
+                    clan: Some(clan.tag.clone()),
+                    reason: clan.reason.clone(),
+                    severity: Severity::Medium,
+                    evidence_url: None,
+                    card_index: i,
+                    pinned: false,
+                    pattern: None,
+                    window_index: 0,
+                    note: None,
+                    read_quality: detection_confidence(detection_text),
+                });
+            }
+
+            // Every pair is reported, but only the closest moron becomes a result.
+            let detection_for_matching = prepare_for_matching(detection_text, config);
+            let mut best_match: Option<(&Moron, u8)> = None;
+            for (moron, matching_moron) in blacklist.morons.iter().zip(matching_morons.iter()) {
+                let similarity = score_moron(
+                    matcher.as_ref(),
+                    &mut similarity_cache,
+                    &detection_for_matching,
+                    matching_moron,
+                    &blacklist.prefixes
+                );
+                scores.push(MatchScore {
+                    detection: detection_text_normalised.clone(),
+                    username: String::from(&moron.username),
+                    similarity,
+                });
+                if best_match.map_or(true, |(_, best_similarity)| similarity > best_similarity) {
+                    best_match = Some((moron, similarity));
+                }
+            }
+            if let Some((moron, similarity)) = best_match {
+                scans.push(moron_scan_info(moron, detection_text, similarity, friendly, i));
+            }
         }
+
+        // Search for names embedded in the card's text as a whole, which line matching can't find.
+        if config.partial_card_matching && !matching_detections.is_empty() {
+            let card_text = matching_detections.join(" ");
+            let card_text_for_matching = normalize(&prepare_for_matching(&card_text, config), &blacklist.prefixes);
+            let friendly = allowlist.find(&normalize(&card_text, &blacklist.prefixes), &blacklist.prefixes).cloned();
+            let mut best_match: Option<(&Moron, u8)> = None;
+            for (moron, matching_moron) in blacklist.morons.iter().zip(matching_morons.iter()) {
+                if moron.mode != MatchMode::Fuzzy || moron.pattern.is_some() {
+                    continue;
+                }
+
+                let similarity = partial_similarity(
+                    &card_text_for_matching,
+                    &normalize(&matching_moron.username, &blacklist.prefixes),
+                );
+                scores.push(MatchScore {
+                    detection: card_text_for_matching.clone(),
+                    username: String::from(&moron.username),
+                    similarity,
+                });
+                if best_match.map_or(true, |(_, best_similarity)| similarity > best_similarity) {
+                    best_match = Some((moron, similarity));
+                }
+            }
+            if let Some((moron, similarity)) = best_match {
+                scans.push(moron_scan_info(moron, &card_text, similarity, friendly, i));
+            }
+        }
+        timings.matching += matching_started.elapsed();
+
+        report.cards.push(CardReport {
+            card_index: i,
+            crop_hash: report::hash_bytes(player_card_image.as_raw()),
+            // The crops are only written to disk when debugging the crop regions.
+            crop_path: config.save_crop_debug.then(|| paths::player_scrshot_path(source, i as i32)).flatten(),
+            raw_detections,
+            normalized_detections,
+            scores,
+            window_index: 0,
+        });
     }
 
-    dbg!(&scans);
+    log::debug!("Scan produced {} results: {:?}", scans.len(), scans);
+    log::debug!(
+        "Scored {} detection pairs and reused {} cached scores.",
+        similarity_cache.misses,
+        similarity_cache.hits
+    );
+
+    let warning = config.min_occupied_cards
+        .filter(|min_occupied_cards| occupied_cards < *min_occupied_cards)
+        .map(|min_occupied_cards| {
+            log::warn!("Only {occupied_cards} of the expected {min_occupied_cards} cards could be read.");
+            format!("Scan likely incomplete - only {occupied_cards} cards read. Check the alignment.")
+        });
 
-    Ok(scans)
+    let mut scans = best_per_username(scans);
+    if let (Some(min_read_quality), true) = (config.min_read_quality, config.hide_low_read_quality) {
+        scans.retain(|scan| scan.read_quality >= min_read_quality);
+    }
+    Ok(ScanResult { scans, report, timings, window: None, warning, occupied_cards, detected_names })
 }
 
-/// Retrieves the window representing the game "RISK", if it exists.
-pub(crate) fn risk_window() -> Option<Window> {
-    let active_windows_result = xcap::Window::all();
-    match active_windows_result {
-        Ok(active_windows) => {
-            active_windows
-                .iter()
-                .find(|w| w.title() == "RISK")
-                .cloned()
+/// Reads the text on each of the six player cards cropped from a screenshot on the OCR worker,
+/// returning each card's image and lines in card order. Cards the primary recognition model can't
+/// read get a fallback pass with the secondary model, if one is configured. A card whose OCR
+/// outlasts the configured timeout is abandoned and reads as empty.
+///
+/// # Arguments
+/// * `player_card_images`: The cropped player cards, in card order.
+/// * `config`: A reference to the [`Config`] holding the secondary model and OCR timeout.
+fn read_player_cards(player_card_images: Vec<RgbImage>, config: &Config) -> anyhow::Result<Vec<(RgbImage, Vec<String>)>> {
+    read_cards(
+        player_card_images,
+        |cards| read_on_ocr_worker(cards, false, config),
+        |cards| {
+            if config.secondary_recognition_model.is_none() {
+                return Ok(vec![None; cards.len()]);
+            }
+            log::debug!("Running the secondary recognition pass on {} cards.", cards.len());
+            read_on_ocr_worker(cards, true, config)
         },
-        Err(_) => None,
+    )
+}
+
+/// Reads the text on each player card with the given readers, returning each card's image and
+/// lines in card order. Cards without a confident detection are read again with the fallback
+/// reader, and its lines are added to the card's. A reader returns [`None`] for each card it
+/// abandons, which then reads as empty.
+///
+/// # Arguments
+/// * `player_card_images`: The cropped player cards, in card order.
+/// * `read`: Reads the lines on the given cards, with their indices, in the order given.
+/// * `read_fallback`: Reads the lines on the cards the first reader couldn't read.
+fn read_cards<R, F>(player_card_images: Vec<RgbImage>, read: R, read_fallback: F) -> anyhow::Result<Vec<(RgbImage, Vec<String>)>>
+where
+    R: FnOnce(&[(usize, &RgbImage)]) -> anyhow::Result<Vec<Option<Vec<String>>>>,
+    F: FnOnce(&[(usize, &RgbImage)]) -> anyhow::Result<Vec<Option<Vec<String>>>>,
+{
+    let cards: Vec<(usize, &RgbImage)> = player_card_images.iter().enumerate().collect();
+    log::debug!("Detecting text in {} cards.", cards.len());
+    let mut raw_detections = read(&cards)?;
+
+    // Fall back to the secondary reader for cards the primary pass couldn't read.
+    let unread_cards: Vec<(usize, &RgbImage)> = cards
+        .iter()
+        .copied()
+        .filter(|(i, _)| raw_detections[*i].as_ref().is_some_and(|detections| !has_confident_detection(detections)))
+        .collect();
+    if !unread_cards.is_empty() {
+        for ((i, _), fallback_detections) in unread_cards.iter().zip(read_fallback(&unread_cards)?) {
+            if let (Some(detections), Some(fallback_detections)) = (&mut raw_detections[*i], fallback_detections) {
+                detections.extend(fallback_detections);
+            }
+        }
     }
+
+    Ok(player_card_images
+        .into_iter()
+        .zip(raw_detections)
+        .enumerate()
+        .map(|(i, (player_card_image, raw_detections))| {
+            // Abandon a card whose OCR hung rather than freezing the whole scan.
+            let raw_detections = raw_detections.unwrap_or_else(|| {
+                log::warn!("Abandoned card {i} after its OCR took too long.");
+                Vec::new()
+            });
+            (player_card_image, raw_detections)
+        })
+        .collect())
+}
+
+/// Keeps only the best result for each blacklisted name (or clan or pattern), as OCR often reads a
+/// card as several overlapping lines that each match the same moron. Friendly and unfriendly results
+/// are kept apart, so a friendly player who happens to match a moron better never hides the moron.
+/// Results keep their order.
+///
+/// # Arguments
+/// * `scans` - The results to deduplicate.
+fn best_per_username(scans: Vec<ScanInfo>) -> Vec<ScanInfo> {
+    let mut best: Vec<ScanInfo> = Vec::with_capacity(scans.len());
+    let mut positions: HashMap<(String, Option<String>, bool), usize> = HashMap::new();
+    for scan in scans {
+        let key = (scan.username.clone(), scan.clan.clone().or(scan.pattern.clone()), scan.friendly.is_some());
+        match positions.get(&key) {
+            Some(position) if best[*position].similarity >= scan.similarity => {}
+            Some(position) => best[*position] = scan,
+            None => {
+                positions.insert(key, best.len());
+                best.push(scan);
+            }
+        }
+    }
+    best
+}
+
+/// Creates the result for a detection's closest moron.
+///
+/// # Arguments
+/// * `moron` - The closest moron, as blacklisted.
+/// * `detection_text` - The text the moron was matched in, as read.
+/// * `similarity` - The similarity between the text and the moron.
+/// * `friendly` - The allowlisted username the text belongs to, if any.
+/// * `card_index` - The index of the player card the text was read from.
+fn moron_scan_info(
+    moron: &Moron,
+    detection_text: &str,
+    similarity: u8,
+    friendly: Option<String>,
+    card_index: usize,
+) -> ScanInfo {
+    ScanInfo {
+        // Patterns flag players by their detected name, so show that instead.
+        username: match &moron.pattern {
+            Some(_) => detection_text.trim().to_string(),
+            None => String::from(&moron.username),
+        },
+        similarity,
+        friendly,
+        clan: None,
+        reason: moron.reason.clone(),
+        severity: moron.severity,
+        evidence_url: moron.evidence_url.clone(),
+        card_index,
+        pinned: moron.pinned,
+        pattern: moron.pattern.clone(),
+        window_index: 0,
+        note: moron.note.clone(),
+        read_quality: detection_confidence(detection_text),
+    }
+}
+
+/// Filters scan results down to those at or above the similarity threshold. This is pure over the
+/// results, so the threshold can be tuned against the last scan without scanning again.
+///
+/// # Arguments
+/// * `scans` - The unfiltered results of a scan.
+/// * `similarity_threshold` - The minimum similarity for a result to be kept.
+pub(crate) fn similar_scans(scans: &[ScanInfo], similarity_threshold: u8) -> Vec<&ScanInfo> {
+    scans
+        .iter()
+        .filter(|s| s.similarity >= similarity_threshold)
+        .collect()
+}
+
+/// Retrieves the window representing the game "RISK", if it exists.
+///
+/// # Arguments
+/// * `remembered` - The window to prefer, if one was remembered.
+pub(crate) fn risk_window(remembered: Option<&WindowIdentity>) -> Option<Window> {
+    find_risk_window(remembered).ok().flatten()
+}
+
+/// Retrieves the window representing the game "RISK", if it exists, failing if the active windows
+/// can't be listed.
+///
+/// # Arguments
+/// * `remembered` - The window to prefer, if one was remembered.
+pub(crate) fn find_risk_window(remembered: Option<&WindowIdentity>) -> anyhow::Result<Option<Window>> {
+    Ok(identify_risk_window(remembered)?.map(|(risk_window, _)| risk_window))
+}
+
+/// Retrieves every window representing the game "RISK" along with their identities.
+fn identify_risk_windows() -> anyhow::Result<Vec<(Window, WindowIdentity)>> {
+    let active_windows = xcap::Window::all()?;
+    let candidates: Vec<WindowCandidate> = active_windows.iter().map(WindowCandidate::from_window).collect();
+    let matches = windows::resolve_all(&candidates);
+    Ok(active_windows
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| matches.contains(index))
+        .map(|(index, window)| (window, windows::identify(&candidates, index)))
+        .collect())
+}
+
+/// Retrieves the window representing the game "RISK" along with its identity, for remembering it.
+///
+/// # Arguments
+/// * `remembered` - The window to prefer, if one was remembered.
+fn identify_risk_window(remembered: Option<&WindowIdentity>) -> anyhow::Result<Option<(Window, WindowIdentity)>> {
+    let mut active_windows = xcap::Window::all()?;
+    let candidates: Vec<WindowCandidate> = active_windows.iter().map(WindowCandidate::from_window).collect();
+    Ok(windows::resolve(&candidates, remembered).map(|picked| {
+        let identity = windows::identify(&candidates, picked);
+        (active_windows.swap_remove(picked), identity)
+    }))
 }
 
 
@@ -125,87 +905,245 @@ pub(crate) fn risk_window() -> Option<Window> {
 /// # Arguments
 /// * `window`: A reference to the [`xcap::Window`] to capture the screenshot from.
 /// * `path`: A reference to the [`PathBuf`] representing the path where the screenshot will be saved.
+/// * `config`: A reference to the [`Config`] holding the DPI to save with.
 pub(crate) fn scrshot_window(
     window: &xcap::Window,
-    path: &PathBuf
+    path: &PathBuf,
+    config: &Config
 ) -> anyhow::Result<()> {
-    let image = window.capture_image()?;
-    Ok(image.save(&path)?)
+    let image = capture_window(window)?;
+    save_png(&image, path, config.png_dpi)
 }
 
-/// Crops the player cards from the screenshot image and saves them individually to the app directory
-/// with an indexed file name.
+/// Saves an image as a PNG with consistent DPI metadata: a `pHYs` chunk for the given DPI, or no
+/// `pHYs` chunk at all.
 ///
 /// # Arguments
-/// * `scrshot_path`: A reference to the [`PathBuf`] representing the path to the screenshot image to crop.
+/// * `image`: A reference to the image to save.
+/// * `path`: A reference to the [`PathBuf`] to save the image to.
+/// * `dpi`: The DPI to record, or [`None`] to record none.
+pub(crate) fn save_png(image: &RgbaImage, path: &PathBuf, dpi: Option<u32>) -> anyhow::Result<()> {
+    let png_file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut png_encoder = png::Encoder::new(png_file, image.width(), image.height());
+    png_encoder.set_color(png::ColorType::Rgba);
+    png_encoder.set_depth(png::BitDepth::Eight);
+    if let Some(dpi) = dpi {
+        // PNG records density in pixels per metre.
+        let pixels_per_metre = (dpi as f64 / 0.0254).round() as u32;
+        png_encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: pixels_per_metre,
+            yppu: pixels_per_metre,
+            unit: png::Unit::Meter,
+        }));
+    }
+
+    let mut png_writer = png_encoder.write_header()?;
+    png_writer.write_image_data(image.as_raw())?;
+    Ok(png_writer.finish()?)
+}
+
+/// Captures the specified window, explaining the screen-capture portal if the capture fails (or
+/// comes back blank) in a Wayland session.
 ///
-/// # Warning
-/// This method assumes the dimension is 1920x1080px. Otherwise, it will not work.
-pub(crate) fn crop_player_cards_1920_1080(scrshot_path: &PathBuf) -> anyhow::Result<()> {
-    // Crop the surrounding space out of the player list.
-    // =============================
-    // ||| [Player 1] [Player 2] |||
-    // ||| [Player 3] [Player 4] |||
-    // ||| [Player 5] [Player 6] |||
-    // =============================
-    let mut image = image::io::Reader::open(&scrshot_path)?.decode()?;
-    let player_list_width = 1200;
-    let player_list_height = 550;
-    let player_list_start_x = (image.width() - player_list_width) / 2;
-    let player_list_start_y = (image.height() - player_list_height) / 2;
-    let player_list_image = image.crop(
-        player_list_start_x,
-        player_list_start_y,
-        player_list_width,
-        player_list_height
-    );
-    // Crop the individual players cards out of the player list.
-    // [Player 1] [Player 2]
-    // [Player 3] [Player 4]
-    // [Player 5] [Player 6]
-    let player_card_width = 600;
-    let player_card_height = 180;
-    for row in 0..3 {
-        for col in 0..2 {
-            let player_card_start_x = col * player_card_width;
-            let player_card_start_y = row * player_card_height;
-            let player_card_image = player_list_image.clone().crop(
-                player_card_start_x,
-                player_card_start_y,
-                player_card_width,
-                player_card_height);
-            let player_card_index = (row * 2 + col) as i32;
-            let player_scrshot_path = paths::player_scrshot_path(player_card_index)
+/// # Arguments
+/// * `window`: A reference to the [`xcap::Window`] to capture.
+pub(crate) fn capture_window(window: &xcap::Window) -> anyhow::Result<RgbaImage> {
+    let wayland = is_wayland_session();
+    let image = window.capture_image().map_err(|err| capture_error(err, wayland))?;
+
+    // Without the portal permission, some compositors hand back an all-black frame instead of an error.
+    if wayland && image.pixels().all(|pixel| pixel.0[..3] == [0, 0, 0]) {
+        return Err(capture_error("the capture was blank", wayland));
+    }
+
+    Ok(image)
+}
+
+/// Whether the app is running in a Wayland session, where window capture goes through the
+/// screen-capture portal.
+pub(crate) fn is_wayland_session() -> bool {
+    std::env::var("XDG_SESSION_TYPE").is_ok_and(|session_type| session_type.eq_ignore_ascii_case("wayland"))
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// Maps a window listing or capture error to the message shown to the user. In a Wayland session,
+/// the message explains the screen-capture portal requirement instead of the raw error.
+///
+/// # Arguments
+/// * `err`: The underlying error.
+/// * `wayland`: Whether the app is running in a Wayland session.
+pub(crate) fn capture_error(err: impl std::fmt::Display, wayland: bool) -> anyhow::Error {
+    if wayland {
+        anyhow::anyhow!(
+            "Unable to capture RISK ({err}). Wayland only allows screen capture through the \
+            screen-capture portal: install xdg-desktop-portal for your desktop, allow Blitz to \
+            share your screen, or log in to an X11 session. See {}",
+            paths::WAYLAND_CAPTURE_HELP_URL
+        )
+    } else {
+        anyhow::anyhow!("Unable to capture RISK: {err}")
+    }
+}
+
+/// Crops the player cards from the screenshot image adjusting for various monitor resolutions and
+/// aspect ratios, returning them in card order. The player list and its 2x3 grid of cards are sized
+/// in proportion to the screenshot's dimensions. The crops are kept in memory, and only saved to the
+/// app directory, with an indexed file name, when debugging the crop regions.
+///
+/// # Arguments
+/// * `scrshot_path`: A reference to the [`PathBuf`] representing the path to the screenshot image to crop.
+/// * `source`: Where the screenshot came from, which names the debug crop files.
+/// * `config`: A reference to the [`Config`] holding the crop region adjustments.
+fn crop_player_cards_dynamic(
+    scrshot_path: &PathBuf,
+    source: ScanSource,
+    config: &Config
+) -> anyhow::Result<Vec<RgbImage>> {
+    // Locate the player list within the screenshot, then crop the individual player cards out of it.
+    let image = image::io::Reader::open(&scrshot_path)?.decode()?;
+    let player_list_rect = resolve_player_list_rect(image.width(), image.height(), &crop::content_rect(&image), config);
+    let player_card_rects = crop::player_card_rects(&player_list_rect);
+
+    if config.save_crop_debug {
+        let mut rects = vec![player_list_rect];
+        rects.extend(player_card_rects.iter().copied());
+        let overlay = crop::draw_overlay(&image.to_rgba8(), &rects, image::Rgba([255, 0, 0, 255]), 2);
+        let debug_scrshot_path = paths::debug_scrshot_path()
+            .ok_or(anyhow::anyhow!("Unable to construct the debug screenshot path."))?;
+        save_png(&overlay, &debug_scrshot_path, config.png_dpi)?;
+    }
+
+    let mut player_card_images = Vec::with_capacity(player_card_rects.len());
+    for (player_card_index, player_card_rect) in player_card_rects.iter().enumerate() {
+        let player_card_image = preprocess_for_ocr(
+            image.crop_imm(
+                player_card_rect.x,
+                player_card_rect.y,
+                player_card_rect.width,
+                player_card_rect.height),
+            &config.ocr_preprocessing
+        );
+        if config.save_crop_debug {
+            let player_scrshot_path = paths::player_scrshot_path(source, player_card_index as i32)
                 .ok_or(anyhow::anyhow!("Unable to construct player screenshot path."))?;
-            player_card_image.save(player_scrshot_path)?;
+            save_png(&player_card_image.to_rgba8(), &player_scrshot_path, config.png_dpi)?;
         }
+        player_card_images.push(player_card_image.to_rgb8());
     }
 
-    Ok(())
+    Ok(player_card_images)
 }
 
-/// Crops the player cards from the screenshot image adjusting for various monitor aspect ratios and
-/// saves them individually to the app directory with an indexed file name.
+/// Resolves the player-list region of a capture from the [`Config`]: the selected region if there is
+/// one, otherwise the default centered within the game content, shifted by the configured offsets.
 ///
 /// # Arguments
-/// * `scrshot_path`: A reference to the [`PathBuf`] representing the path to the screenshot image to crop.
-fn crop_and_save_player_cards_dynamic(scrshot_path: &PathBuf) -> anyhow::Result<()> {
-    todo!()
+/// * `image_width`: The width of the capture.
+/// * `image_height`: The height of the capture.
+/// * `content`: The game content of the capture, from [`crop::content_rect`].
+/// * `config`: A reference to the [`Config`] holding the region and offsets.
+pub(crate) fn resolve_player_list_rect(
+    image_width: u32,
+    image_height: u32,
+    content: &CropRect,
+    config: &Config
+) -> CropRect {
+    match &config.player_list_region {
+        Some(region) => crop::region_rect(
+            region,
+            image_width,
+            image_height,
+            config.crop_offset_x,
+            config.crop_offset_y
+        ),
+        None => crop::player_list_rect(
+            crop::resolve_aspect_preset(config.aspect_preset, content.width, content.height),
+            content,
+            config.crop_offset_x,
+            config.crop_offset_y
+        ),
+    }
 }
 
 /// Creates an OCR engine using the detection and recognition models from the app directory.
 pub(crate) fn create_ocr_engine() -> anyhow::Result<OcrEngine> {
-    // Get the paths to the detection and recognition models
+    let recognition_model_path = paths::recognition_model_path()
+        .ok_or(anyhow::anyhow!("Unable to construct recognition model path."))?;
+    create_ocr_engine_with_recognition_model(&recognition_model_path)
+}
+
+/// The OCR engine kept between scans by the OCR worker (see [`OcrWorker`]), so the models are only
+/// read and parsed once, with the model files it was built from.
+struct CachedOcrEngine {
+    /// The path and last-modified time of each model file, when the engine was built.
+    models: Vec<(PathBuf, Option<SystemTime>)>,
+    /// The engine built from the models.
+    engine: OcrEngine,
+}
+
+/// Gets the OCR engine for the detection and recognition models in the app directory, building it
+/// only if there is none in the cache yet or either model file was replaced since it was built.
+///
+/// # Arguments
+/// * `cache`: The OCR worker's cached engine, if it has built one.
+fn cached_ocr_engine(cache: &mut Option<CachedOcrEngine>) -> anyhow::Result<&OcrEngine> {
     let detection_model_path = paths::detection_model_path()
         .ok_or(anyhow::anyhow!("Unable to construct detection model path."))?;
     let recognition_model_path = paths::recognition_model_path()
         .ok_or(anyhow::anyhow!("Unable to construct recognition model path."))?;
-    // Read the model data from the files
-    let detection_model_data = std::fs::read(&detection_model_path)?;
-    let recognition_model_data = std::fs::read(&recognition_model_path)?;
-    // Load the detection and recognition models
-    let detection_model = Model::load(&detection_model_data)?;
-    let recognition_model = Model::load(&recognition_model_data)?;
+    let models: Vec<(PathBuf, Option<SystemTime>)> = [detection_model_path, recognition_model_path]
+        .into_iter()
+        .map(|model_path| {
+            let modified = std::fs::metadata(&model_path).and_then(|metadata| metadata.modified()).ok();
+            (model_path, modified)
+        })
+        .collect();
+
+    if cache.as_ref().is_none_or(|cached| cached.models != models) {
+        log::info!("Loading the OCR models.");
+        *cache = Some(CachedOcrEngine { models, engine: create_ocr_engine()? });
+    }
+    Ok(&cache.as_ref().expect("the engine was just cached").engine)
+}
+
+/// Creates the secondary OCR engine from the recognition model configured for the fallback pass.
+///
+/// The secondary pass is optional, so if it is not configured, or its model is missing or fails to
+/// load, this function logs why and returns [`None`] rather than failing the scan.
+///
+/// # Arguments
+/// * `config`: A reference to the [`Config`] holding the secondary recognition model path.
+pub(crate) fn create_secondary_ocr_engine(config: &Config) -> Option<OcrEngine> {
+    let recognition_model_path = config.secondary_recognition_model.as_ref()?;
+    if !recognition_model_path.exists() {
+        log::warn!(
+            "The secondary recognition model {} does not exist. Skipping the fallback pass.",
+            recognition_model_path.display()
+        );
+        return None;
+    }
+
+    match create_ocr_engine_with_recognition_model(recognition_model_path) {
+        Ok(ocr_engine) => Some(ocr_engine),
+        Err(err) => {
+            log::warn!("Unable to load the secondary recognition model: {err}. Skipping the fallback pass.");
+            None
+        }
+    }
+}
+
+/// Creates an OCR engine using the detection model from the app directory and the specified
+/// recognition model.
+///
+/// # Arguments
+/// * `recognition_model_path`: A reference to the [`PathBuf`] of the recognition model to load.
+pub(crate) fn create_ocr_engine_with_recognition_model(recognition_model_path: &PathBuf) -> anyhow::Result<OcrEngine> {
+    // Get the path to the detection model
+    let detection_model_path = paths::detection_model_path()
+        .ok_or(anyhow::anyhow!("Unable to construct detection model path."))?;
+    // Load the detection and recognition models, naming whichever one is missing or broken
+    let detection_model = load_model(&Asset::DetectionModel.to_string(), &detection_model_path)?;
+    let recognition_model = load_model(&Asset::RecognitionModel.to_string(), &recognition_model_path)?;
     // Create an OCR engine using the loaded models
     let ocr_engine = OcrEngine::new(OcrEngineParams {
         detection_model: Some(detection_model),
@@ -217,38 +1155,981 @@ pub(crate) fn create_ocr_engine() -> anyhow::Result<OcrEngine> {
     Ok(ocr_engine)
 }
 
-/// Detects text (transformed to lowercase) from an image using the provided OCR engine.
+/// Creates the builder for the thread pool the OCR engine runs its models on, capped to the
+/// configured number of threads.
+///
+/// # Arguments
+/// * `ocr_threads`: The number of threads to use, or [`None`] for one per CPU core.
+fn ocr_thread_pool(ocr_threads: Option<usize>) -> rayon::ThreadPoolBuilder {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(ocr_threads.unwrap_or_default())
+        .thread_name(|i| format!("ocr-{i}"))
+}
+
+/// Detects text from an image using the provided OCR engine, returning each line as read.
 ///
 /// # Arguments
 /// * `ocr_engine`: A reference to the OCR engine ([`OcrEngine`]) used for text detection.
-/// * `scrshot_path`: A reference to the [`PathBuf`] representing the path to the image.
+/// * `image`: The image, as a tensor from [`image_to_tensor`].
 pub(crate) fn detect_text(
     ocr_engine: &OcrEngine,
-    image_path: &PathBuf
+    image: NdTensorView<f32, 3>
 ) -> anyhow::Result<Vec<String>> {
     // Detect the text from the image.
-    let image = rten_imageio::read_image(&image_path.display().to_string())
-        .expect("Unable to read image into the tensor.");
-    let ocr_input = ocr_engine.prepare_input(image.view())?;
+    let ocr_input = ocr_engine.prepare_input(image)?;
     let text = ocr_engine.get_text(&ocr_input)?;
     // Split it on newlines to get an array of detected text chunks.
     Ok(text.split('\n')
-        .map(|s| normalize(&s))
+        .map(String::from)
         .collect()
     )
 }
 
-/// Normalizes a string by converting it to lowercase and removing spaces.
+/// Prepares a player card for the OCR with the configured steps: converting it to grayscale, then
+/// upscaling it 2x, then boosting its contrast. With every step off, the card is returned as it is.
+///
+/// # Arguments
+/// * `image` - The cropped player card.
+/// * `preprocessing` - The steps to apply.
+pub(crate) fn preprocess_for_ocr(image: DynamicImage, preprocessing: &OcrPreprocessing) -> DynamicImage {
+    let mut image = image;
+    if preprocessing.grayscale {
+        image = image.grayscale();
+    }
+    if preprocessing.upscale {
+        image = image.resize_exact(image.width() * 2, image.height() * 2, FilterType::CatmullRom);
+    }
+    if preprocessing.boost_contrast {
+        image = image.adjust_contrast(OCR_CONTRAST_BOOST);
+    }
+    image
+}
+
+/// Converts an image into the tensor the OCR engine reads: its red, green, and blue channels, one
+/// after the other, with values from 0 to 1.
+///
+/// # Arguments
+/// * `image`: A reference to the image to convert.
+pub(crate) fn image_to_tensor(image: &RgbImage) -> NdTensor<f32, 3> {
+    let (width, height) = image.dimensions();
+    let mut data = Vec::with_capacity(3 * width as usize * height as usize);
+    for channel in 0..3 {
+        data.extend(image.pixels().map(|pixel| pixel[channel] as f32 / 255.0));
+    }
+    NdTensor::from_data([3, height as usize, width as usize], data)
+}
+
+/// Loads an OCR model, failing with an error that names the model if it's missing or can't be loaded.
+///
+/// # Arguments
+/// * `model_name`: The name of the model, for the error message.
+/// * `model_path`: A reference to the [`PathBuf`] of the model to load.
+pub(crate) fn load_model(model_name: &str, model_path: &PathBuf) -> anyhow::Result<Model> {
+    if !model_path.exists() {
+        bail!(
+            "The {} is missing from {}. Re-download it from the Maintenance view.",
+            model_name.to_lowercase(),
+            model_path.display()
+        );
+    }
+
+    let model_data = std::fs::read(model_path)
+        .map_err(|err| anyhow::anyhow!("Unable to read the {}: {err}", model_name.to_lowercase()))?;
+    Model::load(&model_data).map_err(|err| anyhow::anyhow!(
+        "The {} at {} is corrupt ({err}). Re-download it from the Maintenance view.",
+        model_name.to_lowercase(),
+        model_path.display()
+    ))
+}
+
+/// The error for a scan whose OCR worker stopped before reading every card.
+const OCR_WORKER_STOPPED: &str = "The OCR worker stopped unexpectedly.";
+
+/// Work for the OCR worker, given the engine it keeps between scans.
+type OcrJob = Box<dyn FnOnce(&mut Option<CachedOcrEngine>) + Send>;
+
+/// A long-lived thread the OCR runs on. `rten` models can't be sent between threads, so the OCR
+/// engines are built, kept, and used on the worker, and scans hand it the cards to read.
+struct OcrWorker {
+    /// The number of threads the worker's thread pool was built with, or [`None`] for the default.
+    ocr_threads: Option<usize>,
+    /// Where to send the worker its jobs.
+    jobs: Sender<OcrJob>,
+}
+
+impl OcrWorker {
+    /// Starts a worker on a thread pool of its own, which runs the jobs it's sent in turn until its
+    /// job queue closes. The jobs run within the pool, so the models do too.
+    ///
+    /// # Arguments
+    /// * `ocr_threads`: The number of threads to run the models on, or [`None`] for the default.
+    fn spawn(ocr_threads: Option<usize>) -> anyhow::Result<Self> {
+        let thread_pool = ocr_thread_pool(ocr_threads)
+            .build()
+            .map_err(|err| anyhow::anyhow!("Unable to start the OCR threads: {err}"))?;
+        log::info!("The OCR engine is running on {} threads.", thread_pool.current_num_threads());
+
+        let (jobs, received) = std::sync::mpsc::channel::<OcrJob>();
+        std::thread::spawn(move || {
+            thread_pool.install(move || {
+                let mut cached_engine = None;
+                for job in received {
+                    job(&mut cached_engine);
+                }
+            });
+        });
+        Ok(Self { ocr_threads, jobs })
+    }
+}
+
+/// The OCR worker, started by the first scan and replaced when a card hangs it.
+static OCR_WORKER: Mutex<Option<OcrWorker>> = Mutex::new(None);
+
+/// Hands a job to the OCR worker, starting a new worker if there is none, the last one stopped, or
+/// the configured thread count changed since it started.
+///
+/// # Arguments
+/// * `ocr_threads`: The number of threads to run the job on, or [`None`] for the default.
+/// * `job`: The job to run.
+fn submit_ocr_job(ocr_threads: Option<usize>, mut job: OcrJob) -> anyhow::Result<()> {
+    let mut worker = OCR_WORKER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(current_worker) = worker.as_ref().filter(|worker| worker.ocr_threads == ocr_threads) {
+        match current_worker.jobs.send(job) {
+            Ok(()) => return Ok(()),
+            Err(SendError(unsent_job)) => job = unsent_job,
+        }
+    }
+
+    let new_worker = OcrWorker::spawn(ocr_threads)?;
+    // A worker that was just started is always listening.
+    let _ = new_worker.jobs.send(job);
+    *worker = Some(new_worker);
+    Ok(())
+}
+
+/// Stops handing jobs to the OCR worker, which is stuck on an abandoned card, so the next scan
+/// starts a new one, which loads the models afresh. The stuck worker exits once it finishes the
+/// card.
+fn abandon_ocr_worker() {
+    OCR_WORKER.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+}
+
+/// News from the OCR worker about a card it's reading, by the card's position in the pass.
+enum CardProgress {
+    /// The worker started reading the card.
+    Started(usize),
+    /// The worker read the card's lines.
+    Read(usize, anyhow::Result<Vec<String>>),
+    /// The worker couldn't read any of the cards.
+    Failed(anyhow::Error),
+}
+
+/// Reads the text on some player cards on the OCR worker, in parallel, returning each card's lines
+/// in the order given. A card whose OCR outlasts the configured timeout is abandoned and reads as
+/// [`None`], and the worker is left to finish it while the next scan gets a new one.
+///
+/// # Arguments
+/// * `cards`: The cards to read, with their indices.
+/// * `secondary`: Whether to read the cards with the secondary recognition model.
+/// * `config`: A reference to the [`Config`] holding the models and OCR timeout.
+fn read_on_ocr_worker(
+    cards: &[(usize, &RgbImage)],
+    secondary: bool,
+    config: &Config
+) -> anyhow::Result<Vec<Option<Vec<String>>>> {
+    let images: Vec<NdTensor<f32, 3>> = cards.iter().map(|(_, image)| image_to_tensor(image)).collect();
+    let (progress, received) = std::sync::mpsc::channel();
+    let worker_config = config.clone();
+    submit_ocr_job(config.ocr_threads, Box::new(move |cached_engine| {
+        let config = worker_config;
+        let read = guard_panics(config.catch_scan_panics, || {
+            if !secondary {
+                detect_cards(cached_ocr_engine(cached_engine)?, images, &progress);
+            } else if let Some(secondary_engine) = create_secondary_ocr_engine(&config) {
+                detect_cards(&secondary_engine, images, &progress);
+            } else {
+                // Without a secondary model, the fallback pass reads nothing.
+                for position in 0..images.len() {
+                    let _ = progress.send(CardProgress::Read(position, Ok(Vec::new())));
+                }
+            }
+            Ok(())
+        });
+        if let Err(err) = read {
+            let _ = progress.send(CardProgress::Failed(err));
+        }
+    }))?;
+
+    let lines = receive_card_lines(cards.len(), received, config.ocr_timeout_secs.map(Duration::from_secs))?;
+    if lines.iter().any(Option::is_none) {
+        abandon_ocr_worker();
+    }
+    Ok(lines)
+}
+
+/// Detects the text on each card in parallel, reporting when each card is started and read.
+///
+/// # Arguments
+/// * `ocr_engine`: A reference to the OCR engine ([`OcrEngine`]) used for text detection.
+/// * `images`: The cards, as tensors from [`image_to_tensor`].
+/// * `progress`: Where to report each card's progress, by its position in `images`.
+fn detect_cards(ocr_engine: &OcrEngine, images: Vec<NdTensor<f32, 3>>, progress: &Sender<CardProgress>) {
+    images.into_par_iter().enumerate().for_each(|(position, image)| {
+        // The scan stops listening once it has every card it's still waiting for.
+        let _ = progress.send(CardProgress::Started(position));
+        let _ = progress.send(CardProgress::Read(position, detect_text(ocr_engine, image.view())));
+    });
+}
+
+/// Waits for the OCR worker to read some cards, returning each card's lines by its position. A card
+/// still unread once the timeout has elapsed since the worker started it is abandoned and reads as
+/// [`None`].
+///
+/// # Arguments
+/// * `card_count`: The number of cards being read.
+/// * `received`: Where the worker reports each card's progress.
+/// * `timeout`: How long the OCR of a card may take, or [`None`] to wait for as long as it takes.
+fn receive_card_lines(
+    card_count: usize,
+    received: Receiver<CardProgress>,
+    timeout: Option<Duration>
+) -> anyhow::Result<Vec<Option<Vec<String>>>> {
+    let mut lines: Vec<Option<Vec<String>>> = vec![None; card_count];
+    let mut abandoned = vec![false; card_count];
+    let mut deadlines: HashMap<usize, Instant> = HashMap::new();
+    let mut remaining = card_count;
+    while remaining > 0 {
+        let next_deadline = deadlines.values().min().copied();
+        let progress = match next_deadline {
+            Some(next_deadline) => match received.recv_timeout(next_deadline.saturating_duration_since(Instant::now())) {
+                Ok(progress) => progress,
+                Err(RecvTimeoutError::Timeout) => {
+                    let now = Instant::now();
+                    deadlines.retain(|position, deadline| {
+                        let expired = *deadline <= now;
+                        if expired {
+                            abandoned[*position] = true;
+                            remaining -= 1;
+                        }
+                        !expired
+                    });
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => bail!(OCR_WORKER_STOPPED),
+            },
+            None => received.recv().map_err(|_| anyhow::anyhow!(OCR_WORKER_STOPPED))?,
+        };
+
+        match progress {
+            CardProgress::Started(position) => {
+                if let Some(timeout) = timeout {
+                    deadlines.insert(position, Instant::now() + timeout);
+                }
+            }
+            CardProgress::Read(position, _) if abandoned[position] => {}
+            CardProgress::Read(position, card_lines) => {
+                deadlines.remove(&position);
+                lines[position] = Some(card_lines?);
+                remaining -= 1;
+            }
+            CardProgress::Failed(err) => return Err(err),
+        }
+    }
+    Ok(lines)
+}
+
+/// Estimates the confidence of a detection from its characters.
+///
+/// `ocrs` doesn't expose recognition scores, so this measures the share of non-space characters
+/// that could plausibly appear in a username. Misreads of stylized text and card graphics tend to
+/// produce runs of symbols, which score low.
+///
+/// # Arguments
+/// * `detection_text` - The detected text to estimate the confidence of.
+pub(crate) fn detection_confidence(detection_text: &str) -> f32 {
+    let characters: Vec<char> = detection_text.chars().filter(|c| !c.is_whitespace()).collect();
+    if characters.is_empty() {
+        return 0.0;
+    }
+
+    let plausible_characters = characters
+        .iter()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        .count();
+    plausible_characters as f32 / characters.len() as f32
+}
+
+/// Estimates the confidence of a whole card from all of its detections, weighting each by its
+/// length, so a card that reads as noise overall scores low even if it has a few clean characters.
+///
+/// # Arguments
+/// * `detections` - The raw detections read from the card.
+pub(crate) fn card_confidence(detections: &[String]) -> f32 {
+    let (plausible_characters, characters) = detections
+        .iter()
+        .map(|detection_text| {
+            let characters = detection_text.chars().filter(|c| !c.is_whitespace()).count() as f32;
+            (detection_confidence(detection_text) * characters, characters)
+        })
+        .fold((0.0, 0.0), |(plausible_sum, sum), (plausible, count)| (plausible_sum + plausible, sum + count));
+
+    if characters == 0.0 {
+        0.0
+    } else {
+        plausible_characters / characters
+    }
+}
+
+/// Whether any of a card's detections is confident enough to be worth matching.
+///
+/// # Arguments
+/// * `detections` - The raw detections read from the card.
+fn has_confident_detection(detections: &[String]) -> bool {
+    detections
+        .iter()
+        .any(|detection_text| {
+            detection_text.trim().chars().count() > 1
+                && detection_confidence(detection_text) >= MIN_DETECTION_CONFIDENCE
+        })
+}
+
+/// Normalizes a string by stripping the card noise around the name (see [`strip_card_noise`]) and
+/// the first matching prefix, converting it to lowercase, and removing spaces.
+///
+/// # Arguments
+/// * `input` - A reference to the input string that needs to be normalized.
+/// * `prefixes` - The prefixes (e.g. ranks) to strip from the start of the input.
+pub(crate) fn normalize(input: &str, prefixes: &[String]) -> String {
+    normalize_preserving_case(input, prefixes).to_lowercase()
+}
+
+/// Normalizes a string like [`normalize`], but keeps its case, for case-sensitive exact matching.
 ///
 /// # Arguments
 /// * `input` - A reference to the input string that needs to be normalized.
-fn normalize(input: &str) -> String {
-    let prefix = "General ";
-    let normalized_without_prefix = if input.starts_with(prefix) {
-        &input[prefix.len()..]
+/// * `prefixes` - The prefixes (e.g. ranks) to strip from the start of the input.
+pub(crate) fn normalize_preserving_case(input: &str, prefixes: &[String]) -> String {
+    let input = strip_card_noise(input);
+    let normalized_without_prefix = prefixes
+        .iter()
+        .find_map(|prefix| input.strip_prefix(prefix.as_str()))
+        .unwrap_or(input);
+
+    normalized_without_prefix.replace(" ", "")
+}
+
+/// Strips the noise the lobby prints after a player's name: the "(You)" marker on the player's own
+/// card, and a troop count set apart from the name by a space (e.g. "SomeName 12"). Digits that are
+/// part of the name itself (e.g. "SomeName12") are kept. The ranks before a name are configurable
+/// prefixes instead, stripped by [`normalize`].
+///
+/// # Arguments
+/// * `input` - A reference to the detected text.
+pub(crate) fn strip_card_noise(input: &str) -> &str {
+    let mut stripped = input.trim();
+    loop {
+        let before = stripped;
+
+        let marker_start = stripped.len().saturating_sub(OWN_CARD_MARKER.len());
+        if stripped.is_char_boundary(marker_start) && stripped[marker_start..].eq_ignore_ascii_case(OWN_CARD_MARKER) {
+            stripped = stripped[..marker_start].trim_end();
+        }
+
+        if let Some((name, count)) = stripped.rsplit_once(char::is_whitespace) {
+            if !name.trim().is_empty() && count.chars().all(|c| c.is_ascii_digit()) && !count.is_empty() {
+                stripped = name.trim_end();
+            }
+        }
+
+        if stripped == before {
+            return stripped;
+        }
+    }
+}
+
+/// Collapses every run of three or more of the same character to a single character, undoing the
+/// padding trolls add to evade matching ("mooooron" becomes "moron"). Doubled letters are kept, as
+/// they're common in real names.
+///
+/// # Arguments
+/// * `input` - A reference to the input string to collapse.
+pub(crate) fn collapse_repeated_chars(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut collapsed = String::with_capacity(input.len());
+    let mut run_start = 0;
+    while run_start < chars.len() {
+        let run_end = chars[run_start..]
+            .iter()
+            .position(|c| *c != chars[run_start])
+            .map_or(chars.len(), |run_length| run_start + run_length);
+        let run_length = if run_end - run_start >= 3 { 1 } else { run_end - run_start };
+        collapsed.extend(std::iter::repeat(chars[run_start]).take(run_length));
+        run_start = run_end;
+    }
+    collapsed
+}
+
+/// Prepares a detection or username for matching, applying the optional normalization steps
+/// enabled in the [`Config`].
+///
+/// # Arguments
+/// * `input` - A reference to the detection or username.
+/// * `config` - A reference to the [`Config`] holding the normalization options.
+fn prepare_for_matching(input: &str, config: &Config) -> String {
+    let collapsed = if config.collapse_repeated_chars {
+        collapse_repeated_chars(input)
     } else {
-        input
+        input.to_string()
+    };
+    strip_digits(&collapsed, config.strip_digits)
+}
+
+/// Strips digits from a detection or username, so numbers players decorate their names with don't
+/// count against a match.
+///
+/// # Arguments
+/// * `input` - A reference to the detection or username.
+/// * `digit_stripping` - Which digits to strip.
+pub(crate) fn strip_digits(input: &str, digit_stripping: DigitStripping) -> String {
+    let stripped: String = match digit_stripping {
+        DigitStripping::None => return input.to_string(),
+        DigitStripping::Trailing => input.trim_end().trim_end_matches(|c: char| c.is_ascii_digit()).to_string(),
+        DigitStripping::All => input.chars().filter(|c| !c.is_ascii_digit()).collect(),
     };
 
-    normalized_without_prefix.to_lowercase().replace(" ", "")
+    // An all-digit name is the number, so keep it rather than matching an empty string.
+    if stripped.trim().is_empty() {
+        input.to_string()
+    } else {
+        stripped
+    }
+}
+
+/// Scores how well a username matches its most similar substring of a card's text, by sliding a
+/// window the length of the username along the text. Both should be normalized the same way.
+///
+/// # Arguments
+/// * `card_text` - The normalized text of the whole card.
+/// * `username` - The normalized username to search for.
+pub(crate) fn partial_similarity(card_text: &str, username: &str) -> u8 {
+    let card_chars: Vec<char> = card_text.chars().collect();
+    let username_length = username.chars().count();
+    if username_length == 0 || card_chars.len() <= username_length {
+        return fuzzywuzzy::fuzz::ratio(card_text, username);
+    }
+
+    card_chars
+        .windows(username_length)
+        .map(|window| fuzzywuzzy::fuzz::ratio(&window.iter().collect::<String>(), username))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Scores an exact match: 100 if the normalized detection and username are identical, 0 otherwise.
+///
+/// # Arguments
+/// * `detection` - The normalized detection.
+/// * `username` - The username, normalized the same way.
+fn exact_similarity(detection: &str, username: &str) -> u8 {
+    if detection == username { 100 } else { 0 }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Creates an unfriendly result for a moron, for tests of the code consuming results.
+    pub(crate) fn scan_info(username: &str, similarity: u8) -> ScanInfo {
+        ScanInfo {
+            username: username.to_string(),
+            similarity,
+            reason: format!("{username} is a moron"),
+            ..ScanInfo::default()
+        }
+    }
+
+    /// Creates a blacklist of fuzzily matched morons with the default prefixes.
+    pub(crate) fn blacklist_of(usernames: &[&str]) -> blacklist::Blacklist {
+        blacklist::Blacklist {
+            morons: usernames
+                .iter()
+                .map(|username| Moron::new(username.to_string(), String::from("Moron")))
+                .collect(),
+            prefixes: blacklist::default_prefixes(),
+            clans: Vec::new(),
+        }
+    }
+
+    /// Matches the lines read from each card, in card order, as a scan would.
+    fn match_lines(cards: &[&[&str]], blacklist: &blacklist::Blacklist, config: &Config) -> ScanResult {
+        let player_cards = cards
+            .iter()
+            .map(|lines| (RgbImage::new(1, 1), lines.iter().map(|line| line.to_string()).collect()))
+            .collect();
+        let allowlist = allowlist::Allowlist { usernames: Vec::new() };
+        match_cards(player_cards, blacklist, &allowlist, ScanSource::File, config).unwrap()
+    }
+
+    #[test]
+    fn match_reports_the_card_it_was_read_from() {
+        let blacklist = blacklist_of(&["Some Moron"]);
+        let scan_result = match_lines(
+            &[&["Nice Player"], &[], &[], &[], &["General Some Moron 12"], &[]],
+            &blacklist,
+            &Config::default(),
+        );
+
+        let moron = scan_result.scans.iter().find(|scan| scan.similarity == 100).unwrap();
+        assert_eq!(moron.username, "Some Moron");
+        assert_eq!(moron.card_index, 4);
+        assert_eq!(scan_result.report.cards[4].raw_detections, vec![String::from("General Some Moron 12")]);
+    }
+
+    fn saved_pixel_dims(dpi: Option<u32>) -> Option<png::PixelDimensions> {
+        let png_path = crate::paths::tests::temp_path(&format!("dpi-{dpi:?}.png"));
+        save_png(&RgbaImage::new(4, 4), &png_path, dpi).unwrap();
+        let png_reader = png::Decoder::new(std::fs::File::open(&png_path).unwrap()).read_info().unwrap();
+        let pixel_dims = png_reader.info().pixel_dims;
+        std::fs::remove_file(&png_path).unwrap();
+        pixel_dims
+    }
+
+    #[test]
+    fn saved_png_records_the_configured_dpi() {
+        let pixel_dims = saved_pixel_dims(Some(96)).unwrap();
+        assert_eq!((pixel_dims.xppu, pixel_dims.yppu), (3780, 3780));
+        assert_eq!(pixel_dims.unit, png::Unit::Meter);
+    }
+
+    #[test]
+    fn saved_png_records_no_dpi_when_unset() {
+        assert!(saved_pixel_dims(None).is_none());
+    }
+
+    #[test]
+    #[ignore = "needs the downloaded OCR models"]
+    fn demo_scan_reads_the_sample_lobby() {
+        let scan_result = demo_scan(&Config::default()).unwrap();
+        let detection_count: usize = scan_result.report.cards
+            .iter()
+            .map(|card| card.raw_detections.len())
+            .sum();
+        assert!(detection_count > 0);
+    }
+
+    #[test]
+    fn scan_core_scores_with_the_configured_matcher() {
+        let blacklist = blacklist_of(&["Some Moron"]);
+        let similarity = |matcher: &str| {
+            let config = Config { matcher: matcher.to_string(), ..Config::default() };
+            match_lines(&[&["Moron Some"]], &blacklist, &config).scans[0].similarity
+        };
+
+        assert!(similarity("ratio") < 70);
+        assert_eq!(similarity("token-sort"), 100);
+    }
+
+    #[test]
+    fn collapse_repeated_chars_keeps_doubled_letters() {
+        assert_eq!(collapse_repeated_chars("mooooron"), "moron");
+        assert_eq!(collapse_repeated_chars("Bobbby"), "Boby");
+        assert_eq!(collapse_repeated_chars("Bobby"), "Bobby");
+    }
+
+    #[test]
+    fn padded_name_matches_when_collapsing_repeated_chars() {
+        let blacklist = blacklist_of(&["Some Moron"]);
+        let similarity = |collapse_repeated_chars: bool| {
+            let config = Config { collapse_repeated_chars, ..Config::default() };
+            match_lines(&[&["Some Mooooooron"]], &blacklist, &config).scans[0].similarity
+        };
+
+        assert!(similarity(false) < 100);
+        assert_eq!(similarity(true), 100);
+    }
+
+    #[test]
+    fn partial_similarity_finds_a_name_mid_string() {
+        assert_eq!(partial_similarity("xxsomemoron34troops", "somemoron"), 100);
+        assert!(partial_similarity("xxsomeotherguy34troops", "somemoron") < 70);
+    }
+
+    #[test]
+    fn name_embedded_in_merged_card_text_matches_with_partial_card_matching() {
+        let blacklist = blacklist_of(&["Some Moron"]);
+        let best_similarity = |partial_card_matching: bool| {
+            let config = Config { partial_card_matching, ..Config::default() };
+            match_lines(&[&["Lvl 12 Some Moron 34 troops"]], &blacklist, &config)
+                .scans
+                .iter()
+                .map(|scan| scan.similarity)
+                .max()
+                .unwrap()
+        };
+
+        assert!(best_similarity(false) < 70);
+        assert_eq!(best_similarity(true), 100);
+    }
+
+    struct FakeWindow {
+        minimized: bool,
+    }
+
+    impl MinimizableWindow for FakeWindow {
+        fn is_minimized(&self) -> bool {
+            self.minimized
+        }
+    }
+
+    #[test]
+    fn minimized_window_is_refused_with_a_specific_error() {
+        let err = ensure_not_minimized(&FakeWindow { minimized: true }).unwrap_err();
+        assert_eq!(err.to_string(), MINIMIZED_ERROR);
+        assert!(ensure_not_minimized(&FakeWindow { minimized: false }).is_ok());
+    }
+
+    #[test]
+    fn low_confidence_card_is_dropped_while_a_clean_card_is_kept() {
+        let blacklist = blacklist_of(&["Some Moron"]);
+        let config = Config { min_card_confidence: Some(0.6), ..Config::default() };
+        let scan_result = match_lines(
+            &[&["Some Moron", "~#%&*@!{}|<>^$~"], &["Some Moron"]],
+            &blacklist,
+            &config,
+        );
+
+        assert!(card_confidence(&[String::from("Some Moron"), String::from("~#%&*@!{}|<>^$~")]) < 0.6);
+        assert!(scan_result.scans.iter().all(|scan| scan.card_index == 1));
+        assert!(!scan_result.scans.is_empty());
+        assert!(scan_result.detected_names.iter().all(|detected_name| detected_name.card_index == 1));
+    }
+
+    #[test]
+    fn strip_digits_strips_as_configured() {
+        assert_eq!(strip_digits("moron99", DigitStripping::None), "moron99");
+        assert_eq!(strip_digits("moron99", DigitStripping::Trailing), "moron");
+        assert_eq!(strip_digits("m0ron99", DigitStripping::Trailing), "m0ron");
+        assert_eq!(strip_digits("m0ron99", DigitStripping::All), "mron");
+        // An all-digit name is kept whole.
+        assert_eq!(strip_digits("1337", DigitStripping::All), "1337");
+    }
+
+    #[test]
+    fn trailing_digits_dont_count_against_a_match_when_stripped() {
+        let blacklist = blacklist_of(&["Some Moron"]);
+        let similarity = |strip_digits: DigitStripping| {
+            let config = Config { strip_digits, ..Config::default() };
+            match_lines(&[&["Some Moron99"]], &blacklist, &config).scans[0].similarity
+        };
+
+        assert!(similarity(DigitStripping::None) < 100);
+        assert_eq!(similarity(DigitStripping::Trailing), 100);
+    }
+
+    #[test]
+    fn too_few_occupied_cards_warns_of_an_incomplete_scan() {
+        let blacklist = blacklist_of(&["Some Moron"]);
+        let config = Config { min_occupied_cards: Some(3), ..Config::default() };
+        let cards: [&[&str]; 6] = [&["Player One"], &["Player Two"], &["~#"], &[], &[], &[]];
+
+        let scan_result = match_lines(&cards, &blacklist, &config);
+        assert_eq!(scan_result.occupied_cards, 2);
+        assert_eq!(scan_result.warning.as_deref(), Some("Scan likely incomplete - only 2 cards read. Check the alignment."));
+
+        let scan_result = match_lines(&cards, &blacklist, &Config { min_occupied_cards: Some(2), ..config });
+        assert_eq!(scan_result.warning, None);
+    }
+
+    /// Creates a crop of a player card: light-grey text on the dark card background.
+    fn sample_card_crop() -> DynamicImage {
+        let mut card = RgbImage::from_pixel(40, 12, image::Rgb([30, 34, 44]));
+        for x in 4..36 {
+            for y in 4..8 {
+                card.put_pixel(x, y, image::Rgb([170, 170, 176]));
+            }
+        }
+        DynamicImage::ImageRgb8(card)
+    }
+
+    #[test]
+    fn preprocessing_with_every_step_off_leaves_the_card_alone() {
+        let card = sample_card_crop();
+        assert_eq!(preprocess_for_ocr(card.clone(), &OcrPreprocessing::default()), card);
+    }
+
+    #[test]
+    fn preprocessing_steps_change_the_card_as_configured() {
+        let preprocessing = OcrPreprocessing { grayscale: true, boost_contrast: true, upscale: true };
+        let before = sample_card_crop().to_luma8();
+        let after = preprocess_for_ocr(sample_card_crop(), &preprocessing);
+
+        assert_eq!((after.width(), after.height()), (80, 24));
+        assert!(after.to_rgb8().pixels().all(|pixel| pixel[0] == pixel[1] && pixel[1] == pixel[2]));
+        // The text stands out further from the background.
+        let after = after.to_luma8();
+        let gap_before = before.get_pixel(20, 5)[0] - before.get_pixel(1, 1)[0];
+        let gap_after = after.get_pixel(40, 11)[0] - after.get_pixel(2, 2)[0];
+        assert!(gap_after > gap_before, "{gap_before} -> {gap_after}");
+    }
+
+    #[test]
+    fn scanning_every_window_tags_and_combines_the_results() {
+        let blacklist = blacklist_of(&["Some Moron", "Other Moron"]);
+        let identity = |index| WindowIdentity { title: String::from("RISK"), app_name: String::from("RISK"), index };
+        let risk_windows = vec![
+            (FakeWindow { minimized: false }, identity(0)),
+            (FakeWindow { minimized: true }, identity(1)),
+            (FakeWindow { minimized: false }, identity(2)),
+        ];
+
+        let mut scanned_sources = Vec::new();
+        let scan_result = scan_windows(risk_windows, |_, _, source| {
+            scanned_sources.push(source);
+            let cards: &[&[&str]] = if source == ScanSource::Window(0) {
+                &[&["Some Moron"], &["Nice Player"]]
+            } else {
+                &[&["Nice Player"], &["Other Moron"]]
+            };
+            Ok(match_lines(cards, &blacklist, &Config::default()))
+        }).unwrap();
+
+        assert_eq!(scanned_sources, vec![ScanSource::Window(0), ScanSource::Window(2)]);
+        let flagged: Vec<(usize, usize, &str)> = scan_result.scans
+            .iter()
+            .filter(|scan| scan.similarity == 100)
+            .map(|scan| (scan.window_index, scan.card_index, scan.username.as_str()))
+            .collect();
+        assert_eq!(flagged, vec![(0, 0, "Some Moron"), (2, 1, "Other Moron")]);
+        assert_eq!(scan_result.occupied_cards, 4);
+        assert!(scan_result.report.cards.iter().map(|card| card.window_index).eq([0, 0, 2, 2]));
+    }
+
+    #[test]
+    fn scanning_only_minimized_windows_is_refused() {
+        let identity = WindowIdentity { title: String::from("RISK"), app_name: String::from("RISK"), index: 0 };
+        let err = scan_windows(vec![(FakeWindow { minimized: true }, identity)], |_, _, _| unreachable!()).unwrap_err();
+        assert_eq!(err.to_string(), MINIMIZED_ERROR);
+    }
+
+    #[test]
+    fn case_sensitive_exact_entry_tells_apart_names_differing_in_case() {
+        let mut blacklist = blacklist_of(&["IL"]);
+        blacklist.morons[0].mode = MatchMode::Exact;
+        blacklist.morons[0].case_sensitive = true;
+        let similarity = |detection: &str| match_lines(&[&[detection]], &blacklist, &Config::default()).scans[0].similarity;
+        assert_eq!(similarity("IL"), 100);
+        assert_eq!(similarity("Il"), 0);
+
+        blacklist.morons[0].case_sensitive = false;
+        let similarity = |detection: &str| match_lines(&[&[detection]], &blacklist, &Config::default()).scans[0].similarity;
+        assert_eq!(similarity("IL"), 100);
+        assert_eq!(similarity("Il"), 100);
+    }
+
+    #[test]
+    fn clan_tag_flags_any_player_wearing_it() {
+        let mut blacklist = blacklist_of(&[]);
+        blacklist.clans.push(blacklist::ClanEntry { tag: String::from("BAD"), reason: String::from("Griefers") });
+        let scan_result = match_lines(&[&["[BAD]SomeName"], &["[GOOD]Other"]], &blacklist, &Config::default());
+
+        assert_eq!(scan_result.scans.len(), 1);
+        let clan_match = &scan_result.scans[0];
+        assert_eq!(clan_match.clan.as_deref(), Some("BAD"));
+        assert_eq!(clan_match.username, "[BAD]SomeName");
+        assert_eq!(clan_match.reason, "Griefers");
+        assert_eq!(clan_match.similarity, 100);
+    }
+
+    /// A matcher that counts how often it scores.
+    struct CountingMatcher {
+        calls: std::cell::Cell<usize>,
+    }
+
+    impl Matcher for CountingMatcher {
+        fn score(&self, detection: &str, moron: &Moron) -> u8 {
+            self.calls.set(self.calls.get() + 1);
+            fuzzywuzzy::fuzz::ratio(detection, &moron.username)
+        }
+    }
+
+    #[test]
+    fn similarity_cache_serves_repeated_pairs() {
+        let matcher = CountingMatcher { calls: std::cell::Cell::new(0) };
+        let moron = Moron::new(String::from("moron"), String::new());
+        let mut similarity_cache = SimilarityCache::default();
+
+        let first = similarity_cache.score(&matcher, "moran", &moron);
+        let repeat = similarity_cache.score(&matcher, "moran", &moron);
+        assert_eq!(first, repeat);
+        assert_eq!(first, fuzzywuzzy::fuzz::ratio("moran", "moron"));
+        assert_eq!(matcher.calls.get(), 1);
+        assert_eq!((similarity_cache.hits, similarity_cache.misses), (1, 1));
+
+        similarity_cache.score(&matcher, "someone", &moron);
+        assert_eq!(matcher.calls.get(), 2);
+        assert_eq!((similarity_cache.hits, similarity_cache.misses), (1, 2));
+    }
+
+    #[test]
+    fn capture_error_under_wayland_explains_the_portal() {
+        let message = capture_error("no such window", true).to_string();
+        assert!(message.contains("no such window"));
+        assert!(message.contains("screen-capture portal"));
+        assert!(message.contains(paths::WAYLAND_CAPTURE_HELP_URL));
+
+        let message = capture_error("no such window", false).to_string();
+        assert_eq!(message, "Unable to capture RISK: no such window");
+    }
+
+    #[test]
+    fn ocr_runs_on_a_pool_of_the_configured_size() {
+        for ocr_threads in [3, 2] {
+            let (sender, received) = std::sync::mpsc::channel();
+            submit_ocr_job(Some(ocr_threads), Box::new(move |_| {
+                let _ = sender.send((rayon::current_thread_index(), rayon::current_num_threads()));
+            })).unwrap();
+
+            let (thread_index, thread_count) = received.recv().unwrap();
+            assert!(thread_index.is_some());
+            assert_eq!(thread_count, ocr_threads);
+        }
+    }
+
+    #[test]
+    fn fallback_pass_reads_only_the_cards_the_primary_pass_failed() {
+        let mut fallback_cards = Vec::new();
+        let player_cards = read_cards(
+            vec![RgbImage::new(1, 1); 6],
+            |cards| Ok(cards
+                .iter()
+                .map(|(i, _)| Some(if i % 2 == 0 { vec![format!("Player {i}")] } else { vec![String::from("~#%")] }))
+                .collect()),
+            |cards| {
+                fallback_cards.extend(cards.iter().map(|(i, _)| *i));
+                Ok(cards.iter().map(|(i, _)| Some(vec![format!("Fallback {i}")])).collect())
+            },
+        ).unwrap();
+
+        assert_eq!(fallback_cards, vec![1, 3, 5]);
+        assert_eq!(player_cards[0].1, vec![String::from("Player 0")]);
+        assert_eq!(player_cards[1].1, vec![String::from("~#%"), String::from("Fallback 1")]);
+    }
+
+    #[test]
+    fn panic_reading_a_card_becomes_a_reported_error() {
+        let read = |cards: &[(usize, &RgbImage)]| {
+            cards
+                .iter()
+                .map(|(i, _)| {
+                    if *i == 3 {
+                        panic!("injected panic");
+                    }
+                    Ok(Some(vec![format!("Player {i}")]))
+                })
+                .collect()
+        };
+        let read_fallback = |cards: &[(usize, &RgbImage)]| Ok(vec![None; cards.len()]);
+        let err = guard_panics(true, || read_cards(vec![RgbImage::new(1, 1); 6], read, read_fallback)).unwrap_err();
+
+        let message = err.to_string();
+        assert!(message.contains("injected panic"), "{message}");
+        assert!(message.contains("This is a bug"), "{message}");
+    }
+
+    #[test]
+    fn slow_card_is_abandoned_while_the_others_are_read() {
+        let started = Instant::now();
+        let (progress, received) = std::sync::mpsc::channel();
+        for position in 0..6 {
+            let progress = progress.clone();
+            std::thread::spawn(move || {
+                let _ = progress.send(CardProgress::Started(position));
+                if position == 2 {
+                    std::thread::sleep(Duration::from_secs(5));
+                }
+                let _ = progress.send(CardProgress::Read(position, Ok(vec![format!("Player {position}")])));
+            });
+        }
+        drop(progress);
+
+        let lines = receive_card_lines(6, received, Some(Duration::from_millis(200))).unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        for (position, lines) in lines.iter().enumerate() {
+            if position == 2 {
+                assert_eq!(lines, &None);
+            } else {
+                assert_eq!(lines, &Some(vec![format!("Player {position}")]));
+            }
+        }
+    }
+
+    #[test]
+    fn card_waiting_for_a_free_thread_is_not_timed_out() {
+        let (progress, received) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for position in 0..2 {
+                let _ = progress.send(CardProgress::Started(position));
+                std::thread::sleep(Duration::from_millis(150));
+                let _ = progress.send(CardProgress::Read(position, Ok(vec![format!("Player {position}")])));
+            }
+        });
+
+        let lines = receive_card_lines(2, received, Some(Duration::from_millis(250))).unwrap();
+
+        assert_eq!(lines, vec![Some(vec![String::from("Player 0")]), Some(vec![String::from("Player 1")])]);
+    }
+
+    #[test]
+    fn worker_failure_fails_the_read() {
+        let (progress, received) = std::sync::mpsc::channel();
+        progress.send(CardProgress::Started(0)).unwrap();
+        progress.send(CardProgress::Failed(anyhow::anyhow!("The detection model is missing"))).unwrap();
+
+        let err = receive_card_lines(6, received, Some(Duration::from_secs(5))).unwrap_err();
+        assert_eq!(err.to_string(), "The detection model is missing");
+
+        let (progress, received) = std::sync::mpsc::channel::<CardProgress>();
+        drop(progress);
+        let err = receive_card_lines(6, received, None).unwrap_err();
+        assert_eq!(err.to_string(), OCR_WORKER_STOPPED);
+    }
+
+    #[test]
+    fn missing_model_error_names_the_model() {
+        for (asset, file_name) in [(Asset::DetectionModel, "detection.rten"), (Asset::RecognitionModel, "recognition.rten")] {
+            let model_path = crate::paths::tests::temp_path(file_name);
+            let err = load_model(&asset.to_string(), &model_path).err().unwrap().to_string();
+            assert!(err.starts_with(&format!("The {} is missing", asset.to_string().to_lowercase())), "{err}");
+        }
+    }
+
+    #[test]
+    fn corrupt_model_error_names_the_model() {
+        let model_path = crate::paths::tests::temp_path("corrupt-recognition.rten");
+        std::fs::write(&model_path, b"not a model").unwrap();
+
+        let err = load_model(&Asset::RecognitionModel.to_string(), &model_path).err().unwrap().to_string();
+        std::fs::remove_file(&model_path).unwrap();
+        assert!(err.starts_with("The recognition model at"), "{err}");
+        assert!(err.contains("is corrupt"), "{err}");
+    }
+
+    #[test]
+    fn best_per_username_keeps_the_strongest_duplicate() {
+        let best = best_per_username(vec![scan_info("Moron", 70), scan_info("Moron", 90)]);
+        assert_eq!(best.len(), 1);
+        assert_eq!(best[0].similarity, 90);
+    }
+
+    #[test]
+    fn best_per_username_never_lets_a_friendly_result_hide_a_moron() {
+        let best = best_per_username(vec![
+            scan_info("Moron", 80),
+            ScanInfo { friendly: Some(String::from("Moran")), ..scan_info("Moron", 95) },
+        ]);
+        assert_eq!(best.len(), 2);
+        assert!(best.iter().any(|scan| scan.friendly.is_none() && scan.similarity == 80));
+        assert!(best.iter().any(|scan| scan.friendly.is_some() && scan.similarity == 95));
+    }
 }
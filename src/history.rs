@@ -0,0 +1,246 @@
+//! Module for recording flagged players across scans and aggregating them into a leaderboard.
+//!
+//! Every scan appends one [`HistoryEntry`] per flagged username to a JSON lines file in the app
+//! directory. Appending a line is cheap and never rewrites earlier entries, so the history grows
+//! without slowing down scanning. The [`leaderboard`] function aggregates the history into how often
+//! each blacklisted player has shown up, and [`diff_flagged`] compares two consecutive scans.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use crate::{history, paths};
+//!
+//! fn print_leaderboard() -> anyhow::Result<()> {
+//!     let history_path = paths::history_path()
+//!         .ok_or(anyhow::anyhow!("Unable to construct the history path."))?;
+//!     let entries = history::load(&history_path)?;
+//!     for row in history::leaderboard(&entries) {
+//!         println!("{}: flagged {} times", row.username, row.times_flagged);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+use crate::detector::{self, ScanInfo};
+
+/// A blacklisted player flagged by a scan.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct HistoryEntry {
+    /// When the scan ran, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The blacklisted username that was flagged.
+    pub username: String,
+    /// The highest similarity the username was flagged with in the scan.
+    pub similarity: u8,
+}
+
+/// How often a blacklisted player has been flagged across all scans.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LeaderboardRow {
+    /// The blacklisted username.
+    pub username: String,
+    /// The number of scans the username was flagged in.
+    pub times_flagged: usize,
+    /// When the username was last flagged, in seconds since the Unix epoch.
+    pub last_seen: u64,
+}
+
+/// The change in flagged players between two consecutive scans.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ScanDiff {
+    /// The players flagged now who weren't flagged in the previous scan.
+    pub joined: Vec<String>,
+    /// The players flagged in the previous scan who aren't flagged now.
+    pub left: Vec<String>,
+}
+
+/// Compares the flagged players of two consecutive scans. Names are compared in their normalized
+/// form, so the same player read with different spacing or case isn't reported as both joining and
+/// leaving.
+///
+/// # Arguments
+/// * `previous` - The results of the previous scan.
+/// * `current` - The results of the current scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+pub(crate) fn diff_flagged(previous: &[ScanInfo], current: &[ScanInfo], similarity_threshold: u8) -> ScanDiff {
+    let previous_flagged = flagged_names(previous, similarity_threshold);
+    let current_flagged = flagged_names(current, similarity_threshold);
+
+    ScanDiff {
+        joined: current_flagged
+            .iter()
+            .filter(|(name, _)| !previous_flagged.contains_key(*name))
+            .map(|(_, username)| username.to_string())
+            .collect(),
+        left: previous_flagged
+            .iter()
+            .filter(|(name, _)| !current_flagged.contains_key(*name))
+            .map(|(_, username)| username.to_string())
+            .collect(),
+    }
+}
+
+/// Gets the flagged, non-friendly usernames of a scan keyed by their normalized form.
+///
+/// # Arguments
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+fn flagged_names(scans: &[ScanInfo], similarity_threshold: u8) -> BTreeMap<String, &str> {
+    detector::similar_scans(scans, similarity_threshold)
+        .into_iter()
+        .filter(|scan| scan.friendly.is_none())
+        .map(|scan| (detector::normalize(&scan.username, &[]), scan.username.as_str()))
+        .collect()
+}
+
+/// Creates the history entries for a scan: one per flagged username, with its highest similarity.
+/// Friendly players and results below the threshold are not recorded.
+///
+/// # Arguments
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+/// * `timestamp` - When the scan ran, in seconds since the Unix epoch.
+pub(crate) fn entries_for_scan(scans: &[ScanInfo], similarity_threshold: u8, timestamp: u64) -> Vec<HistoryEntry> {
+    let mut best_similarities: HashMap<&str, u8> = HashMap::new();
+    for scan in scans.iter().filter(|s| s.friendly.is_none() && s.similarity >= similarity_threshold) {
+        let best_similarity = best_similarities.entry(&scan.username).or_insert(scan.similarity);
+        *best_similarity = (*best_similarity).max(scan.similarity);
+    }
+
+    let mut entries: Vec<HistoryEntry> = best_similarities
+        .into_iter()
+        .map(|(username, similarity)| HistoryEntry {
+            timestamp,
+            username: username.to_string(),
+            similarity,
+        })
+        .collect();
+    entries.sort_by(|a, b| a.username.cmp(&b.username));
+    entries
+}
+
+/// Appends history entries to the history file, one JSON object per line.
+///
+/// # Arguments
+/// * `history_path` - A reference to the [`PathBuf`] of the history file.
+/// * `entries` - The entries to append.
+pub(crate) fn append(history_path: &PathBuf, entries: &[HistoryEntry]) -> anyhow::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let mut lines = String::new();
+    for entry in entries {
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
+    }
+
+    let mut history_file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history_path)?;
+    history_file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Loads every entry from the history file. A missing file is an empty history, and lines that
+/// can't be parsed (e.g. a partially written last line) are skipped.
+///
+/// # Arguments
+/// * `history_path` - A reference to the [`PathBuf`] of the history file.
+pub(crate) fn load(history_path: &PathBuf) -> anyhow::Result<Vec<HistoryEntry>> {
+    if !history_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let history_file = std::fs::File::open(history_path)?;
+    let mut entries = Vec::new();
+    for line in BufReader::new(history_file).lines() {
+        match serde_json::from_str::<HistoryEntry>(&line?) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => log::warn!("Skipping an unreadable history entry: {err}"),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Aggregates history entries into a leaderboard of how often each username was flagged, most
+/// flagged first. Ties are broken by the most recently seen.
+///
+/// # Arguments
+/// * `entries` - The history entries to aggregate.
+pub(crate) fn leaderboard(entries: &[HistoryEntry]) -> Vec<LeaderboardRow> {
+    let mut rows: HashMap<&str, LeaderboardRow> = HashMap::new();
+    for entry in entries {
+        let row = rows.entry(&entry.username).or_insert_with(|| LeaderboardRow {
+            username: entry.username.clone(),
+            times_flagged: 0,
+            last_seen: entry.timestamp,
+        });
+        row.times_flagged += 1;
+        row.last_seen = row.last_seen.max(entry.timestamp);
+    }
+
+    let mut rows: Vec<LeaderboardRow> = rows.into_values().collect();
+    rows.sort_by(|a, b| {
+        b.times_flagged
+            .cmp(&a.times_flagged)
+            .then(b.last_seen.cmp(&a.last_seen))
+            .then(a.username.cmp(&b.username))
+    });
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::tests::scan_info;
+
+    fn entry(username: &str, timestamp: u64) -> HistoryEntry {
+        HistoryEntry { timestamp, username: username.to_string(), similarity: 90 }
+    }
+
+    #[test]
+    fn diff_classifies_joined_and_left_players() {
+        let previous = vec![scan_info("Stayed", 90), scan_info("Left", 90), scan_info("Below Threshold", 50)];
+        let current = vec![scan_info("stayed ", 85), scan_info("Joined", 95), scan_info("Below Threshold", 95)];
+
+        assert_eq!(diff_flagged(&previous, &current, 70), ScanDiff {
+            joined: vec![String::from("Below Threshold"), String::from("Joined")],
+            left: vec![String::from("Left")],
+        });
+    }
+
+    #[test]
+    fn diff_ignores_friendly_players() {
+        let friendly = ScanInfo { friendly: Some(String::from("Friend")), ..scan_info("Friend", 100) };
+        assert_eq!(diff_flagged(&[], &[friendly], 70), ScanDiff::default());
+    }
+
+    #[test]
+    fn leaderboard_counts_flags_and_tracks_last_seen() {
+        let entries = vec![
+            entry("Frequent", 100),
+            entry("Rare", 150),
+            entry("Frequent", 300),
+            entry("Frequent", 200),
+            entry("Tied", 400),
+        ];
+
+        assert_eq!(leaderboard(&entries), vec![
+            LeaderboardRow { username: String::from("Frequent"), times_flagged: 3, last_seen: 300 },
+            LeaderboardRow { username: String::from("Tied"), times_flagged: 1, last_seen: 400 },
+            LeaderboardRow { username: String::from("Rare"), times_flagged: 1, last_seen: 150 },
+        ]);
+    }
+
+    #[test]
+    fn leaderboard_of_no_history_is_empty() {
+        assert!(leaderboard(&[]).is_empty());
+    }
+}
@@ -0,0 +1,54 @@
+//! Module for the global hotkey that scans the RISK lobby without focusing the app.
+//!
+//! The hotkey is registered with the OS, so it's seen even while RISK is full-screen. Presses are
+//! queued by `global-hotkey` and drained once a tick, like watch mode polls the window list. Some
+//! platforms restrict global hotkeys: macOS needs the app to be granted accessibility permissions,
+//! and Wayland sessions don't support them at all.
+
+use std::time::Duration;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use global_hotkey::hotkey::HotKey;
+
+/// How often presses of the hotkey are checked for.
+pub(crate) const HOTKEY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The hotkey that scans, registered for as long as it's kept.
+pub(crate) struct ScanHotkey {
+    /// The manager the hotkey is registered with. It's only kept so the hotkey stays registered.
+    _manager: GlobalHotKeyManager,
+    /// The ID of the hotkey, to tell its presses apart.
+    id: u32,
+}
+
+impl ScanHotkey {
+    /// Registers the hotkey with the OS.
+    ///
+    /// # Arguments
+    /// * `binding` - The key combination, e.g. "ctrl+shift+S".
+    pub(crate) fn register(binding: &str) -> anyhow::Result<Self> {
+        let hotkey: HotKey = binding
+            .parse()
+            .map_err(|err| anyhow::anyhow!("'{binding}' isn't a valid hotkey: {err}"))?;
+        let manager = GlobalHotKeyManager::new()
+            .map_err(|err| anyhow::anyhow!("Unable to use global hotkeys: {err}"))?;
+        manager
+            .register(hotkey)
+            .map_err(|err| anyhow::anyhow!("Unable to register the {binding} hotkey: {err}"))?;
+
+        Ok(Self {
+            _manager: manager,
+            id: hotkey.id(),
+        })
+    }
+
+    /// Drains the queued hotkey events, returning whether the hotkey was pressed since the last check.
+    pub(crate) fn was_pressed(&self) -> bool {
+        let mut pressed = false;
+        while let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+            if event.id == self.id && event.state == HotKeyState::Pressed {
+                pressed = true;
+            }
+        }
+        pressed
+    }
+}
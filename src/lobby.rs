@@ -0,0 +1,172 @@
+//! Module for telling whether the RISK window is showing the lobby, so scans can wait out loading
+//! screens instead of reading a spinner.
+//!
+//! The [`CaptureLobbyClassifier`] captures the RISK window and measures the contrast of the
+//! player-list region: populated player cards are full of text and portraits, whereas loading
+//! screens are mostly flat. [`wait_for_lobby`] polls any [`LobbyClassifier`] until it reports the
+//! lobby or a timeout elapses.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use std::time::Duration;
+//! use crate::lobby::{self, CaptureLobbyClassifier};
+//!
+//! fn wait(window: &xcap::Window, config: &Config) -> anyhow::Result<()> {
+//!     let mut classifier = CaptureLobbyClassifier::new(window, config);
+//!     if !lobby::wait_for_lobby(&mut classifier, Duration::from_secs(10), Duration::from_millis(500))? {
+//!         anyhow::bail!("RISK is still loading.");
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+use image::RgbaImage;
+use crate::config::Config;
+use crate::crop::{self, CropRect};
+use crate::detector;
+
+/// The luminance standard deviation above which the player-list region is considered populated.
+const LOBBY_CONTRAST_THRESHOLD: f64 = 20.0;
+
+/// Whether the RISK window is showing the lobby.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LobbyState {
+    /// The lobby and its player cards are on screen.
+    InLobby,
+    /// Something else (e.g. a loading screen) is on screen.
+    NotInLobby,
+}
+
+/// Classifies what the RISK window is currently showing.
+pub(crate) trait LobbyClassifier {
+    /// Classifies what the RISK window is currently showing.
+    fn classify(&mut self) -> anyhow::Result<LobbyState>;
+}
+
+/// Classifies the lobby state from a fresh capture of the RISK window.
+pub(crate) struct CaptureLobbyClassifier<'a> {
+    /// The RISK window to capture.
+    window: &'a xcap::Window,
+    /// The config holding the player-list region.
+    config: &'a Config,
+}
+
+impl<'a> CaptureLobbyClassifier<'a> {
+    /// Creates a new [`CaptureLobbyClassifier`].
+    ///
+    /// # Arguments
+    /// * `window` - The RISK window to capture.
+    /// * `config` - The config holding the player-list region.
+    pub(crate) fn new(window: &'a xcap::Window, config: &'a Config) -> Self {
+        Self { window, config }
+    }
+}
+
+impl LobbyClassifier for CaptureLobbyClassifier<'_> {
+    fn classify(&mut self) -> anyhow::Result<LobbyState> {
+        let capture = detector::capture_window(self.window)?;
+        let content = crop::content_rect(&capture);
+        let player_list_rect = detector::resolve_player_list_rect(capture.width(), capture.height(), &content, self.config);
+        Ok(classify_capture(&capture, &player_list_rect))
+    }
+}
+
+/// Classifies a capture as showing the lobby if its player-list region has enough contrast.
+///
+/// # Arguments
+/// * `capture` - The capture of the RISK window.
+/// * `player_list_rect` - The player-list region of the capture.
+pub(crate) fn classify_capture(capture: &RgbaImage, player_list_rect: &CropRect) -> LobbyState {
+    // Sample a sparse grid; the contrast of a whole lobby doesn't hinge on individual pixels.
+    let mut luminances: Vec<f64> = Vec::new();
+    for y in (player_list_rect.y..player_list_rect.y + player_list_rect.height).step_by(4) {
+        for x in (player_list_rect.x..player_list_rect.x + player_list_rect.width).step_by(4) {
+            if let Some(pixel) = capture.get_pixel_checked(x, y) {
+                let [r, g, b, _] = pixel.0;
+                luminances.push(0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64);
+            }
+        }
+    }
+
+    if luminances.is_empty() {
+        return LobbyState::NotInLobby;
+    }
+
+    let mean = luminances.iter().sum::<f64>() / luminances.len() as f64;
+    let variance = luminances.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / luminances.len() as f64;
+
+    if variance.sqrt() > LOBBY_CONTRAST_THRESHOLD {
+        LobbyState::InLobby
+    } else {
+        LobbyState::NotInLobby
+    }
+}
+
+/// Polls the classifier until it reports the lobby or the timeout elapses. Returns whether the
+/// lobby was reached.
+///
+/// # Arguments
+/// * `classifier` - The classifier to poll.
+/// * `timeout` - How long to wait for the lobby before giving up.
+/// * `poll_interval` - How long to wait between polls.
+pub(crate) fn wait_for_lobby(
+    classifier: &mut impl LobbyClassifier,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> anyhow::Result<bool> {
+    let started = Instant::now();
+    loop {
+        if classifier.classify()? == LobbyState::InLobby {
+            return Ok(true);
+        }
+
+        if started.elapsed() + poll_interval > timeout {
+            return Ok(false);
+        }
+
+        log::debug!("The RISK lobby isn't on screen yet. Waiting {poll_interval:?}.");
+        std::thread::sleep(poll_interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A classifier that reports the lobby from the given poll onwards, counting its polls.
+    struct FlippingClassifier {
+        ready_on_poll: usize,
+        polls: usize,
+    }
+
+    impl LobbyClassifier for FlippingClassifier {
+        fn classify(&mut self) -> anyhow::Result<LobbyState> {
+            self.polls += 1;
+            Ok(if self.polls >= self.ready_on_poll { LobbyState::InLobby } else { LobbyState::NotInLobby })
+        }
+    }
+
+    #[test]
+    fn waits_until_the_lobby_is_ready() {
+        let mut classifier = FlippingClassifier { ready_on_poll: 2, polls: 0 };
+        let reached = wait_for_lobby(&mut classifier, Duration::from_secs(5), Duration::from_millis(1)).unwrap();
+        assert!(reached);
+        assert_eq!(classifier.polls, 2);
+    }
+
+    #[test]
+    fn gives_up_once_the_timeout_elapses() {
+        let mut classifier = FlippingClassifier { ready_on_poll: usize::MAX, polls: 0 };
+        let reached = wait_for_lobby(&mut classifier, Duration::from_millis(20), Duration::from_millis(5)).unwrap();
+        assert!(!reached);
+    }
+
+    #[test]
+    fn classifies_a_flat_capture_as_not_in_lobby() {
+        let capture = RgbaImage::from_pixel(64, 64, image::Rgba([20, 20, 20, 255]));
+        let rect = CropRect { x: 0, y: 0, width: 64, height: 64 };
+        assert_eq!(classify_capture(&capture, &rect), LobbyState::NotInLobby);
+    }
+}
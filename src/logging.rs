@@ -0,0 +1,186 @@
+//! This module provides the application logger and the in-memory buffer backing the log panel.
+//!
+//! Every record passed to the [`log`] macros is written to the log file in the app directory and
+//! pushed into a bounded [`RingBuffer`], so the GUI can tail the most recent lines without
+//! re-reading the file. Only [`log`] records are captured, not events emitted through `tracing`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use crate::{logging, paths};
+//!
+//! fn initialize_logging() -> anyhow::Result<()> {
+//!     logging::init(paths::log_path())?;
+//!     log::info!("Logging initialised.");
+//!     // The most recent records, oldest first.
+//!     let lines = logging::recent_records();
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// The number of log records kept in memory for the log panel.
+pub(crate) const LOG_BUFFER_CAPACITY: usize = 200;
+
+/// How often the log panel is redrawn to show new records.
+pub(crate) const LOG_PANEL_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The global logger, set once by [`init`].
+static LOGGER: OnceLock<BlitzLogger> = OnceLock::new();
+
+/// A fixed-capacity queue that evicts its oldest item once full.
+#[derive(Debug)]
+pub(crate) struct RingBuffer<T> {
+    /// The buffered items, oldest first.
+    items: VecDeque<T>,
+    /// The maximum number of items held before the oldest is evicted.
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// Creates a new, empty [`RingBuffer`] holding at most `capacity` items.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of items to hold. A capacity of zero holds nothing.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Pushes an item onto the end of the buffer, evicting the oldest item if the buffer is full.
+    ///
+    /// # Arguments
+    /// * `item` - The item to push.
+    pub(crate) fn push(&mut self, item: T) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.items.len() == self.capacity {
+            self.items.pop_front();
+        }
+
+        self.items.push_back(item);
+    }
+
+    /// Iterates over the buffered items, oldest first.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+}
+
+/// Logger that writes every record to the log file and the in-memory [`RingBuffer`].
+struct BlitzLogger {
+    /// The most recent formatted records.
+    buffer: Mutex<RingBuffer<String>>,
+    /// The log file, if it could be opened.
+    file: Mutex<Option<File>>,
+}
+
+impl Log for BlitzLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!("[{}] {}", record.level(), record.args());
+
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                // There's nowhere left to report a failure to write the log, so ignore it.
+                let _ = writeln!(file, "{line}");
+            }
+        }
+
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.push(line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Installs the application logger. Records are appended to the file at `log_path` (if any) and
+/// kept in memory for [`recent_records`].
+///
+/// # Arguments
+/// * `log_path` - The [`PathBuf`] of the log file, or [`None`] to only log in memory.
+pub(crate) fn init(log_path: Option<PathBuf>) -> anyhow::Result<()> {
+    let file = match log_path {
+        Some(log_path) => Some(
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(log_path)?
+        ),
+        None => None,
+    };
+
+    let logger = LOGGER.get_or_init(|| BlitzLogger {
+        buffer: Mutex::new(RingBuffer::new(LOG_BUFFER_CAPACITY)),
+        file: Mutex::new(file),
+    });
+
+    log::set_logger(logger).map_err(|err| anyhow::anyhow!("Unable to install the logger: {err}"))?;
+    log::set_max_level(LevelFilter::Debug);
+    Ok(())
+}
+
+/// Gets a copy of the most recent log records, oldest first.
+pub(crate) fn recent_records() -> Vec<String> {
+    match LOGGER.get() {
+        Some(logger) => match logger.buffer.lock() {
+            Ok(buffer) => buffer.iter().cloned().collect(),
+            Err(_) => Vec::new(),
+        },
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_buffer_evicts_oldest_past_capacity() {
+        let mut buffer = RingBuffer::new(3);
+        for item in 1..=4 {
+            buffer.push(item);
+        }
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn ring_buffer_keeps_everything_below_capacity() {
+        let mut buffer = RingBuffer::new(3);
+        buffer.push("first");
+        buffer.push("second");
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn ring_buffer_with_zero_capacity_holds_nothing() {
+        let mut buffer = RingBuffer::new(0);
+        buffer.push(1);
+        assert_eq!(buffer.iter().count(), 0);
+    }
+}
@@ -3,10 +3,29 @@ use iced::{
     window, Application, Settings, Size,
 };
 
+mod alerts;
+mod allowlist;
 mod app;
+mod bench;
 mod blacklist;
+mod config;
+mod crop;
+mod danger;
 mod detector;
+mod history;
+mod hotkey;
+mod lobby;
+mod logging;
+mod matcher;
 mod paths;
+mod report;
+mod scan_log;
+mod selector;
+mod session;
+mod telemetry;
+mod verify;
+mod watch;
+mod windows;
 
 #[tokio::main]
 async fn main() {
@@ -17,33 +36,69 @@ async fn main() {
         return
     }
 
+    if let Err(err) = logging::init(paths::log_path()) {
+        display_error(&err.to_string());
+        return
+    }
+
     if let Err(err) = paths::create_init_file_if_not_exists() {
         display_error(&err.to_string());
         return
     }
 
+    if let Err(err) = paths::create_config_file_if_not_exists() {
+        display_error(&err.to_string());
+        return
+    }
+
     if let Err(err) = paths::create_blacklist_file_if_not_exists() {
         display_error(&err.to_string());
         return
     }
 
-    if let Err(err) = paths::download_rten_models().await {
+    if let Err(err) = paths::create_allowlist_file_if_not_exists() {
         display_error(&err.to_string());
         return
     }
 
-    if let Err(err) = paths::download_banner_file().await {
+    if let Err(err) = paths::download_rten_models().await {
         display_error(&err.to_string());
         return
     }
 
+    // Run a headless subcommand instead of the GUI if one was given.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(subcommand) = args.first() {
+        if let Err(err) = run_subcommand(subcommand, &args[1..]) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return
+    }
+
+    // Only the default banner is downloaded; a local or disabled banner never touches the network.
+    let banner = paths::config_path()
+        .and_then(|config_path| config::Config::load(&config_path).ok())
+        .map(|config| config.banner)
+        .unwrap_or_default();
+    if banner == config::Banner::Default {
+        if let Err(err) = paths::download_banner_file().await {
+            display_error(&err.to_string());
+            return
+        }
+    }
+
     let settings: Settings<()> = Settings {
         window: window::Settings {
             size: Size {
                 width: 400f32,
                 height: 380f32,
             },
-            resizable: false,
+            min_size: Some(Size {
+                width: 400f32,
+                height: 380f32,
+            }),
+            resizable: true,
             decorations: true,
             ..Default::default()
         },
@@ -53,6 +108,22 @@ async fn main() {
     app::BlitzApp::run(settings).unwrap()
 }
 
+/// Runs a headless subcommand.
+///
+/// # Arguments
+/// * `subcommand` - The name of the subcommand to run.
+/// * `args` - The arguments that follow the subcommand name.
+fn run_subcommand(subcommand: &str, args: &[String]) -> anyhow::Result<()> {
+    let config_path = paths::config_path().ok_or(anyhow::anyhow!("Unable to construct the config path."))?;
+    let config = config::Config::load(&config_path).map_err(|err| anyhow::anyhow!("Config Error: {err}"))?;
+
+    match subcommand {
+        "bench" => bench::run(args, &config),
+        "verify-blacklist" => verify::run(args, &config),
+        other => anyhow::bail!("Unknown subcommand '{other}'. Available subcommands: bench, verify-blacklist"),
+    }
+}
+
 /// Displays an error message in a GUI pop-up for an error propogated before
 /// initialisation of the main application.
 ///
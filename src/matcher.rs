@@ -0,0 +1,301 @@
+//! Module for the strategies that score how closely a detection matches a blacklisted username.
+//!
+//! Every strategy implements [`Matcher`], and the scan uses whichever one is named in the config.
+//! The built-in strategies are:
+//! - `ratio`: the Levenshtein-based [`fuzzywuzzy::fuzz::ratio`] (the default).
+//! - `jaro-winkler`: the Jaro-Winkler similarity, which favours names that share a prefix.
+//! - `token-sort`: [`fuzzywuzzy::fuzz::token_sort_ratio`], which ignores the order of words.
+//! - `partial-ratio`: the best ratio of the shorter name against any same-length slice of the
+//!   longer, so a name read with extra text around it still scores highly.
+//! - `token-set`: [`fuzzywuzzy::fuzz::token_set_ratio`], which ignores extra words on either side.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use crate::matcher;
+//!
+//! fn score(moron: &Moron) -> anyhow::Result<u8> {
+//!     let matcher = matcher::matcher_by_name("jaro-winkler", &blacklist::default_prefixes())?;
+//!     Ok(matcher.score("General Moron", moron))
+//! }
+//! ```
+
+use crate::blacklist::Moron;
+use crate::detector;
+
+/// The name of the matcher used when none is configured.
+pub(crate) const DEFAULT_MATCHER: &str = "ratio";
+
+/// The names of the built-in matchers.
+pub(crate) const MATCHER_NAMES: [&str; 5] = ["ratio", "jaro-winkler", "token-sort", "partial-ratio", "token-set"];
+
+/// A strategy for scoring how closely a detection matches a blacklisted username.
+pub(crate) trait Matcher {
+    /// Scores the similarity between a detection and a moron's username, as a percentage.
+    ///
+    /// # Arguments
+    /// * `detection` - The text detected on a player card, as read.
+    /// * `moron` - The blacklisted moron to compare against.
+    fn score(&self, detection: &str, moron: &Moron) -> u8;
+}
+
+/// Scores with [`fuzzywuzzy::fuzz::ratio`] over the normalized names.
+pub(crate) struct RatioMatcher {
+    /// The prefixes stripped before comparing.
+    prefixes: Vec<String>,
+}
+
+/// Scores with the Jaro-Winkler similarity over the normalized names.
+pub(crate) struct JaroWinklerMatcher {
+    /// The prefixes stripped before comparing.
+    prefixes: Vec<String>,
+}
+
+/// Scores with [`fuzzywuzzy::fuzz::token_sort_ratio`], keeping the spaces between words.
+pub(crate) struct TokenSortMatcher {
+    /// The prefixes stripped before comparing.
+    prefixes: Vec<String>,
+}
+
+/// Scores the shorter of the normalized names against every same-length slice of the longer, keeping
+/// the best [`fuzzywuzzy::fuzz::ratio`].
+pub(crate) struct PartialRatioMatcher {
+    /// The prefixes stripped before comparing.
+    prefixes: Vec<String>,
+}
+
+/// Scores with [`fuzzywuzzy::fuzz::token_set_ratio`], keeping the spaces between words.
+pub(crate) struct TokenSetMatcher {
+    /// The prefixes stripped before comparing.
+    prefixes: Vec<String>,
+}
+
+impl Matcher for RatioMatcher {
+    fn score(&self, detection: &str, moron: &Moron) -> u8 {
+        fuzzywuzzy::fuzz::ratio(
+            &detector::normalize(detection, &self.prefixes),
+            &detector::normalize(&moron.username, &self.prefixes),
+        )
+    }
+}
+
+impl Matcher for JaroWinklerMatcher {
+    fn score(&self, detection: &str, moron: &Moron) -> u8 {
+        let similarity = strsim::jaro_winkler(
+            &detector::normalize(detection, &self.prefixes),
+            &detector::normalize(&moron.username, &self.prefixes),
+        );
+        (similarity * 100.0).round() as u8
+    }
+}
+
+impl Matcher for TokenSortMatcher {
+    fn score(&self, detection: &str, moron: &Moron) -> u8 {
+        // Token sort needs the words, so only the prefix is stripped here.
+        fuzzywuzzy::fuzz::token_sort_ratio(
+            &strip_prefix(detection, &self.prefixes),
+            &strip_prefix(&moron.username, &self.prefixes),
+            true,
+            true
+        )
+    }
+}
+
+impl Matcher for PartialRatioMatcher {
+    fn score(&self, detection: &str, moron: &Moron) -> u8 {
+        // fuzzywuzzy's partial ratio slices by byte, which panics on non-ASCII reads, so this slides
+        // over characters instead.
+        let detection = detector::normalize(detection, &self.prefixes);
+        let username = detector::normalize(&moron.username, &self.prefixes);
+        if detection.chars().count() >= username.chars().count() {
+            detector::partial_similarity(&detection, &username)
+        } else {
+            detector::partial_similarity(&username, &detection)
+        }
+    }
+}
+
+impl Matcher for TokenSetMatcher {
+    fn score(&self, detection: &str, moron: &Moron) -> u8 {
+        // Token set needs the words, so only the prefix is stripped here.
+        fuzzywuzzy::fuzz::token_set_ratio(
+            &strip_prefix(detection, &self.prefixes),
+            &strip_prefix(&moron.username, &self.prefixes),
+            true,
+            true
+        )
+    }
+}
+
+/// Strips the card noise and first matching prefix from a name and lowercases it, keeping its words
+/// intact.
+///
+/// # Arguments
+/// * `input` - The name to strip.
+/// * `prefixes` - The prefixes (e.g. ranks) to strip.
+fn strip_prefix(input: &str, prefixes: &[String]) -> String {
+    let input = detector::strip_card_noise(input);
+    let without_prefix = prefixes
+        .iter()
+        .find_map(|prefix| input.strip_prefix(prefix.as_str()))
+        .unwrap_or(input);
+    without_prefix.to_lowercase()
+}
+
+/// Scores with another matcher, but also with [`fuzzywuzzy::fuzz::ratio`] over the names as read,
+/// and keeps the higher score. This recovers names that normalization over-collapses.
+pub(crate) struct RawFormMatcher {
+    /// The matcher scoring the normalized names.
+    inner: Box<dyn Matcher>,
+}
+
+impl RawFormMatcher {
+    /// Creates a new [`RawFormMatcher`].
+    ///
+    /// # Arguments
+    /// * `inner` - The matcher scoring the normalized names.
+    pub(crate) fn new(inner: Box<dyn Matcher>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Matcher for RawFormMatcher {
+    fn score(&self, detection: &str, moron: &Moron) -> u8 {
+        let raw_similarity = fuzzywuzzy::fuzz::ratio(detection.trim(), moron.username.trim());
+        self.inner.score(detection, moron).max(raw_similarity)
+    }
+}
+
+/// Scores with another matcher after folding the glyphs OCR commonly confuses (e.g. "rn" and "m", or
+/// "0" and "O") to the same form on both sides, so misreads of those glyphs cost nothing.
+pub(crate) struct ConfusionMatcher {
+    /// The matcher scoring the folded names.
+    inner: Box<dyn Matcher>,
+    /// The prefixes stripped before folding, as folding changes their case.
+    prefixes: Vec<String>,
+}
+
+impl ConfusionMatcher {
+    /// Creates a new [`ConfusionMatcher`].
+    ///
+    /// # Arguments
+    /// * `inner` - The matcher scoring the folded names.
+    /// * `prefixes` - The prefixes (e.g. ranks) stripped before folding.
+    pub(crate) fn new(inner: Box<dyn Matcher>, prefixes: &[String]) -> Self {
+        Self { inner, prefixes: prefixes.to_vec() }
+    }
+
+    /// Strips the prefix from a name, then folds it.
+    ///
+    /// # Arguments
+    /// * `input` - The name to fold.
+    fn fold(&self, input: &str) -> String {
+        fold_ocr_confusions(&detector::normalize_preserving_case(input, &self.prefixes))
+    }
+}
+
+impl Matcher for ConfusionMatcher {
+    fn score(&self, detection: &str, moron: &Moron) -> u8 {
+        let folded_moron = Moron {
+            username: self.fold(&moron.username),
+            ..moron.clone()
+        };
+        self.inner.score(&self.fold(detection), &folded_moron)
+    }
+}
+
+/// Folds the glyphs OCR commonly confuses to a single form: "1", "I", and "|" become "l", "0" and
+/// "O" become "o", "5" and "S" become "s", "rn" becomes "m", and "cl" becomes "d". The result is
+/// lowercase.
+///
+/// # Arguments
+/// * `input` - The text to fold.
+pub(crate) fn fold_ocr_confusions(input: &str) -> String {
+    let folded: String = input
+        .chars()
+        .map(|c| match c {
+            '1' | 'I' | '|' => 'l',
+            '0' | 'O' => 'o',
+            '5' | 'S' => 's',
+            c => c,
+        })
+        .collect::<String>()
+        .to_lowercase();
+    folded.replace("rn", "m").replace("cl", "d")
+}
+
+/// Creates the built-in matcher with the given name.
+///
+/// # Arguments
+/// * `name` - The name of the matcher, as written in the config.
+/// * `prefixes` - The prefixes (e.g. ranks) the matcher strips before comparing.
+pub(crate) fn matcher_by_name(name: &str, prefixes: &[String]) -> anyhow::Result<Box<dyn Matcher>> {
+    let prefixes = prefixes.to_vec();
+    match name {
+        "ratio" => Ok(Box::new(RatioMatcher { prefixes })),
+        "jaro-winkler" => Ok(Box::new(JaroWinklerMatcher { prefixes })),
+        "token-sort" => Ok(Box::new(TokenSortMatcher { prefixes })),
+        "partial-ratio" => Ok(Box::new(PartialRatioMatcher { prefixes })),
+        "token-set" => Ok(Box::new(TokenSetMatcher { prefixes })),
+        other => anyhow::bail!("Unknown matcher '{other}'. Available matchers: {}", MATCHER_NAMES.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blacklist::default_prefixes;
+
+    #[test]
+    fn every_listed_matcher_can_be_created_by_name() {
+        let moron = Moron::new(String::from("Some Moron"), String::new());
+        for name in MATCHER_NAMES {
+            let matcher = matcher_by_name(name, &default_prefixes()).unwrap();
+            assert_eq!(matcher.score("General Some Moron", &moron), 100, "{name} missed an identical name");
+        }
+    }
+
+    #[test]
+    fn unknown_matcher_is_rejected() {
+        assert!(matcher_by_name("soundex", &default_prefixes()).is_err());
+    }
+
+    /// Scores every pair the same, standing in for a matcher that normalizes a name badly.
+    struct FixedMatcher(u8);
+
+    impl Matcher for FixedMatcher {
+        fn score(&self, _detection: &str, _moron: &Moron) -> u8 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn raw_form_wins_when_it_matches_better() {
+        let moron = Moron::new(String::from("Some Moron"), String::new());
+        let matcher = RawFormMatcher::new(Box::new(FixedMatcher(40)));
+        assert_eq!(matcher.score("Some Moron ", &moron), 100);
+    }
+
+    #[test]
+    fn normalized_form_wins_when_it_matches_better() {
+        let moron = Moron::new(String::from("Some Moron"), String::new());
+        let matcher = RawFormMatcher::new(Box::new(FixedMatcher(90)));
+        assert_eq!(matcher.score("Nobody", &moron), 90);
+    }
+
+    #[test]
+    fn ocr_confusions_fold_to_one_form() {
+        assert_eq!(fold_ocr_confusions("Sorne M0ron"), fold_ocr_confusions("Some Moron"));
+        assert_eq!(fold_ocr_confusions("I|1"), "lll");
+        assert_eq!(fold_ocr_confusions("clan"), "dan");
+    }
+
+    #[test]
+    fn confused_glyphs_cost_nothing_with_the_confusion_matcher() {
+        let moron = Moron::new(String::from("Some Moron"), String::new());
+        let ratio = || matcher_by_name("ratio", &default_prefixes()).unwrap();
+
+        assert!(ratio().score("General Sorne M0ron", &moron) < 100);
+        assert_eq!(ConfusionMatcher::new(ratio(), &default_prefixes()).score("General Sorne M0ron", &moron), 100);
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! It includes functions for:
 //! - Getting paths to various files and directories within the application directory.
-//! - Creating the application directory and blacklist file if they don't exist.
+//! - Creating the application directory, config, blacklist, and allowlist files if they don't exist.
 //! - Asynchronously downloading required RTEN (Real-Time Entity Recognition) models.
 //! - Asynchronously downloading files from URLs and saving them to specified paths.
 //!
@@ -26,11 +26,17 @@
 use std::error::Error;
 use std::io::Write;
 use std::path::PathBuf;
-use crate::blacklist;
+use std::time::Duration;
+use crate::{allowlist, blacklist, config};
+use crate::config::Banner;
+use crate::detector::{self, ScanSource};
 
 /// The URL to report bugs and issues to.
 pub(crate) const SUPPORT_URL: &str = "https://github.com/Hakxsorus/blitz/tree/master";
 
+/// The URL explaining the screen-capture portal that Wayland sessions need for window capture.
+pub(crate) const WAYLAND_CAPTURE_HELP_URL: &str = "https://wiki.archlinux.org/title/XDG_Desktop_Portal";
+
 /// The download URL for the OCRS detection model.
 const DETECTION_MODEL_URL: &str = "https://ocrs-models.s3-accelerate.amazonaws.com/text-detection.rten";
 
@@ -49,9 +55,66 @@ const RECOGNITION_MODEL_FILE_NAME: &str = "text-recognition.rten";
 /// The file name for the application banner.
 const BANNER_PNG_FILE_NAME: &str = "banner.png";
 
-/// Gets the [`PathBuf`] to the app directory.
+/// The environment variable that overrides the location of the app directory.
+const APP_DIR_ENV_VAR: &str = "BLITZ_APP_DIR";
+
+/// How long fetching a shared blacklist for syncing may take before giving up.
+const BLACKLIST_SYNC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many times a download is attempted before giving up.
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// How long to wait before retrying a failed download. The wait doubles after each attempt.
+const DOWNLOAD_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// A downloaded file the app depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Asset {
+    DetectionModel,
+    RecognitionModel,
+    Banner,
+}
+
+impl Asset {
+    /// Every asset, in the order they are listed in the maintenance panel.
+    pub(crate) const ALL: [Asset; 3] = [Asset::DetectionModel, Asset::RecognitionModel, Asset::Banner];
+
+    /// Gets the URL the asset is downloaded from.
+    fn url(&self) -> &'static str {
+        match self {
+            Asset::DetectionModel => DETECTION_MODEL_URL,
+            Asset::RecognitionModel => RECOGNITION_MODEL_URL,
+            Asset::Banner => BANNER_PNG_URL,
+        }
+    }
+
+    /// Gets the name of the asset's file in the app directory.
+    fn file_name(&self) -> &'static str {
+        match self {
+            Asset::DetectionModel => DETECTION_MODEL_FILE_NAME,
+            Asset::RecognitionModel => RECOGNITION_MODEL_FILE_NAME,
+            Asset::Banner => BANNER_PNG_FILE_NAME,
+        }
+    }
+}
+
+impl std::fmt::Display for Asset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Asset::DetectionModel => "Detection model",
+            Asset::RecognitionModel => "Recognition model",
+            Asset::Banner => "Banner",
+        })
+    }
+}
+
+/// Gets the [`PathBuf`] to the app directory: the directory named by the `BLITZ_APP_DIR`
+/// environment variable if it's set, otherwise `blitz-app` in the home directory.
 pub(crate) fn app_dir_path() -> Option<PathBuf> {
-    dirs::home_dir().map(|home_dir_path| home_dir_path.join("blitz-app"))
+    match std::env::var_os(APP_DIR_ENV_VAR).filter(|app_dir_path| !app_dir_path.is_empty()) {
+        Some(app_dir_path) => Some(PathBuf::from(app_dir_path)),
+        None => dirs::home_dir().map(|home_dir_path| home_dir_path.join("blitz-app")),
+    }
 }
 
 /// Gets the [`PathBuf`] to the init file.
@@ -64,14 +127,69 @@ pub(crate) fn blacklist_path() -> Option<PathBuf> {
     join_to_app_dir_path("blacklist.json")
 }
 
+/// Gets the [`PathBuf`] to the allowlist file.
+pub(crate) fn allowlist_path() -> Option<PathBuf> {
+    join_to_app_dir_path("allowlist.json")
+}
+
+/// Gets the [`PathBuf`] to the config file.
+pub(crate) fn config_path() -> Option<PathBuf> {
+    join_to_app_dir_path("config.json")
+}
+
+/// Gets the [`PathBuf`] to the log file.
+pub(crate) fn log_path() -> Option<PathBuf> {
+    join_to_app_dir_path("blitz.log")
+}
+
 /// Gets the [`PathBuf`] to the screenshot file.
 pub(crate) fn scrshot_path() -> Option<PathBuf> {
     join_to_app_dir_path("players.png")
 }
 
-/// Gets the [`PathBuf`] to a cropped screenshot file.
-pub(crate) fn player_scrshot_path(n: i32) -> Option<PathBuf> {
-    join_to_app_dir_path(format!("player-crop-{n}.png").as_str())
+/// Gets the [`PathBuf`] to the screenshot with the crop regions outlined on it.
+pub(crate) fn debug_scrshot_path() -> Option<PathBuf> {
+    join_to_app_dir_path("players-debug.png")
+}
+
+/// Gets the [`PathBuf`] the bundled sample lobby is written to for a demo scan.
+pub(crate) fn demo_scrshot_path() -> Option<PathBuf> {
+    join_to_app_dir_path("demo-lobby.png")
+}
+
+/// Gets the [`PathBuf`] to a cropped screenshot file. Crops are named after the source of the
+/// screenshot, so scans from different sources don't overwrite each other's crops.
+///
+/// # Arguments
+/// * `source` - Where the cropped screenshot came from.
+/// * `n` - The index of the player card.
+pub(crate) fn player_scrshot_path(source: ScanSource, n: i32) -> Option<PathBuf> {
+    join_to_app_dir_path(format!("player-crop-{source}-{n}.png").as_str())
+}
+
+/// Gets the [`PathBuf`] to a timestamped scan report file.
+pub(crate) fn report_path(timestamp: u64) -> Option<PathBuf> {
+    join_to_app_dir_path(format!("report-{timestamp}.json").as_str())
+}
+
+/// Gets the [`PathBuf`] to the scan history file.
+pub(crate) fn history_path() -> Option<PathBuf> {
+    join_to_app_dir_path("scan-history.jsonl")
+}
+
+/// Gets the [`PathBuf`] to a timestamped leaderboard export file.
+pub(crate) fn leaderboard_path(timestamp: u64) -> Option<PathBuf> {
+    join_to_app_dir_path(format!("leaderboard-{timestamp}.json").as_str())
+}
+
+/// Gets the [`PathBuf`] to the CSV scan log.
+pub(crate) fn scan_log_path() -> Option<PathBuf> {
+    join_to_app_dir_path("scan_log.csv")
+}
+
+/// Gets the [`PathBuf`] to the local file of anonymous match stats.
+pub(crate) fn telemetry_path() -> Option<PathBuf> {
+    join_to_app_dir_path("telemetry.jsonl")
 }
 
 /// Gets the [`PathBuf`] to the detection model file.
@@ -89,6 +207,46 @@ pub(crate) fn banner_path() -> Option<PathBuf> {
     join_to_app_dir_path(BANNER_PNG_FILE_NAME)
 }
 
+/// Gets the [`PathBuf`] to the banner image to show, or [`None`] if the banner is disabled.
+///
+/// # Arguments
+/// * `banner` - The configured banner.
+pub(crate) fn banner_source(banner: &Banner) -> Option<PathBuf> {
+    match banner {
+        Banner::Default => banner_path(),
+        Banner::Local(banner_path) => Some(banner_path.clone()),
+        Banner::Disabled => None,
+    }
+}
+
+/// Deletes the screenshot and player card crops a scan leaves in the app directory, including crops
+/// left by older versions. This is best-effort: files that can't be deleted are logged and left.
+pub(crate) fn cleanup_scan_artifacts() {
+    let Some(app_dir_path) = app_dir_path() else {
+        return;
+    };
+    let entries = match std::fs::read_dir(&app_dir_path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::warn!("Unable to list {} to clean up the scan files: {err}", app_dir_path.display());
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        let is_scan_artifact = file_name == "players.png"
+            || (file_name.starts_with("player-crop-") && file_name.ends_with(".png"));
+        if !is_scan_artifact {
+            continue;
+        }
+        if let Err(err) = std::fs::remove_file(entry.path()) {
+            log::warn!("Unable to delete {}: {err}", entry.path().display());
+        }
+    }
+}
+
 /// Joins a file name to the app directory path and returns it as a [`PathBuf`].
 ///
 /// # Arguments
@@ -118,10 +276,30 @@ pub(crate) fn create_init_file_if_not_exists() -> anyhow::Result<()> {
 pub(crate) fn create_blacklist_file_if_not_exists() -> anyhow::Result<()> {
     let blacklist_path = blacklist_path().ok_or(anyhow::anyhow!("Unable construct the blacklist file path"))?;
     if !blacklist_path.exists() {
-        let default_blacklist = blacklist::Blacklist::default();
-        let default_blacklist_json = serde_json::to_string_pretty(&default_blacklist)?;
-        let mut default_blacklist_file = std::fs::File::create(&blacklist_path)?;
-        default_blacklist_file.write_all(&default_blacklist_json.as_ref())?;
+        blacklist::Blacklist::default().save(&blacklist_path)?;
+    }
+
+    Ok(())
+}
+
+/// Creates the allowlist file (with default data) file if it does not exist to the app directory.
+pub(crate) fn create_allowlist_file_if_not_exists() -> anyhow::Result<()> {
+    let allowlist_path = allowlist_path().ok_or(anyhow::anyhow!("Unable construct the allowlist file path"))?;
+    if !allowlist_path.exists() {
+        let default_allowlist = allowlist::Allowlist::default();
+        let default_allowlist_json = serde_json::to_string_pretty(&default_allowlist)?;
+        let mut default_allowlist_file = std::fs::File::create(&allowlist_path)?;
+        default_allowlist_file.write_all(&default_allowlist_json.as_ref())?;
+    }
+
+    Ok(())
+}
+
+/// Creates the config file (with default settings) if it does not exist to the app directory.
+pub(crate) fn create_config_file_if_not_exists() -> anyhow::Result<()> {
+    let config_path = config_path().ok_or(anyhow::anyhow!("Unable construct the config file path"))?;
+    if !config_path.exists() {
+        config::Config::default().save(&config_path)?;
     }
 
     Ok(())
@@ -129,10 +307,31 @@ pub(crate) fn create_blacklist_file_if_not_exists() -> anyhow::Result<()> {
 
 /// Asynchronously downloads required RTEN (Real-Time Entity Recognition) models if they don't already
 /// exist locally. This function downloads both the detection and recognition models used for real-time
-/// entity recognition.
+/// entity recognition. Models on disk that fail to load, e.g. because an earlier download was cut
+/// short, are downloaded again.
 pub(crate) async fn download_rten_models() -> Result<(), Box<dyn Error>> {
-    download_if_not_exists(DETECTION_MODEL_URL, DETECTION_MODEL_FILE_NAME).await?;
-    download_if_not_exists(RECOGNITION_MODEL_URL, RECOGNITION_MODEL_FILE_NAME).await?;
+    download_model_if_invalid(Asset::DetectionModel).await?;
+    download_model_if_invalid(Asset::RecognitionModel).await?;
+    Ok(())
+}
+
+/// Asynchronously downloads an OCR model if it's missing or fails to load, then checks that the
+/// downloaded model loads.
+///
+/// # Arguments
+/// * `asset`: The model to check and download.
+async fn download_model_if_invalid(asset: Asset) -> anyhow::Result<()> {
+    let file_path = join_to_app_dir_path(asset.file_name())
+        .ok_or(anyhow::anyhow!("Unable to construct the download path."))?;
+    if file_path.exists() {
+        match detector::load_model(&asset.to_string(), &file_path) {
+            Ok(_) => return Ok(()),
+            Err(err) => log::warn!("{err} Downloading it again."),
+        }
+    }
+
+    redownload_file(asset.url(), &file_path).await?;
+    detector::load_model(&asset.to_string(), &file_path)?;
     Ok(())
 }
 
@@ -141,6 +340,37 @@ pub(crate) async fn download_banner_file() -> anyhow::Result<()> {
     Ok(download_if_not_exists(BANNER_PNG_URL, BANNER_PNG_FILE_NAME).await?)
 }
 
+/// Asynchronously deletes and downloads an asset again, for when it's suspected to be corrupt.
+///
+/// # Arguments
+/// * `asset`: The asset to download again.
+pub(crate) async fn redownload_asset(asset: Asset) -> anyhow::Result<()> {
+    let file_path = join_to_app_dir_path(asset.file_name())
+        .ok_or(anyhow::anyhow!("Unable to construct the download path."))?;
+    log::info!("Downloading the {} again from {}.", asset.to_string().to_lowercase(), asset.url());
+    redownload_file(asset.url(), &file_path).await?;
+    if asset != Asset::Banner {
+        detector::load_model(&asset.to_string(), &file_path)?;
+    }
+    Ok(())
+}
+
+/// Asynchronously deletes a file, if it exists, and downloads it again from the given URL.
+///
+/// # Arguments
+/// * `url`: A string slice representing the URL from which to download the file.
+/// * `path`: A [`PathBuf`] representing the path of the file to replace.
+async fn redownload_file(
+    url: &str,
+    path: &PathBuf
+) -> anyhow::Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+
+    download_file(url, path).await
+}
+
 /// Asynchronously downloads a file from the specified URL if it doesn't already exist locally to
 /// the app directory.
 ///
@@ -160,7 +390,30 @@ async fn download_if_not_exists(
 }
 
 
-/// Asynchronously downloads a file from the given URL and saves it to the specified path.
+/// Asynchronously fetches and parses a shared blacklist for syncing. Nothing is written, so an
+/// unreachable URL or an invalid blacklist leaves the local blacklist untouched.
+///
+/// # Arguments
+/// * `url`: A string slice representing the URL of the shared blacklist JSON file.
+pub(crate) async fn fetch_remote_blacklist(url: &str) -> anyhow::Result<blacklist::Blacklist> {
+    let client = reqwest::Client::builder()
+        .timeout(BLACKLIST_SYNC_TIMEOUT)
+        .build()?;
+    let content = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| anyhow::anyhow!("Unable to fetch the shared blacklist from {url}: {err}"))?
+        .text()
+        .await?;
+    blacklist::Blacklist::parse(&content)
+        .map_err(|err| anyhow::anyhow!("The shared blacklist at {url} isn't valid: {err}"))
+}
+
+/// Asynchronously downloads a file from the given URL and saves it to the specified path, retrying
+/// with exponential backoff so a flaky connection doesn't fail the download outright. Only the last
+/// attempt's error is returned.
 ///
 /// # Arguments
 /// * `url`: A string slice representing the URL from which to download the file.
@@ -169,14 +422,160 @@ async fn download_file(
     url: &str,
     path: &PathBuf
 ) -> anyhow::Result<()> {
-    let response = reqwest::get(url).await?;
-    if response.status().is_success() {
-        let mut file = std::fs::File::create(path)?;
-        let bytes = response.bytes().await?;
-        std::io::copy(&mut bytes.as_ref(), &mut file)?;
-    } else {
-        response.error_for_status()?;
+    let mut backoff = DOWNLOAD_INITIAL_BACKOFF;
+    for attempt in 1..DOWNLOAD_ATTEMPTS {
+        match try_download_file(url, path).await {
+            Ok(()) => return Ok(()),
+            Err(err) => {
+                log::warn!("Download attempt {attempt} of {url} failed: {err}. Retrying in {backoff:?}.");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+    }
+
+    try_download_file(url, path)
+        .await
+        .map_err(|err| anyhow::anyhow!("Unable to download {url} after {DOWNLOAD_ATTEMPTS} attempts: {err}"))
+}
+
+/// Asynchronously downloads a file from the given URL once and saves it to the specified path. The
+/// file is only created once the whole download has arrived, so an interrupted download never
+/// leaves a truncated file behind. Progress is printed as the download streams in, as the models
+/// are large and downloaded before the window opens.
+///
+/// # Arguments
+/// * `url`: A string slice representing the URL from which to download the file.
+/// * `path`: A [`PathBuf`] representing the path where the downloaded file should be saved.
+async fn try_download_file(
+    url: &str,
+    path: &PathBuf
+) -> anyhow::Result<()> {
+    let mut response = reqwest::get(url).await?.error_for_status()?;
+    let expected_length = response.content_length();
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    let mut bytes: Vec<u8> = Vec::with_capacity(expected_length.unwrap_or_default() as usize);
+    let mut reported_percent = None;
+    while let Some(chunk) = response.chunk().await? {
+        bytes.extend_from_slice(&chunk);
+        if let Some(percent) = download_percent(bytes.len() as u64, expected_length) {
+            // Only report each tenth, so the log isn't flooded with one line per chunk.
+            let tenth = percent / 10 * 10;
+            if reported_percent != Some(tenth) {
+                reported_percent = Some(tenth);
+                println!("Downloading {file_name}: {tenth}%");
+                log::info!("Downloading {file_name}: {tenth}%.");
+            }
+        }
+    }
+
+    if let Some(expected_length) = expected_length.filter(|expected_length| *expected_length != bytes.len() as u64) {
+        anyhow::bail!("The download was cut short ({} of {expected_length} bytes).", bytes.len());
     }
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(&bytes)?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Computes how much of a download has arrived, as a whole percentage, or [`None`] if the server
+/// didn't say how large it is.
+///
+/// # Arguments
+/// * `downloaded`: The number of bytes downloaded so far.
+/// * `expected_length`: The size of the download, from its `Content-Length`, if known.
+fn download_percent(downloaded: u64, expected_length: Option<u64>) -> Option<u64> {
+    expected_length
+        .filter(|expected_length| *expected_length > 0)
+        .map(|expected_length| (downloaded.saturating_mul(100) / expected_length).min(100))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Gets a path in the temporary directory that's unique to the test run, removing anything a
+    /// previous run left there.
+    pub(crate) fn temp_path(name: &str) -> PathBuf {
+        let temp_path = std::env::temp_dir().join(format!("blitz-test-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_file(&temp_path);
+        temp_path
+    }
+
+    /// Serves a single HTTP response with the given body on a local port, returning its URL.
+    fn serve_once(body: &'static [u8]) -> String {
+        use std::io::Read;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/asset", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // Read the request headers before responding, so the client sees the whole response.
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buf).unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..read]);
+            }
+            let header = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(body).unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn redownload_replaces_the_existing_file() {
+        let asset_path = temp_path("asset.rten");
+        std::fs::write(&asset_path, b"corrupt").unwrap();
+
+        redownload_file(&serve_once(b"fresh model"), &asset_path).await.unwrap();
+
+        let downloaded = std::fs::read(&asset_path).unwrap();
+        std::fs::remove_file(&asset_path).unwrap();
+        assert_eq!(downloaded, b"fresh model");
+    }
+
+    #[tokio::test]
+    async fn redownload_fetches_a_missing_file() {
+        let asset_path = temp_path("missing_asset.rten");
+
+        redownload_file(&serve_once(b"fresh model"), &asset_path).await.unwrap();
+
+        let downloaded = std::fs::read(&asset_path).unwrap();
+        std::fs::remove_file(&asset_path).unwrap();
+        assert_eq!(downloaded, b"fresh model");
+    }
+
+    #[test]
+    fn crop_paths_are_distinct_across_sources_and_cards() {
+        let sources = [ScanSource::Live, ScanSource::Window(0), ScanSource::Window(1), ScanSource::File, ScanSource::Demo];
+        let crop_paths: std::collections::HashSet<PathBuf> = sources
+            .into_iter()
+            .flat_map(|source| (0..6).map(move |n| player_scrshot_path(source, n).unwrap()))
+            .collect();
+        assert_eq!(crop_paths.len(), sources.len() * 6);
+    }
+
+    #[test]
+    fn banner_source_resolves_each_mode() {
+        assert_eq!(banner_source(&Banner::Default), banner_path());
+        assert_eq!(
+            banner_source(&Banner::Local(PathBuf::from("my-banner.gif"))),
+            Some(PathBuf::from("my-banner.gif"))
+        );
+        assert_eq!(banner_source(&Banner::Disabled), None);
+    }
+
+    #[test]
+    fn banner_modes_deserialize_from_the_config() {
+        let banner = |json: &str| serde_json::from_str::<Banner>(json).unwrap();
+        assert_eq!(banner(r#""default""#), Banner::Default);
+        assert_eq!(banner(r#"{ "local": "my-banner.gif" }"#), Banner::Local(PathBuf::from("my-banner.gif")));
+        assert_eq!(banner(r#""disabled""#), Banner::Disabled);
+    }
+}
@@ -0,0 +1,146 @@
+//! Module for recording the internals of a scan so missed or false matches can be diagnosed.
+//!
+//! A [`ScanReport`] captures, for every player card, a hash of the crop that was read, the raw and
+//! normalized OCR detections, and every score computed between a detection and a blacklisted
+//! username. The report is written as a timestamped JSON file in the app directory.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use crate::{detector, paths};
+//!
+//! fn export_report(config: &Config) -> anyhow::Result<()> {
+//!     let scan_result = detector::scan(config)?;
+//!     let report_path = paths::report_path(scan_result.report.timestamp)
+//!         .ok_or(anyhow::anyhow!("Unable to construct the report path."))?;
+//!     scan_result.report.save(&report_path)?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::hash::{DefaultHasher, Hasher};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+
+/// The internals of a single scan.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct ScanReport {
+    /// When the scan ran, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The internals of each player card, in card order.
+    pub cards: Vec<CardReport>,
+}
+
+/// The internals of a single player card within a scan.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct CardReport {
+    /// The index of the card within the player list.
+    pub card_index: usize,
+    /// The position of the RISK window the card was read from, when scanning every window.
+    pub window_index: usize,
+    /// The path the cropped card image was saved to, if crops are being saved for debugging.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crop_path: Option<PathBuf>,
+    /// A hash of the pixels of the cropped card image, to tell identical crops apart from changed
+    /// ones.
+    pub crop_hash: String,
+    /// The text chunks exactly as the OCR engine read them.
+    pub raw_detections: Vec<String>,
+    /// The text chunks after normalization.
+    pub normalized_detections: Vec<String>,
+    /// Every score computed between a detection on this card and a blacklisted username.
+    pub scores: Vec<MatchScore>,
+}
+
+/// A score computed between a detection and a blacklisted username.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct MatchScore {
+    /// The normalized detection that was scored.
+    pub detection: String,
+    /// The blacklisted username it was scored against.
+    pub username: String,
+    /// The similarity between the two.
+    pub similarity: u8,
+}
+
+impl ScanReport {
+    /// Creates a new, empty [`ScanReport`] stamped with the current time.
+    pub(crate) fn new() -> Self {
+        Self {
+            timestamp: unix_timestamp(),
+            cards: Vec::new(),
+        }
+    }
+
+    /// Serializes the [`ScanReport`] and writes it to the specified path.
+    ///
+    /// # Arguments
+    /// * `report_path` - A reference to the [`PathBuf`] to write the report to.
+    pub(crate) fn save(&self, report_path: &PathBuf) -> anyhow::Result<()> {
+        let report_json = serde_json::to_string_pretty(self)?;
+        let mut report_file = std::fs::File::create(report_path)?;
+        report_file.write_all(report_json.as_ref())?;
+        Ok(())
+    }
+}
+
+/// Gets the current time in seconds since the Unix epoch.
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Hashes some bytes, such as the pixels of an image, returning the hash as a hex string.
+///
+/// # Arguments
+/// * `bytes` - The bytes to hash.
+pub(crate) fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(bytes);
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_serializes_every_section() {
+        let mut report = ScanReport::new();
+        report.cards.push(CardReport {
+            card_index: 3,
+            window_index: 0,
+            crop_path: Some(PathBuf::from("player-crop-3.png")),
+            crop_hash: hash_bytes(&[1, 2, 3]),
+            raw_detections: vec![String::from("General Some Moron")],
+            normalized_detections: vec![String::from("somemoron")],
+            scores: vec![MatchScore {
+                detection: String::from("somemoron"),
+                username: String::from("Some Moron"),
+                similarity: 100,
+            }],
+        });
+
+        let report_json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(report_json["timestamp"], report.timestamp);
+        let card = &report_json["cards"][0];
+        assert_eq!(card["card_index"], 3);
+        assert_eq!(card["crop_path"], "player-crop-3.png");
+        assert_eq!(card["crop_hash"], hash_bytes(&[1, 2, 3]));
+        assert_eq!(card["raw_detections"][0], "General Some Moron");
+        assert_eq!(card["normalized_detections"][0], "somemoron");
+        assert_eq!(card["scores"][0]["detection"], "somemoron");
+        assert_eq!(card["scores"][0]["username"], "Some Moron");
+        assert_eq!(card["scores"][0]["similarity"], 100);
+    }
+
+    #[test]
+    fn hash_bytes_tells_different_crops_apart() {
+        assert_eq!(hash_bytes(&[1, 2, 3]), hash_bytes(&[1, 2, 3]));
+        assert_ne!(hash_bytes(&[1, 2, 3]), hash_bytes(&[3, 2, 1]));
+    }
+}
@@ -0,0 +1,327 @@
+//! Module for appending flagged scan results to a CSV file, for organizers who track morons in
+//! spreadsheets.
+//!
+//! Each scan appends one [`ScanLogRow`] per flagged result. The header is written only when the file
+//! is first created, so the file always opens cleanly as a single table. The same rows are written
+//! by the auto-export hook, in JSON or CSV, to a file of the user's choosing. The flagged results
+//! can also be rendered as a Markdown table for posting in forums, or as plain lines for warning
+//! teammates in chat.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use crate::{paths, scan_log};
+//!
+//! fn log_scan(scans: &[ScanInfo], timestamp: u64) -> anyhow::Result<()> {
+//!     let scan_log_path = paths::scan_log_path()
+//!         .ok_or(anyhow::anyhow!("Unable to construct the scan log path."))?;
+//!     scan_log::append(&scan_log_path, &scan_log::rows_for_scan(scans, 70, timestamp))
+//! }
+//! ```
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::Serialize;
+use crate::blacklist::Severity;
+use crate::config::{AutoExport, ExportFormat};
+use crate::detector::{self, ScanInfo};
+
+/// Serializes appends from within the app, so the header check and the write happen together.
+static SCAN_LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// A flagged result, as written to the scan log.
+#[derive(Serialize, Debug, Clone)]
+pub(crate) struct ScanLogRow {
+    /// When the scan ran, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The blacklisted username, or the detected name for a clan match.
+    pub username: String,
+    /// The similarity the result was flagged with.
+    pub similarity: u8,
+    /// Why the player (or their clan) is blacklisted.
+    pub reason: String,
+    /// The index of the player card the result was read from.
+    pub card_index: usize,
+}
+
+/// Creates the scan log rows for a scan: one per result at or above the threshold. Friendly players
+/// are not logged.
+///
+/// # Arguments
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+/// * `timestamp` - When the scan ran, in seconds since the Unix epoch.
+pub(crate) fn rows_for_scan(scans: &[ScanInfo], similarity_threshold: u8, timestamp: u64) -> Vec<ScanLogRow> {
+    detector::similar_scans(scans, similarity_threshold)
+        .into_iter()
+        .filter(|scan| scan.friendly.is_none())
+        .map(|scan| ScanLogRow {
+            timestamp,
+            username: scan.username.clone(),
+            similarity: scan.similarity,
+            reason: scan.reason.clone(),
+            card_index: scan.card_index,
+        })
+        .collect()
+}
+
+/// Appends rows to the scan log, writing the header first if the file is new or empty.
+///
+/// The rows are written with a single append so that another writer can't interleave with them.
+///
+/// # Arguments
+/// * `scan_log_path` - A reference to the [`PathBuf`] of the scan log.
+/// * `rows` - The rows to append.
+pub(crate) fn append(scan_log_path: &PathBuf, rows: &[ScanLogRow]) -> anyhow::Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let _guard = SCAN_LOG_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let mut scan_log_file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(scan_log_path)?;
+    let write_header = scan_log_file.metadata()?.len() == 0;
+
+    scan_log_file.write_all(&to_csv(rows, write_header)?)?;
+    Ok(())
+}
+
+/// Writes the rows of a scan to the auto-export file in the configured format, appending to or
+/// overwriting it as configured.
+///
+/// # Arguments
+/// * `auto_export` - Where and how to write the rows.
+/// * `rows` - The flagged results of the scan.
+pub(crate) fn auto_export(auto_export: &AutoExport, rows: &[ScanLogRow]) -> anyhow::Result<()> {
+    match (auto_export.format, auto_export.append) {
+        (ExportFormat::Csv, true) => append(&auto_export.path, rows),
+        (ExportFormat::Csv, false) => Ok(std::fs::write(&auto_export.path, to_csv(rows, true)?)?),
+        (ExportFormat::Json, true) => {
+            let mut lines = String::new();
+            for row in rows {
+                lines.push_str(&serde_json::to_string(row)?);
+                lines.push('\n');
+            }
+
+            let _guard = SCAN_LOG_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let mut export_file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&auto_export.path)?;
+            Ok(export_file.write_all(lines.as_bytes())?)
+        }
+        (ExportFormat::Json, false) => Ok(std::fs::write(&auto_export.path, serde_json::to_string_pretty(rows)?)?),
+    }
+}
+
+/// Checks that the auto-export file can be written, creating it if it doesn't exist yet.
+///
+/// # Arguments
+/// * `export_path` - A reference to the [`PathBuf`] of the auto-export file.
+pub(crate) fn validate_writable(export_path: &PathBuf) -> anyhow::Result<()> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(export_path)
+        .map_err(|err| anyhow::anyhow!("Unable to write to {}: {err}", export_path.display()))?;
+    Ok(())
+}
+
+/// Serializes rows as CSV.
+///
+/// # Arguments
+/// * `rows` - The rows to serialize.
+/// * `header` - Whether to write the header row first.
+fn to_csv(rows: &[ScanLogRow], header: bool) -> anyhow::Result<Vec<u8>> {
+    let mut csv_writer = csv::WriterBuilder::new()
+        .has_headers(header)
+        .from_writer(Vec::new());
+    for row in rows {
+        csv_writer.serialize(row)?;
+    }
+    csv_writer.into_inner().map_err(|err| anyhow::anyhow!(err.to_string()))
+}
+
+/// Renders the flagged results of a scan as a Markdown table of username, similarity, severity, and
+/// reason. Friendly players are left out.
+///
+/// # Arguments
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+pub(crate) fn to_markdown(scans: &[ScanInfo], similarity_threshold: u8) -> String {
+    let mut markdown = String::from("| Username | Similarity | Severity | Reason |\n| --- | --- | --- | --- |\n");
+    for scan in detector::similar_scans(scans, similarity_threshold)
+        .into_iter()
+        .filter(|scan| scan.friendly.is_none())
+    {
+        let severity = match scan.severity {
+            Severity::Low => "Low",
+            Severity::Medium => "Medium",
+            Severity::High => "High",
+        };
+        markdown.push_str(&format!(
+            "| {} | {}% | {severity} | {} |\n",
+            escape_markdown_cell(&scan.username),
+            scan.similarity,
+            escape_markdown_cell(&scan.reason),
+        ));
+    }
+    markdown
+}
+
+/// Renders the flagged results of a scan as plain text for pasting into chat, one line per result,
+/// e.g. "MORON: SomeMoron (85%) — teammate backstabber". Friendly players are left out, and a scan
+/// that flagged no one reads "No morons detected.".
+///
+/// # Arguments
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+pub(crate) fn to_chat_text(scans: &[ScanInfo], similarity_threshold: u8) -> String {
+    let lines: Vec<String> = detector::similar_scans(scans, similarity_threshold)
+        .into_iter()
+        .filter(|scan| scan.friendly.is_none())
+        .map(|scan| {
+            let label = match (&scan.clan, &scan.pattern) {
+                (Some(clan), _) => format!("CLAN [{clan}]"),
+                (None, Some(_)) => String::from("PATTERN"),
+                (None, None) => String::from("MORON"),
+            };
+            let reason = scan.reason.trim();
+            if reason.is_empty() {
+                format!("{label}: {} ({}%)", scan.username, scan.similarity)
+            } else {
+                format!("{label}: {} ({}%) — {reason}", scan.username, scan.similarity)
+            }
+        })
+        .collect();
+
+    if lines.is_empty() {
+        return String::from("No morons detected.");
+    }
+    lines.join("\n")
+}
+
+/// Escapes text for a Markdown table cell: pipes would end the cell and newlines the row.
+///
+/// # Arguments
+/// * `cell` - The text of the cell.
+fn escape_markdown_cell(cell: &str) -> String {
+    cell.trim()
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\r', '\n'], "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::tests::scan_info;
+    use crate::paths::tests::temp_path;
+
+    fn row(username: &str, timestamp: u64) -> ScanLogRow {
+        ScanLogRow {
+            timestamp,
+            username: username.to_string(),
+            similarity: 90,
+            reason: String::from("Spam, and worse"),
+            card_index: 2,
+        }
+    }
+
+    #[test]
+    fn append_keeps_earlier_rows_and_writes_the_header_once() {
+        let scan_log_path = temp_path("scan_log.csv");
+        append(&scan_log_path, &[row("First", 100)]).unwrap();
+        append(&scan_log_path, &[row("Second", 200), row("Third", 200)]).unwrap();
+
+        let scan_log = std::fs::read_to_string(&scan_log_path).unwrap();
+        std::fs::remove_file(&scan_log_path).unwrap();
+        assert_eq!(scan_log, concat!(
+            "timestamp,username,similarity,reason,card_index\n",
+            "100,First,90,\"Spam, and worse\",2\n",
+            "200,Second,90,\"Spam, and worse\",2\n",
+            "200,Third,90,\"Spam, and worse\",2\n",
+        ));
+    }
+
+    #[test]
+    fn append_of_nothing_creates_no_file() {
+        let scan_log_path = temp_path("empty_scan_log.csv");
+        append(&scan_log_path, &[]).unwrap();
+        assert!(!scan_log_path.exists());
+    }
+
+    #[test]
+    fn auto_export_overwrites_json_with_the_latest_scan() {
+        let export_path = temp_path("auto_export.json");
+        let export = AutoExport { path: export_path.clone(), format: ExportFormat::Json, append: false };
+        auto_export(&export, &[row("First", 100)]).unwrap();
+        auto_export(&export, &[row("Second", 200)]).unwrap();
+
+        let exported = std::fs::read_to_string(&export_path).unwrap();
+        std::fs::remove_file(&export_path).unwrap();
+        let exported: serde_json::Value = serde_json::from_str(&exported).unwrap();
+        assert_eq!(exported.as_array().unwrap().len(), 1);
+        assert_eq!(exported[0]["username"], "Second");
+    }
+
+    #[test]
+    fn auto_export_appends_json_lines() {
+        let export_path = temp_path("auto_export.jsonl");
+        let export = AutoExport { path: export_path.clone(), format: ExportFormat::Json, append: true };
+        auto_export(&export, &[row("First", 100)]).unwrap();
+        auto_export(&export, &[row("Second", 200)]).unwrap();
+
+        let exported = std::fs::read_to_string(&export_path).unwrap();
+        std::fs::remove_file(&export_path).unwrap();
+        let usernames: Vec<String> = exported
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["username"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(usernames, vec!["First", "Second"]);
+    }
+
+    #[test]
+    fn auto_export_overwrites_csv_with_a_header() {
+        let export_path = temp_path("auto_export.csv");
+        let export = AutoExport { path: export_path.clone(), format: ExportFormat::Csv, append: false };
+        auto_export(&export, &[row("First", 100)]).unwrap();
+        auto_export(&export, &[row("Second", 200)]).unwrap();
+
+        let exported = std::fs::read_to_string(&export_path).unwrap();
+        std::fs::remove_file(&export_path).unwrap();
+        assert_eq!(exported, concat!(
+            "timestamp,username,similarity,reason,card_index\n",
+            "200,Second,90,\"Spam, and worse\",2\n",
+        ));
+    }
+
+    #[test]
+    fn markdown_table_escapes_cells_and_leaves_out_friendly_players() {
+        let scans = vec![
+            ScanInfo {
+                reason: String::from("Teams | then\nbetrays"),
+                severity: Severity::High,
+                ..scan_info("Some|Moron", 90)
+            },
+            ScanInfo { friendly: Some(String::from("Moran")), ..scan_info("Moran", 95) },
+            scan_info("Weak Match", 40),
+        ];
+
+        assert_eq!(to_markdown(&scans, 70), concat!(
+            "| Username | Similarity | Severity | Reason |\n",
+            "| --- | --- | --- | --- |\n",
+            "| Some\\|Moron | 90% | High | Teams \\| then<br>betrays |\n",
+        ));
+    }
+
+    #[test]
+    fn markdown_table_of_a_clean_scan_is_just_the_header() {
+        assert_eq!(to_markdown(&[], 70), "| Username | Similarity | Severity | Reason |\n| --- | --- | --- | --- |\n");
+    }
+}
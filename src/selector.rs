@@ -0,0 +1,256 @@
+//! A widget that displays a capture and lets the user drag out a region of it.
+//!
+//! The [`RegionSelector`] draws the capture scaled to fit its bounds, outlines the currently
+//! configured region, and, while the left mouse button is held, outlines the region being dragged.
+//! On release it publishes the selection as a [`Region`] of fractions of the capture, so the
+//! selection is independent of how large the capture is drawn.
+
+use iced::advanced::image::{self as image_renderer, FilterMethod};
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer::{self, Quad};
+use iced::advanced::widget::{tree, Tree, Widget};
+use iced::advanced::{mouse, Clipboard, Shell};
+use iced::widget::image::Handle;
+use iced::{event, Border, Color, Element, Event, Length, Point, Rectangle, Size};
+use crate::crop::Region;
+
+/// A widget that displays a capture and lets the user drag out a region of it.
+pub(crate) struct RegionSelector<'a, Message> {
+    /// The capture to display.
+    handle: Handle,
+    /// The dimensions of the capture, in pixels.
+    image_size: Size,
+    /// The currently configured region, outlined on the capture.
+    highlight: Option<Region>,
+    /// The height of the widget.
+    height: f32,
+    /// Produces the message published when a region is selected.
+    on_select: Box<dyn Fn(Region) -> Message + 'a>,
+}
+
+/// The drag in progress, in coordinates relative to the drawn capture.
+#[derive(Debug, Default)]
+struct State {
+    /// Where the drag started.
+    drag_from: Option<Point>,
+    /// Where the cursor is now.
+    drag_to: Option<Point>,
+}
+
+impl<'a, Message> RegionSelector<'a, Message> {
+    /// Creates a new [`RegionSelector`].
+    ///
+    /// # Arguments
+    /// * `handle` - The capture to display.
+    /// * `image_width` - The width of the capture, in pixels.
+    /// * `image_height` - The height of the capture, in pixels.
+    /// * `on_select` - Produces the message published when a region is selected.
+    pub(crate) fn new(
+        handle: Handle,
+        image_width: u32,
+        image_height: u32,
+        on_select: impl Fn(Region) -> Message + 'a,
+    ) -> Self {
+        Self {
+            handle,
+            image_size: Size::new(image_width as f32, image_height as f32),
+            highlight: None,
+            height: 150.0,
+            on_select: Box::new(on_select),
+        }
+    }
+
+    /// Sets the region outlined on the capture.
+    pub(crate) fn highlight(mut self, highlight: Option<Region>) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    /// Sets the height of the widget.
+    pub(crate) fn height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Computes where the capture is drawn: scaled to fit the bounds and centered.
+    fn drawing_bounds(&self, bounds: Rectangle) -> Rectangle {
+        if self.image_size.width <= 0.0 || self.image_size.height <= 0.0 {
+            return bounds;
+        }
+
+        let scale = (bounds.width / self.image_size.width).min(bounds.height / self.image_size.height);
+        let width = self.image_size.width * scale;
+        let height = self.image_size.height * scale;
+
+        Rectangle {
+            x: bounds.x + (bounds.width - width) / 2.0,
+            y: bounds.y + (bounds.height - height) / 2.0,
+            width,
+            height,
+        }
+    }
+}
+
+impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer> for RegionSelector<'a, Message>
+where
+    Renderer: image_renderer::Renderer<Handle = Handle>,
+{
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn size(&self) -> Size<Length> {
+        Size::new(Length::Fill, Length::Fixed(self.height))
+    }
+
+    fn layout(
+        &self,
+        _tree: &mut Tree,
+        _renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        layout::Node::new(limits.resolve(Length::Fill, Length::Fixed(self.height), Size::ZERO))
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        _theme: &Theme,
+        _style: &renderer::Style,
+        layout: Layout<'_>,
+        _cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        let state = tree.state.downcast_ref::<State>();
+        let drawing_bounds = self.drawing_bounds(layout.bounds());
+
+        image_renderer::Renderer::draw(renderer, self.handle.clone(), FilterMethod::Linear, drawing_bounds);
+
+        if let Some(highlight) = &self.highlight {
+            let highlight_bounds = Rectangle {
+                x: drawing_bounds.x + highlight.x * drawing_bounds.width,
+                y: drawing_bounds.y + highlight.y * drawing_bounds.height,
+                width: highlight.width * drawing_bounds.width,
+                height: highlight.height * drawing_bounds.height,
+            };
+            draw_outline(renderer, highlight_bounds, Color::from_rgb8(0, 200, 83));
+        }
+
+        if let (Some(from), Some(to)) = (state.drag_from, state.drag_to) {
+            let drag_bounds = Rectangle {
+                x: drawing_bounds.x + from.x.min(to.x),
+                y: drawing_bounds.y + from.y.min(to.y),
+                width: (from.x - to.x).abs(),
+                height: (from.y - to.y).abs(),
+            };
+            draw_outline(renderer, drag_bounds, Color::from_rgb8(255, 0, 0));
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        _viewport: &Rectangle,
+    ) -> event::Status {
+        let state = tree.state.downcast_mut::<State>();
+        let drawing_bounds = self.drawing_bounds(layout.bounds());
+
+        // Cursor positions relative to the drawn capture, clamped to its edges.
+        let relative_position = |position: Point| Point::new(
+            (position.x - drawing_bounds.x).clamp(0.0, drawing_bounds.width),
+            (position.y - drawing_bounds.y).clamp(0.0, drawing_bounds.height),
+        );
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if let Some(position) = cursor.position_over(drawing_bounds) {
+                    let position = relative_position(position);
+                    state.drag_from = Some(position);
+                    state.drag_to = Some(position);
+                    return event::Status::Captured;
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                if state.drag_from.is_some() {
+                    state.drag_to = Some(relative_position(position));
+                    return event::Status::Captured;
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if let (Some(from), Some(to)) = (state.drag_from.take(), state.drag_to.take()) {
+                    if drawing_bounds.width > 0.0 && drawing_bounds.height > 0.0 {
+                        let region = Region::from_corners(
+                            (from.x / drawing_bounds.width, from.y / drawing_bounds.height),
+                            (to.x / drawing_bounds.width, to.y / drawing_bounds.height),
+                        );
+                        // Ignore clicks that didn't drag out an area.
+                        if region.width > 0.0 && region.height > 0.0 {
+                            shell.publish((self.on_select)(region));
+                        }
+                    }
+                    return event::Status::Captured;
+                }
+            }
+            _ => {}
+        }
+
+        event::Status::Ignored
+    }
+
+    fn mouse_interaction(
+        &self,
+        _tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        if cursor.is_over(self.drawing_bounds(layout.bounds())) {
+            mouse::Interaction::Crosshair
+        } else {
+            mouse::Interaction::Idle
+        }
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<RegionSelector<'a, Message>> for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Renderer: image_renderer::Renderer<Handle = Handle> + 'a,
+{
+    fn from(region_selector: RegionSelector<'a, Message>) -> Self {
+        Element::new(region_selector)
+    }
+}
+
+/// Draws the outline of a rectangle.
+///
+/// # Arguments
+/// * `renderer` - The renderer to draw with.
+/// * `bounds` - The rectangle to outline.
+/// * `color` - The color of the outline.
+fn draw_outline<Renderer: renderer::Renderer>(renderer: &mut Renderer, bounds: Rectangle, color: Color) {
+    renderer.fill_quad(
+        Quad {
+            bounds,
+            border: Border {
+                color,
+                width: 2.0,
+                radius: 0.0.into(),
+            },
+            ..Quad::default()
+        },
+        Color::TRANSPARENT,
+    );
+}
@@ -0,0 +1,93 @@
+//! Module for the statistics of the current session, kept in memory until the app closes or the
+//! user resets them.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use crate::session::SessionStats;
+//!
+//! fn summarise(scan_result: &ScanResult) -> String {
+//!     let mut session_stats = SessionStats::default();
+//!     session_stats.record(&scan_result, 70);
+//!     format!("{} scans, {} flagged", session_stats.scans, session_stats.morons_flagged)
+//! }
+//! ```
+
+use std::time::Duration;
+use crate::detector::{self, ScanResult};
+
+/// The statistics of the scans run this session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct SessionStats {
+    /// The number of scans run.
+    pub scans: usize,
+    /// The number of occupied player cards read, over all scans.
+    pub players_seen: usize,
+    /// The number of flagged, non-friendly results, over all scans.
+    pub morons_flagged: usize,
+    /// The time spent cropping, reading, and matching, over all scans.
+    pub total_scan_time: Duration,
+}
+
+impl SessionStats {
+    /// Adds a scan to the statistics.
+    ///
+    /// # Arguments
+    /// * `scan_result` - The outcome of the scan.
+    /// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+    pub(crate) fn record(&mut self, scan_result: &ScanResult, similarity_threshold: u8) {
+        self.scans += 1;
+        self.players_seen += scan_result.occupied_cards;
+        self.morons_flagged += detector::similar_scans(&scan_result.scans, similarity_threshold)
+            .iter()
+            .filter(|scan| scan.friendly.is_none())
+            .count();
+        self.total_scan_time += scan_result.timings.crop + scan_result.timings.ocr + scan_result.timings.matching;
+    }
+
+    /// Gets the mean time a scan took, or [`None`] if nothing has been scanned.
+    pub(crate) fn average_scan_time(&self) -> Option<Duration> {
+        (self.scans > 0).then(|| self.total_scan_time / self.scans as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::tests::scan_info;
+    use crate::detector::{ScanInfo, ScanTimings};
+    use crate::report::ScanReport;
+
+    fn scan_result(scans: Vec<ScanInfo>, occupied_cards: usize, ocr_millis: u64) -> ScanResult {
+        ScanResult {
+            scans,
+            report: ScanReport { timestamp: 0, cards: Vec::new() },
+            timings: ScanTimings { ocr: Duration::from_millis(ocr_millis), ..ScanTimings::default() },
+            window: None,
+            warning: None,
+            occupied_cards,
+            detected_names: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn session_stats_accumulate_over_scans() {
+        let mut session_stats = SessionStats::default();
+        assert_eq!(session_stats.average_scan_time(), None);
+
+        session_stats.record(&scan_result(vec![scan_info("Moron", 90), scan_info("Weak", 40)], 6, 300), 70);
+        session_stats.record(&scan_result(vec![], 4, 100), 70);
+        session_stats.record(&scan_result(vec![
+            scan_info("Moron", 85),
+            ScanInfo { friendly: Some(String::from("Moran")), ..scan_info("Moron", 95) },
+        ], 5, 200), 70);
+
+        assert_eq!(session_stats, SessionStats {
+            scans: 3,
+            players_seen: 15,
+            morons_flagged: 2,
+            total_scan_time: Duration::from_millis(600),
+        });
+        assert_eq!(session_stats.average_scan_time(), Some(Duration::from_millis(200)));
+    }
+}
@@ -0,0 +1,148 @@
+//! Module for the opt-in, anonymous match-quality telemetry.
+//!
+//! Telemetry is off unless the config has a `telemetry` section. When on, each scan is reduced to a
+//! [`MatchStats`]: counts and a histogram of similarity scores, and nothing else. The stats never
+//! hold usernames, detections, reasons, or anything else from the blacklist or the lobby, so they
+//! say how well matching performs without saying who was matched. Stats are appended to a local
+//! file and, only if an upload URL is configured, posted to it.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use crate::{paths, telemetry};
+//!
+//! fn record(scans: &[ScanInfo], timestamp: u64) -> anyhow::Result<()> {
+//!     let telemetry_path = paths::telemetry_path()
+//!         .ok_or(anyhow::anyhow!("Unable to construct the telemetry path."))?;
+//!     telemetry::append(&telemetry_path, &telemetry::aggregate(scans, 70, timestamp))
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::PathBuf;
+use serde::Serialize;
+use crate::detector::ScanInfo;
+
+/// The number of buckets in the similarity histogram: one per ten points, plus one for 100.
+const HISTOGRAM_BUCKETS: usize = 11;
+
+/// The anonymous statistics recorded for a scan.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MatchStats {
+    /// When the scan ran, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The similarity threshold the scan was flagged with.
+    pub similarity_threshold: u8,
+    /// The number of player cards that produced any results.
+    pub card_count: usize,
+    /// The number of results scored.
+    pub result_count: usize,
+    /// The number of results at or above the threshold that weren't friendly.
+    pub flagged_count: usize,
+    /// The number of results at or above the threshold that were allowlisted.
+    pub friendly_count: usize,
+    /// How many results fell in each ten-point band of similarity, with 100 counted on its own.
+    pub histogram: [usize; HISTOGRAM_BUCKETS],
+}
+
+/// Reduces the results of a scan to anonymous statistics. Only scores, counts, and card indices are
+/// read from the results, so nothing identifying can reach the stats.
+///
+/// # Arguments
+/// * `scans` - The results of the scan.
+/// * `similarity_threshold` - The minimum similarity for a result to count as flagged.
+/// * `timestamp` - When the scan ran, in seconds since the Unix epoch.
+pub(crate) fn aggregate(scans: &[ScanInfo], similarity_threshold: u8, timestamp: u64) -> MatchStats {
+    let mut histogram = [0; HISTOGRAM_BUCKETS];
+    let mut flagged_count = 0;
+    let mut friendly_count = 0;
+    for scan in scans {
+        histogram[(scan.similarity as usize / 10).min(HISTOGRAM_BUCKETS - 1)] += 1;
+        if scan.similarity >= similarity_threshold {
+            match scan.friendly {
+                Some(_) => friendly_count += 1,
+                None => flagged_count += 1,
+            }
+        }
+    }
+
+    MatchStats {
+        timestamp,
+        similarity_threshold,
+        card_count: scans.iter().map(|scan| scan.card_index).collect::<BTreeSet<_>>().len(),
+        result_count: scans.len(),
+        flagged_count,
+        friendly_count,
+        histogram,
+    }
+}
+
+/// Appends the stats for a scan to the local telemetry file, one JSON object per line.
+///
+/// # Arguments
+/// * `path` - The path of the telemetry file.
+/// * `stats` - The stats to append.
+pub(crate) fn append(path: &PathBuf, stats: &MatchStats) -> anyhow::Result<()> {
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(stats)?)?;
+    Ok(())
+}
+
+/// Asynchronously posts the stats for a scan, as JSON, to the configured upload URL.
+///
+/// # Arguments
+/// * `upload_url` - The URL to post the stats to.
+/// * `stats` - The stats to post.
+pub(crate) async fn upload(upload_url: String, stats: MatchStats) -> anyhow::Result<()> {
+    reqwest::Client::new()
+        .post(upload_url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(&stats)?)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::tests::scan_info;
+
+    #[test]
+    fn stats_never_carry_names_or_blacklist_contents() {
+        let scans = vec![
+            ScanInfo {
+                clan: Some(String::from("SecretClan")),
+                reason: String::from("SecretReason"),
+                evidence_url: Some(String::from("https://example.com/SecretEvidence")),
+                note: Some(String::from("SecretNote")),
+                ..scan_info("SecretMoron", 95)
+            },
+            ScanInfo {
+                friendly: Some(String::from("SecretFriend")),
+                pattern: Some(String::from("Secret*")),
+                card_index: 1,
+                ..scan_info("SecretPlayer", 80)
+            },
+        ];
+
+        let payload = serde_json::to_string(&aggregate(&scans, 70, 1_700_000_000)).unwrap();
+        assert!(!payload.contains("Secret"), "{payload}");
+    }
+
+    #[test]
+    fn stats_count_and_bucket_the_results() {
+        let scans = vec![
+            scan_info("Moron", 100),
+            ScanInfo { card_index: 1, ..scan_info("Moron", 95) },
+            ScanInfo { friendly: Some(String::from("Moran")), card_index: 2, ..scan_info("Moron", 72) },
+            ScanInfo { card_index: 2, ..scan_info("Moron", 30) },
+        ];
+
+        let stats = aggregate(&scans, 70, 0);
+        assert_eq!((stats.card_count, stats.result_count, stats.flagged_count, stats.friendly_count), (3, 4, 2, 1));
+        assert_eq!(stats.histogram, [0, 0, 0, 1, 0, 0, 0, 1, 0, 1, 1]);
+    }
+}
@@ -0,0 +1,181 @@
+//! Module for the headless `verify-blacklist` subcommand, which checks a blacklist before it's
+//! shared.
+//!
+//! The subcommand loads a blacklist file without modifying it and reports every problem it finds,
+//! split into errors (entries that are broken) and warnings (entries that work but will likely cause
+//! false positives). It exits with an error if there are any errors.
+//!
+//! # Example
+//!
+//! ```text
+//! blitz-app verify-blacklist community-blacklist.json
+//! ```
+
+use std::path::PathBuf;
+use crate::blacklist::{self, Blacklist, MatchMode};
+use crate::config::Config;
+use crate::detector;
+
+/// The similarity at or above which two usernames are reported as near-duplicates.
+const NEAR_DUPLICATE_SIMILARITY: u8 = 90;
+
+/// The problems found in a blacklist.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct ValidationSummary {
+    /// The number of morons and clans checked.
+    pub entry_count: usize,
+    /// Problems that break an entry.
+    pub errors: Vec<String>,
+    /// Problems that will likely cause false positives.
+    pub warnings: Vec<String>,
+}
+
+/// Checks the contents of a blacklist file for broken entries, generic usernames, and
+/// near-duplicates.
+///
+/// # Arguments
+/// * `content` - The contents of the blacklist file.
+/// * `min_username_length` - The shortest username that isn't reported as too short.
+pub(crate) fn verify(content: &str, min_username_length: usize) -> ValidationSummary {
+    let mut summary = ValidationSummary::default();
+    let blacklist: Blacklist = match serde_json::from_str(content) {
+        Ok(blacklist) => blacklist,
+        Err(err) => {
+            summary.errors.push(format!("The blacklist isn't valid: {}", blacklist::describe_json_error(&err)));
+            return summary;
+        }
+    };
+    summary.entry_count = blacklist.morons.len() + blacklist.clans.len();
+
+    for (index, moron) in blacklist.morons.iter().enumerate() {
+        let entry = format!("Entry {} ('{}')", index + 1, moron.username);
+        if moron.username.trim().is_empty() {
+            summary.errors.push(format!("Entry {} has no username.", index + 1));
+            continue;
+        }
+        if moron.reason.trim().is_empty() {
+            summary.warnings.push(format!("{entry} has no reason."));
+        }
+        if let Some(evidence_url) = &moron.evidence_url {
+            if let Err(err) = blacklist::validate_evidence_url(evidence_url) {
+                summary.errors.push(format!("{entry}: {err}"));
+            }
+        }
+        if moron.mode == MatchMode::Fuzzy && moron.pattern.is_none() {
+            if let Some(generic_name) = blacklist::generic_name(&moron.username, min_username_length) {
+                summary.warnings.push(format!("{entry} {generic_name}. Consider using exact mode for it."));
+            }
+        }
+    }
+
+    for (index, clan) in blacklist.clans.iter().enumerate() {
+        if clan.tag.trim().is_empty() {
+            summary.errors.push(format!("Clan {} has no tag.", index + 1));
+        }
+    }
+
+    // Compare every pair of usernames the way the scan would see them.
+    let normalized: Vec<String> = blacklist.morons
+        .iter()
+        .map(|moron| detector::normalize(&moron.username, &blacklist.prefixes))
+        .collect();
+    for first in 0..normalized.len() {
+        for second in first + 1..normalized.len() {
+            if normalized[first].is_empty() || normalized[second].is_empty() {
+                continue;
+            }
+
+            let (first_username, second_username) = (&blacklist.morons[first].username, &blacklist.morons[second].username);
+            if normalized[first] == normalized[second] {
+                summary.errors.push(format!(
+                    "Entries {} and {} ('{first_username}' and '{second_username}') are duplicates.",
+                    first + 1,
+                    second + 1
+                ));
+                continue;
+            }
+
+            let similarity = fuzzywuzzy::fuzz::ratio(&normalized[first], &normalized[second]);
+            if similarity >= NEAR_DUPLICATE_SIMILARITY {
+                summary.warnings.push(format!(
+                    "Entries {} and {} ('{first_username}' and '{second_username}') are near-duplicates ({similarity}%).",
+                    first + 1,
+                    second + 1
+                ));
+            }
+        }
+    }
+
+    summary
+}
+
+/// Runs the `verify-blacklist` subcommand, printing the validation summary to stdout.
+///
+/// # Arguments
+/// * `args` - The arguments after the subcommand name.
+/// * `config` - A reference to the [`Config`] holding the minimum username length.
+pub(crate) fn run(args: &[String], config: &Config) -> anyhow::Result<()> {
+    let blacklist_path = match args {
+        [blacklist_path] => PathBuf::from(blacklist_path),
+        _ => anyhow::bail!("Usage: verify-blacklist <path>"),
+    };
+    let content = std::fs::read_to_string(&blacklist_path)
+        .map_err(|err| anyhow::anyhow!("Unable to read {}: {err}", blacklist_path.display()))?;
+
+    let summary = verify(&content, config.min_username_length);
+    println!("Checked {} entries in {}", summary.entry_count, blacklist_path.display());
+    for error in summary.errors.iter() {
+        println!("error: {error}");
+    }
+    for warning in summary.warnings.iter() {
+        println!("warning: {warning}");
+    }
+    println!("{} errors, {} warnings", summary.errors.len(), summary.warnings.len());
+
+    if !summary.errors.is_empty() {
+        anyhow::bail!("{} is not valid.", blacklist_path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_reports_errors_and_warnings_per_entry() {
+        let summary = verify(r#"{
+            "morons": [
+                { "username": "Some Moron", "reason": "Spam" },
+                { "username": "Bob", "reason": "" },
+                { "username": " ", "reason": "Spam" },
+                { "username": "Linked Moron", "reason": "Spam", "evidence_url": "example.com/clip" },
+                { "username": "some moron", "reason": "Spam" },
+                { "username": "Some Morons", "reason": "Spam" }
+            ],
+            "clans": [{ "tag": "", "reason": "Spam" }]
+        }"#, 4);
+
+        assert_eq!(summary.entry_count, 7);
+        assert_eq!(summary.errors.len(), 4, "{:?}", summary.errors);
+        assert!(summary.errors.iter().any(|err| err.starts_with("Entry 3 has no username")));
+        assert!(summary.errors.iter().any(|err| err.starts_with("Entry 4 ('Linked Moron')")));
+        assert!(summary.errors.iter().any(|err| err.starts_with("Entries 1 and 5") && err.ends_with("are duplicates.")));
+        assert!(summary.errors.iter().any(|err| err.starts_with("Clan 1 has no tag")));
+        assert_eq!(summary.warnings, vec![
+            String::from("Entry 2 ('Bob') has no reason."),
+            String::from("Entry 2 ('Bob') is too short. Consider using exact mode for it."),
+            String::from("Entries 1 and 6 ('Some Moron' and 'Some Morons') are near-duplicates (95%)."),
+            String::from("Entries 5 and 6 ('some moron' and 'Some Morons') are near-duplicates (95%)."),
+        ]);
+    }
+
+    #[test]
+    fn verify_reports_invalid_json_without_checking_entries() {
+        let summary = verify(r#"{ "morons": [ }"#, 4);
+        assert_eq!(summary.entry_count, 0);
+        assert_eq!(summary.errors.len(), 1);
+        assert!(summary.errors[0].starts_with("The blacklist isn't valid"));
+        assert!(summary.warnings.is_empty());
+    }
+}
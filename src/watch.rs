@@ -0,0 +1,179 @@
+//! Module for watch mode, which scans automatically when the RISK window appears or is restored.
+//!
+//! Neither `xcap` nor the OS give us focus events portably, so the [`Watcher`] observes the cheap
+//! window list (no capture) once a tick and turns changes in the RISK window's [`WindowState`] into
+//! [`WindowEvent`]s. Only an event triggers a scan, and scans are debounced so a window that
+//! flickers doesn't scan repeatedly. Where the window list isn't available at all (e.g. on some
+//! Wayland compositors), the watcher falls back to scanning on a fixed interval.
+
+use std::time::{Duration, Instant};
+use crate::detector;
+use crate::windows::WindowIdentity;
+
+/// How often the window list is observed while watching.
+pub(crate) const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What the RISK window is doing, as far as the window list tells us.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WindowState {
+    /// There is no RISK window.
+    Missing,
+    /// The RISK window is minimized.
+    Minimized,
+    /// The RISK window is open and not minimized.
+    Visible,
+    /// The window list couldn't be read, so the state is unknown.
+    Unavailable,
+}
+
+/// A change in the RISK window worth scanning for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WindowEvent {
+    /// The RISK window was opened.
+    Appeared,
+    /// The RISK window was restored from being minimized.
+    Restored,
+}
+
+/// What the watcher wants done on a tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WatchAction {
+    /// Scan the RISK lobby.
+    Scan,
+    /// Do nothing until the next tick.
+    Wait,
+}
+
+/// Turns observed window states into scans.
+#[derive(Debug, Clone)]
+pub(crate) struct Watcher {
+    /// The state observed on the previous tick, or [`None`] before the first tick.
+    previous: Option<WindowState>,
+    /// When the watcher last asked for a scan.
+    last_scan: Option<Instant>,
+    /// The minimum time between scans triggered by events.
+    debounce: Duration,
+    /// The time between scans when the window list is unavailable.
+    fallback_interval: Duration,
+}
+
+impl Watcher {
+    /// Creates a new [`Watcher`]. The first tick only records the window state, so turning watch mode
+    /// on doesn't scan a window that was already open.
+    ///
+    /// # Arguments
+    /// * `debounce` - The minimum time between scans triggered by events.
+    /// * `fallback_interval` - The time between scans when the window list is unavailable.
+    pub(crate) fn new(debounce: Duration, fallback_interval: Duration) -> Self {
+        Self {
+            previous: None,
+            last_scan: None,
+            debounce,
+            fallback_interval,
+        }
+    }
+
+    /// Records the window state observed on this tick and decides whether to scan.
+    ///
+    /// # Arguments
+    /// * `current` - The window state observed on this tick.
+    /// * `now` - When the state was observed.
+    pub(crate) fn on_tick(&mut self, current: WindowState, now: Instant) -> WatchAction {
+        let previous = self.previous.replace(current);
+        let since_last_scan = self.last_scan.map(|last_scan| now.saturating_duration_since(last_scan));
+
+        let action = if current == WindowState::Unavailable {
+            match since_last_scan {
+                Some(since_last_scan) if since_last_scan < self.fallback_interval => WatchAction::Wait,
+                _ => WatchAction::Scan,
+            }
+        } else {
+            match (previous.and_then(|previous| window_event(previous, current)), since_last_scan) {
+                (Some(_), Some(since_last_scan)) if since_last_scan < self.debounce => WatchAction::Wait,
+                (Some(_), _) => WatchAction::Scan,
+                (None, _) => WatchAction::Wait,
+            }
+        };
+
+        if action == WatchAction::Scan {
+            self.last_scan = Some(now);
+        }
+        action
+    }
+}
+
+/// Maps a change in window state to the event it represents, if any.
+///
+/// # Arguments
+/// * `previous` - The state observed on the previous tick.
+/// * `current` - The state observed on this tick.
+pub(crate) fn window_event(previous: WindowState, current: WindowState) -> Option<WindowEvent> {
+    match (previous, current) {
+        (WindowState::Missing, WindowState::Visible) => Some(WindowEvent::Appeared),
+        (WindowState::Minimized, WindowState::Visible) => Some(WindowEvent::Restored),
+        _ => None,
+    }
+}
+
+/// Observes the state of the RISK window from the window list, without capturing it.
+///
+/// # Arguments
+/// * `remembered` - The window to prefer, if one was remembered.
+pub(crate) fn observe(remembered: Option<&WindowIdentity>) -> WindowState {
+    match detector::find_risk_window(remembered) {
+        Ok(Some(risk_window)) if risk_window.is_minimized() => WindowState::Minimized,
+        Ok(Some(_)) => WindowState::Visible,
+        Ok(None) => WindowState::Missing,
+        Err(err) => {
+            log::debug!("Unable to list windows for watch mode: {err}");
+            WindowState::Unavailable
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEBOUNCE: Duration = Duration::from_secs(10);
+    const FALLBACK_INTERVAL: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn window_event_maps_appearing_and_restoring() {
+        assert_eq!(window_event(WindowState::Missing, WindowState::Visible), Some(WindowEvent::Appeared));
+        assert_eq!(window_event(WindowState::Minimized, WindowState::Visible), Some(WindowEvent::Restored));
+        assert_eq!(window_event(WindowState::Visible, WindowState::Visible), None);
+        assert_eq!(window_event(WindowState::Visible, WindowState::Minimized), None);
+        assert_eq!(window_event(WindowState::Visible, WindowState::Missing), None);
+    }
+
+    #[test]
+    fn first_tick_never_scans_an_open_window() {
+        let mut watcher = Watcher::new(DEBOUNCE, FALLBACK_INTERVAL);
+        assert_eq!(watcher.on_tick(WindowState::Visible, Instant::now()), WatchAction::Wait);
+    }
+
+    #[test]
+    fn events_scan_and_are_debounced() {
+        let mut watcher = Watcher::new(DEBOUNCE, FALLBACK_INTERVAL);
+        let start = Instant::now();
+        watcher.on_tick(WindowState::Missing, start);
+        assert_eq!(watcher.on_tick(WindowState::Visible, start + Duration::from_secs(1)), WatchAction::Scan);
+
+        // The window flickers straight back, within the debounce.
+        watcher.on_tick(WindowState::Minimized, start + Duration::from_secs(2));
+        assert_eq!(watcher.on_tick(WindowState::Visible, start + Duration::from_secs(3)), WatchAction::Wait);
+
+        watcher.on_tick(WindowState::Minimized, start + Duration::from_secs(20));
+        assert_eq!(watcher.on_tick(WindowState::Visible, start + Duration::from_secs(21)), WatchAction::Scan);
+    }
+
+    #[test]
+    fn unavailable_window_list_falls_back_to_the_interval() {
+        let mut watcher = Watcher::new(DEBOUNCE, FALLBACK_INTERVAL);
+        let start = Instant::now();
+        assert_eq!(watcher.on_tick(WindowState::Unavailable, start), WatchAction::Scan);
+        assert_eq!(watcher.on_tick(WindowState::Unavailable, start + Duration::from_secs(10)), WatchAction::Wait);
+        assert_eq!(watcher.on_tick(WindowState::Unavailable, start + Duration::from_secs(30)), WatchAction::Scan);
+    }
+}
@@ -0,0 +1,178 @@
+//! Module for picking the RISK window out of the open windows.
+//!
+//! The RISK window is found by its exact title. Localized clients and launchers that append a version
+//! retitle it, so if no window has the exact title, any window whose title or application name
+//! contains "RISK" (ignoring case) is picked instead, except for this app's own window.
+//!
+//! Several windows can be titled "RISK" (e.g. the launcher and the game, or two clients). The
+//! window a scan used can be remembered as a [`WindowIdentity`], so later scans, even after a
+//! restart, prefer the same window. Process IDs change between launches, so the identity is the
+//! window's title, its application name, and its position among the windows sharing that title.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use crate::windows::{self, WindowCandidate};
+//!
+//! fn pick(windows: &[xcap::Window], remembered: Option<&WindowIdentity>) -> Option<&xcap::Window> {
+//!     let candidates: Vec<WindowCandidate> = windows.iter().map(WindowCandidate::from_window).collect();
+//!     windows::resolve(&candidates, remembered).map(|index| &windows[index])
+//! }
+//! ```
+
+use serde::{Serialize, Deserialize};
+
+/// The title of the RISK window.
+pub(crate) const RISK_WINDOW_TITLE: &str = "RISK";
+
+/// The title of this app's own window, which mentions RISK but is never scanned.
+pub(crate) const APP_WINDOW_TITLE: &str = "Blitz - The RISK Moron Detector";
+
+/// A window, remembered in a way that survives restarts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WindowIdentity {
+    /// The title of the window.
+    pub title: String,
+    /// The name of the application that owns the window.
+    pub app_name: String,
+    /// The position of the window among the open windows sharing its title.
+    pub index: usize,
+}
+
+/// The details of an open window that identify it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WindowCandidate {
+    /// The title of the window.
+    pub title: String,
+    /// The name of the application that owns the window.
+    pub app_name: String,
+}
+
+impl WindowCandidate {
+    /// Reads the identifying details of an open window.
+    ///
+    /// # Arguments
+    /// * `window` - The open window.
+    pub(crate) fn from_window(window: &xcap::Window) -> Self {
+        Self {
+            title: window.title().to_string(),
+            app_name: window.app_name().to_string(),
+        }
+    }
+
+    /// Checks whether the window's title or application name contains "RISK", ignoring case. This
+    /// app's own window is never a match.
+    pub(crate) fn mentions_risk(&self) -> bool {
+        if self.title == APP_WINDOW_TITLE {
+            return false;
+        }
+        let risk = RISK_WINDOW_TITLE.to_lowercase();
+        self.title.to_lowercase().contains(&risk) || self.app_name.to_lowercase().contains(&risk)
+    }
+}
+
+/// Picks the RISK window from the open windows, returning its index. The remembered window is
+/// preferred: first the window matching it exactly, then one from the same application. Otherwise
+/// the first RISK window is picked.
+///
+/// # Arguments
+/// * `candidates` - The open windows, in the order they're listed.
+/// * `remembered` - The window to prefer, if one was remembered.
+pub(crate) fn resolve(candidates: &[WindowCandidate], remembered: Option<&WindowIdentity>) -> Option<usize> {
+    let matches = resolve_all(candidates);
+
+    if let Some(remembered) = remembered {
+        let same_title: Vec<usize> = matches
+            .iter()
+            .copied()
+            .filter(|index| candidates[*index].title == remembered.title)
+            .collect();
+        let exact = same_title
+            .get(remembered.index)
+            .filter(|index| candidates[**index].app_name == remembered.app_name);
+        let same_app = || matches.iter().find(|index| candidates[**index].app_name == remembered.app_name);
+        if let Some(index) = exact.or_else(same_app) {
+            return Some(*index);
+        }
+    }
+
+    matches.first().copied()
+}
+
+/// Picks every RISK window from the open windows, returning their indices in order. Windows titled
+/// exactly "RISK" are preferred. Only if there are none are windows that merely mention RISK picked.
+///
+/// # Arguments
+/// * `candidates` - The open windows, in the order they're listed.
+pub(crate) fn resolve_all(candidates: &[WindowCandidate]) -> Vec<usize> {
+    let matching = |is_match: fn(&WindowCandidate) -> bool| -> Vec<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| is_match(candidate))
+            .map(|(index, _)| index)
+            .collect()
+    };
+
+    let exact = matching(|candidate| candidate.title == RISK_WINDOW_TITLE);
+    if !exact.is_empty() {
+        return exact;
+    }
+    matching(WindowCandidate::mentions_risk)
+}
+
+/// Creates the identity of a picked window, for remembering it.
+///
+/// # Arguments
+/// * `candidates` - The open windows, in the order they're listed.
+/// * `picked` - The index of the picked window.
+pub(crate) fn identify(candidates: &[WindowCandidate], picked: usize) -> WindowIdentity {
+    let candidate = &candidates[picked];
+    WindowIdentity {
+        title: candidate.title.clone(),
+        app_name: candidate.app_name.clone(),
+        index: candidates[..picked]
+            .iter()
+            .filter(|other| other.title == candidate.title)
+            .count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(title: &str, app_name: &str) -> WindowCandidate {
+        WindowCandidate { title: title.to_string(), app_name: app_name.to_string() }
+    }
+
+    #[test]
+    fn remembered_window_is_preferred_over_the_first() {
+        let candidates = vec![
+            candidate("RISK", "Browser"),
+            candidate("Notes", "Editor"),
+            candidate("RISK", "RISK Global Domination"),
+            candidate("RISK", "RISK Global Domination"),
+        ];
+        let remembered = identify(&candidates, 3);
+        assert_eq!(remembered, WindowIdentity { title: String::from("RISK"), app_name: String::from("RISK Global Domination"), index: 2 });
+
+        assert_eq!(resolve(&candidates, None), Some(0));
+        assert_eq!(resolve(&candidates, Some(&remembered)), Some(3));
+    }
+
+    #[test]
+    fn remembered_window_falls_back_to_its_application() {
+        let remembered = WindowIdentity { title: String::from("RISK"), app_name: String::from("RISK Global Domination"), index: 1 };
+        // The remembered window has closed, leaving another from the same application.
+        let candidates = vec![candidate("RISK", "Browser"), candidate("RISK", "RISK Global Domination")];
+        assert_eq!(resolve(&candidates, Some(&remembered)), Some(1));
+    }
+
+    #[test]
+    fn exact_risk_title_is_preferred_over_a_mention() {
+        let candidates = vec![candidate("RISK strategy guide", "Browser"), candidate("RISK", "RISK Global Domination")];
+        assert_eq!(resolve_all(&candidates), vec![1]);
+        assert_eq!(resolve_all(&[candidate("RISK strategy guide", "Browser")]), vec![0]);
+    }
+}